@@ -89,5 +89,25 @@ data class PttMessage(
                 type = "HEARTBEAT",
                 clientId = clientId,
             )
+
+        fun undo(clientId: String) =
+            PttMessage(
+                type = "UNDO",
+                clientId = clientId,
+            )
+
+        fun confirmHeld(
+            clientId: String,
+            heldId: Long,
+            accept: Boolean,
+        ) = PttMessage(
+            type = "CONFIRM_HELD",
+            clientId = clientId,
+            payload =
+                buildJsonObject {
+                    put("heldId", heldId)
+                    put("accept", accept)
+                },
+        )
     }
 }
@@ -56,6 +56,23 @@ class MessageTest {
         assertTrue(str.contains(""""type":"HEARTBEAT""""))
     }
 
+    @Test
+    fun `serialize UNDO message`() {
+        val msg = PttMessage.undo("phone-01")
+        val str = json.encodeToString(PttMessage.serializer(), msg)
+        assertTrue(str.contains(""""type":"UNDO""""))
+        assertTrue(str.contains(""""clientId":"phone-01""""))
+    }
+
+    @Test
+    fun `serialize CONFIRM_HELD message`() {
+        val msg = PttMessage.confirmHeld("phone-01", 3, accept = true)
+        val str = json.encodeToString(PttMessage.serializer(), msg)
+        assertTrue(str.contains(""""type":"CONFIRM_HELD""""))
+        assertTrue(str.contains(""""heldId":3"""))
+        assertTrue(str.contains(""""accept":true"""))
+    }
+
     @Test
     fun `deserialize ACK message`() {
         val raw = """{"type":"ACK","clientId":"phone-01","payload":{"ackType":"HELLO"}}"""
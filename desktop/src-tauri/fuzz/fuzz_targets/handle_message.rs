@@ -0,0 +1,58 @@
+//! Hostile message sequences through the full `handle_message` path: each
+//! input line that parses becomes an inbound message on one of three
+//! connections, replayed without delays against a throwaway server context.
+
+#![no_main]
+
+use std::sync::Arc;
+
+use desktop_lib::capture::{self, CaptureRecord, Direction, Timing};
+use desktop_lib::client_registry::ClientRegistry;
+use desktop_lib::injection::TextInjector;
+use desktop_lib::protocol::parse_message;
+use desktop_lib::ws_server::{LogEmitter, ServerContext};
+use libfuzzer_sys::fuzz_target;
+
+/// Accepts everything, so injection never short-circuits a path
+struct NullInjector;
+
+impl TextInjector for NullInjector {
+    fn inject(&self, _text: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn erase(&self, _count: usize) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn press(&self, _action: desktop_lib::injection::KeyAction) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    let records: Vec<CaptureRecord> = input
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            Some(CaptureRecord {
+                at: 0,
+                connection: (index % 3) as u64,
+                direction: Direction::In,
+                message: parse_message(line).ok()?,
+            })
+        })
+        .collect();
+    if records.is_empty() {
+        return;
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    let registry = Arc::new(tokio::sync::Mutex::new(ClientRegistry::new(30)));
+    let ctx = ServerContext::new(registry, Arc::new(NullInjector), Arc::new(LogEmitter));
+    runtime.block_on(capture::replay(&records, &ctx, Timing::Immediate));
+});
@@ -0,0 +1,18 @@
+//! Arbitrary bytes into `parse_message`: it must return an error, never panic,
+//! and whatever it accepts must serialize and parse back to the same message.
+
+#![no_main]
+
+use desktop_lib::protocol::{parse_message, serialize_message};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(message) = parse_message(input) else {
+        return;
+    };
+    let json = serialize_message(&message).expect("parsed message serializes");
+    assert_eq!(parse_message(&json).expect("serialized message parses"), message);
+});
@@ -0,0 +1,107 @@
+//! Exports dictation history from the app's history database.
+//!
+//! Usage: ptt-export --db PATH [--format md|jsonl|csv|srt|vtt] [--from YYYY-MM-DD]
+//!                   [--to YYYY-MM-DD] [--client ID] [--search TEXT] [--all] [--out FILE]
+//! Dates are local and inclusive; without `--out` the export goes to stdout.
+//! Notes and subtitles hold only injected FINALs unless `--all` is given.
+
+use std::path::PathBuf;
+
+use chrono::{Local, NaiveDate, TimeZone};
+use desktop_lib::export::{self, ExportFormat};
+use desktop_lib::history::{History, HistorySelection};
+
+const USAGE: &str = "Usage: ptt-export --db PATH [--format md|jsonl|csv|srt|vtt] \
+     [--from YYYY-MM-DD] [--to YYYY-MM-DD] [--client ID] [--search TEXT] [--all] [--out FILE]";
+
+struct Args {
+    db: PathBuf,
+    format: ExportFormat,
+    selection: HistorySelection,
+    all_outcomes: bool,
+    out: Option<PathBuf>,
+}
+
+/// Unix ms of local midnight on `value`, plus `days`
+fn local_day_ms(value: &str, days: i64) -> Result<i64, String> {
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|e| format!("Invalid date {value}: {e}"))?;
+    let midnight = (date + chrono::Days::new(days as u64))
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|t| t.timestamp_millis())
+        .ok_or_else(|| format!("Invalid local date {value}"))
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut db = None;
+    let mut format = ExportFormat::Markdown;
+    let mut selection = HistorySelection::default();
+    let mut all_outcomes = false;
+    let mut out = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--db" => db = Some(PathBuf::from(value()?)),
+            "--format" => format = ExportFormat::from_name(&value()?)?,
+            "--from" => selection.from = Some(local_day_ms(&value()?, 0)?),
+            // Inclusive: everything before the next midnight
+            "--to" => selection.to = Some(local_day_ms(&value()?, 1)? - 1),
+            "--client" => selection.client_id = Some(value()?),
+            "--search" => selection.text = Some(value()?),
+            "--all" => all_outcomes = true,
+            "--out" => out = Some(PathBuf::from(value()?)),
+            other => return Err(format!("Unknown argument: {other}")),
+        }
+    }
+    Ok(Args {
+        db: db.ok_or("--db is required")?,
+        format,
+        selection,
+        all_outcomes,
+        out,
+    })
+}
+
+fn run(args: Args) -> Result<(), String> {
+    // Opening would create an empty database
+    if !args.db.exists() {
+        return Err(format!("No history database at {}", args.db.display()));
+    }
+    let history = History::open(&args.db)?;
+    let entries = history.select(&args.selection)?;
+    let rendered = export::render(
+        &entries,
+        args.format,
+        *Local::now().offset(),
+        args.all_outcomes,
+    );
+    match args.out {
+        Some(path) => {
+            std::fs::write(&path, rendered).map_err(|e| format!("Write {}: {e}", path.display()))
+        }
+        None => {
+            print!("{rendered}");
+            Ok(())
+        }
+    }
+}
+
+fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = run(args) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
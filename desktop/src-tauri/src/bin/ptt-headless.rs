@@ -0,0 +1,97 @@
+//! Runs the dictation server without the Tauri window, delivering every FINAL
+//! to a sink — for scripts, note files or a terminal on a headless box.
+//!
+//! Usage: ptt-headless [--port 9876] [--sink SPEC]... [--capture PATH]
+//! Repeat `--sink` to deliver every FINAL to several sinks at once; the first
+//! one decides whether a FINAL counts as delivered.
+//! `--capture` records the protocol traffic for `ptt-replay`.
+
+use std::sync::Arc;
+
+use desktop_lib::client_registry::ClientRegistry;
+use desktop_lib::injection::fanout::FanOutInjector;
+use desktop_lib::injection::{sink, TextInjector};
+use desktop_lib::ws_server::{self, LogEmitter, ServerContext};
+
+const USAGE: &str = "Usage: ptt-headless [--port 9876] \
+     [--sink stdout|file:PATH|pipe:PATH|webhook:URL]... [--capture PATH]";
+
+struct Args {
+    port: u16,
+    sink_specs: Vec<String>,
+    capture: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut port = 9876;
+    let mut sink_specs = Vec::new();
+    let mut capture = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => {
+                let value = args.next().ok_or("--port needs a value")?;
+                port = value
+                    .parse()
+                    .map_err(|e| format!("Invalid port {value}: {e}"))?;
+            }
+            "--sink" => sink_specs.push(args.next().ok_or("--sink needs a value")?),
+            "--capture" => capture = Some(args.next().ok_or("--capture needs a value")?),
+            other => return Err(format!("Unknown argument: {other}")),
+        }
+    }
+    if sink_specs.is_empty() {
+        sink_specs.push("stdout".to_string());
+    }
+    Ok(Args {
+        port,
+        sink_specs,
+        capture,
+    })
+}
+
+fn build_injector(specs: &[String]) -> Result<Arc<dyn TextInjector>, String> {
+    let mut sinks = specs
+        .iter()
+        .map(|spec| Ok((spec.clone(), sink::from_spec(spec)?)))
+        .collect::<Result<Vec<_>, String>>()?;
+    if sinks.len() == 1 {
+        return Ok(sinks.remove(0).1);
+    }
+    Ok(Arc::new(FanOutInjector::new(sinks)))
+}
+
+#[tokio::main]
+async fn main() {
+    // Logs go to stderr so a stdout sink stays clean for piping
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    let injector = match build_injector(&args.sink_specs) {
+        Ok(injector) => injector,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    };
+
+    let registry = Arc::new(tokio::sync::Mutex::new(ClientRegistry::new(15)));
+    let ctx = ServerContext::new(registry, injector, Arc::new(LogEmitter));
+    if let Some(path) = &args.capture {
+        if let Err(e) = ctx.capture.start(path) {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    }
+    if let Err(e) = ws_server::serve(args.port, Arc::new(ctx)).await {
+        log::error!("WebSocket server error: {e}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,95 @@
+//! Replays a protocol capture (from `ptt-headless --capture` or the app's
+//! capture setting) through the server's message handling, delivering the
+//! FINALs to a sink instead of a phone-driven session.
+//!
+//! Usage: ptt-replay CAPTURE [--speed N | --instant] [--sink SPEC]
+//! `--speed 10` replays ten times faster than recorded; the default is the
+//! original timing. Exits with 1 when responses differ from the capture.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use desktop_lib::capture::{self, Timing};
+use desktop_lib::client_registry::ClientRegistry;
+use desktop_lib::injection::sink;
+use desktop_lib::ws_server::{LogEmitter, ServerContext};
+
+const USAGE: &str = "Usage: ptt-replay CAPTURE [--speed N | --instant] \
+     [--sink stdout|file:PATH|pipe:PATH|webhook:URL]";
+
+struct Args {
+    capture: PathBuf,
+    timing: Timing,
+    sink_spec: String,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut capture = None;
+    let mut timing = Timing::Original;
+    let mut sink_spec = "stdout".to_string();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--speed" => {
+                let value = args.next().ok_or("--speed needs a value")?;
+                let factor: f64 = value
+                    .parse()
+                    .map_err(|e| format!("Invalid speed {value}: {e}"))?;
+                if factor <= 0.0 {
+                    return Err(format!("Invalid speed {value}: must be positive"));
+                }
+                timing = Timing::Accelerated(factor);
+            }
+            "--instant" => timing = Timing::Immediate,
+            "--sink" => sink_spec = args.next().ok_or("--sink needs a value")?,
+            other if other.starts_with("--") => return Err(format!("Unknown argument: {other}")),
+            path => capture = Some(PathBuf::from(path)),
+        }
+    }
+    Ok(Args {
+        capture: capture.ok_or("No capture file given")?,
+        timing,
+        sink_spec,
+    })
+}
+
+#[tokio::main]
+async fn main() {
+    // Logs go to stderr so a stdout sink stays clean for piping
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    let loaded = capture::read_capture(&args.capture)
+        .and_then(|records| Ok((records, sink::from_spec(&args.sink_spec)?)));
+    let (records, injector) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    };
+
+    let registry = Arc::new(tokio::sync::Mutex::new(ClientRegistry::new(15)));
+    let ctx = ServerContext::new(registry, injector, Arc::new(LogEmitter));
+    let report = capture::replay(&records, &ctx, args.timing).await;
+
+    eprintln!(
+        "Replayed {} messages, {} responses, {} mismatches",
+        report.inbound,
+        report.responses,
+        report.mismatches.len()
+    );
+    for mismatch in &report.mismatches {
+        eprintln!("  {mismatch}");
+    }
+    if !report.mismatches.is_empty() {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,42 @@
+//! Regenerates the protocol's JSON Schema (`docs/protocol.schema.json`) and
+//! the frontend's TypeScript types (`src/types/protocol.generated.ts`) from
+//! the serde types in `protocol.rs`.
+//!
+//! Usage: ptt-schema [--check]
+//! `--check` writes nothing and exits with 1 when a file is out of date.
+
+use desktop_lib::schema;
+
+const USAGE: &str = "Usage: ptt-schema [--check]";
+
+fn main() {
+    let check = match std::env::args().nth(1).as_deref() {
+        None => false,
+        Some("--check") => true,
+        Some(other) => {
+            eprintln!("Unknown argument: {other}");
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    let mut stale = 0;
+    for (path, content) in schema::generated_files() {
+        let current = std::fs::read_to_string(&path).unwrap_or_default();
+        if current == content {
+            continue;
+        }
+        if check {
+            eprintln!("Out of date: {}", path.display());
+            stale += 1;
+        } else if let Err(e) = std::fs::write(&path, content) {
+            eprintln!("Write {}: {e}", path.display());
+            std::process::exit(1);
+        } else {
+            println!("Wrote {}", path.display());
+        }
+    }
+    if stale > 0 {
+        std::process::exit(1);
+    }
+}
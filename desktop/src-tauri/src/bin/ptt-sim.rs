@@ -0,0 +1,130 @@
+//! Simulated phone: connects to the dictation server and speaks the
+//! protocol, so the desktop can be tested without the Android app.
+//!
+//! Usage: ptt-sim [--url ws://127.0.0.1:9876] [--script PATH|-] [--client-id ID]
+//!                [--device MODEL] [--engine NAME] [--interval MS] [--heartbeat MS]
+//!                [--confidence 0.9] [--reorder P] [--duplicate P] [--disconnect P]
+//!                [--seed N]
+//! The script is read from stdin without `--script` (or with `-`): one
+//! utterance per line, `#` comments, and `@pause MS`, `@disconnect`, `@undo`.
+
+use std::io::Read;
+use std::time::Duration;
+
+use desktop_lib::simulator::{self, SimOptions};
+
+const USAGE: &str = "Usage: ptt-sim [--url ws://127.0.0.1:9876] [--script PATH|-] \
+     [--client-id ID] [--device MODEL] [--engine NAME] [--interval MS] [--heartbeat MS] \
+     [--confidence 0.9] [--reorder P] [--duplicate P] [--disconnect P] [--seed N]";
+
+struct Args {
+    url: String,
+    script: String,
+    options: SimOptions,
+}
+
+fn parse<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e| format!("Invalid {flag} {value}: {e}"))
+}
+
+/// Milliseconds that must not be zero (a zero heartbeat would never pause)
+fn interval(flag: &str, value: &str) -> Result<Duration, String> {
+    match parse(flag, value)? {
+        0 => Err(format!("Invalid {flag} {value}: must be at least 1")),
+        ms => Ok(Duration::from_millis(ms)),
+    }
+}
+
+fn probability(flag: &str, value: &str) -> Result<f64, String> {
+    let p: f64 = parse(flag, value)?;
+    if !(0.0..=1.0).contains(&p) {
+        return Err(format!("Invalid {flag} {value}: must be between 0 and 1"));
+    }
+    Ok(p)
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut url = "ws://127.0.0.1:9876".to_string();
+    let mut script = "-".to_string();
+    let mut options = SimOptions::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(format!("{arg} needs a value"))?;
+        match arg.as_str() {
+            "--url" => url = value,
+            "--script" => script = value,
+            "--client-id" => options.client_id = value,
+            "--device" => options.device_model = value,
+            "--engine" => options.engine = value,
+            "--interval" => options.partial_interval = Duration::from_millis(parse(&arg, &value)?),
+            "--heartbeat" => options.heartbeat = interval(&arg, &value)?,
+            "--confidence" => options.confidence = probability(&arg, &value)?,
+            "--reorder" => options.reorder = probability(&arg, &value)?,
+            "--duplicate" => options.duplicate = probability(&arg, &value)?,
+            "--disconnect" => options.disconnect = probability(&arg, &value)?,
+            "--seed" => options.seed = parse(&arg, &value)?,
+            other => return Err(format!("Unknown argument: {other}")),
+        }
+    }
+    Ok(Args {
+        url,
+        script,
+        options,
+    })
+}
+
+fn read_script(source: &str) -> Result<String, String> {
+    if source == "-" {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| format!("Read stdin: {e}"))?;
+        return Ok(text);
+    }
+    std::fs::read_to_string(source).map_err(|e| format!("Read {source}: {e}"))
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    let script = match read_script(&args.script).and_then(|text| simulator::parse_script(&text)) {
+        Ok(script) => script,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    };
+
+    match simulator::run(&args.url, &script, &args.options).await {
+        Ok(report) => {
+            println!(
+                "Sent {} FINALs and {} PARTIALs ({} interrupted, {} reconnects)",
+                report.finals.len(),
+                report.partials,
+                report.interrupted,
+                report.reconnects
+            );
+            for ack in report.acks.iter().filter(|a| a.ack_type == "FINAL") {
+                println!("FINAL {}", ack.status.as_deref().unwrap_or("acknowledged"));
+            }
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::protocol;
+use crate::ws_server::{self, ServerContext};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Phone → desktop
+    In,
+    /// Desktop → phone
+    Out,
+}
+
+/// One line of a capture file (JSON Lines)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureRecord {
+    /// Unix ms when the message was received or sent
+    pub at: i64,
+    /// Numbers the WebSocket connections of one server run
+    pub connection: u64,
+    pub direction: Direction,
+    pub message: protocol::Message,
+}
+
+struct ActiveCapture {
+    path: PathBuf,
+    file: File,
+}
+
+/// Opt-in recorder for protocol traffic. Captures contain dictated text in
+/// clear; they're meant for reproducing bugs, not for keeping.
+pub struct CaptureRecorder {
+    active: Mutex<Option<ActiveCapture>>,
+    connections: AtomicU64,
+}
+
+impl CaptureRecorder {
+    /// Not recording until `start`
+    pub fn new() -> Self {
+        Self {
+            active: Mutex::new(None),
+            connections: AtomicU64::new(0),
+        }
+    }
+
+    /// Start appending to `path`, replacing any capture in progress.
+    pub fn start(&self, path: impl Into<PathBuf>) -> Result<(), String> {
+        let path = path.into();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Create {}: {e}", dir.display()))?;
+        }
+        let file = File::options()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Open {}: {e}", path.display()))?;
+        log::info!("Capturing protocol traffic to {}", path.display());
+        *self.active.lock().unwrap() = Some(ActiveCapture { path, file });
+        Ok(())
+    }
+
+    /// Stop recording; returns the finished capture's path.
+    pub fn stop(&self) -> Option<PathBuf> {
+        self.active.lock().unwrap().take().map(|c| c.path)
+    }
+
+    /// Path of the capture in progress
+    pub fn path(&self) -> Option<PathBuf> {
+        self.active.lock().unwrap().as_ref().map(|c| c.path.clone())
+    }
+
+    pub fn next_connection(&self) -> u64 {
+        self.connections.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Append one message if recording. A failed write stops the capture
+    /// rather than the connection.
+    pub fn record(&self, connection: u64, direction: Direction, message: &protocol::Message) {
+        let mut active = self.active.lock().unwrap();
+        let Some(capture) = active.as_mut() else {
+            return;
+        };
+        let record = CaptureRecord {
+            at: chrono::Utc::now().timestamp_millis(),
+            connection,
+            direction,
+            message: message.clone(),
+        };
+        let written = serde_json::to_string(&record)
+            .map_err(|e| e.to_string())
+            .and_then(|json| writeln!(capture.file, "{json}").map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            log::error!("Capture to {} failed: {}", capture.path.display(), e);
+            *active = None;
+        }
+    }
+}
+
+impl Default for CaptureRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Read a capture file. Blank lines are skipped; a truncated last line
+/// (recorder killed mid-write) is ignored.
+pub fn read_capture(path: &Path) -> Result<Vec<CaptureRecord>, String> {
+    let file = File::open(path).map_err(|e| format!("Open {}: {e}", path.display()))?;
+    let lines = BufReader::new(file)
+        .lines()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Read {}: {e}", path.display()))?;
+    let last = lines.len().saturating_sub(1);
+    let mut records = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(record) => records.push(record),
+            Err(_) if index == last => break,
+            Err(e) => return Err(format!("{}:{}: {e}", path.display(), index + 1)),
+        }
+    }
+    Ok(records)
+}
+
+/// How fast `replay` feeds the capture
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Timing {
+    /// Keep the recorded gaps between inbound messages
+    Original,
+    /// Divide the recorded gaps by this factor
+    Accelerated(f64),
+    /// No delays
+    Immediate,
+}
+
+/// What `replay` saw
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayReport {
+    pub inbound: usize,
+    pub responses: usize,
+    /// Responses that differ from the recorded ones
+    pub mismatches: Vec<String>,
+}
+
+/// Feed the inbound messages of a capture through `handle_message`, with
+/// whatever injector and emitter `ctx` carries. Each recorded connection
+/// keeps its own client slot and is disconnected at the end.
+pub async fn replay(
+    records: &[CaptureRecord],
+    ctx: &ServerContext,
+    timing: Timing,
+) -> ReplayReport {
+    let mut report = ReplayReport::default();
+    let mut slots: HashMap<u64, Option<String>> = HashMap::new();
+    let mut previous_at: Option<i64> = None;
+    for (index, record) in records.iter().enumerate() {
+        if record.direction != Direction::In {
+            continue;
+        }
+        let gap_ms = previous_at.map_or(0, |at| (record.at - at).max(0)) as f64;
+        previous_at = Some(record.at);
+        let delay_ms = match timing {
+            Timing::Original => gap_ms,
+            Timing::Accelerated(factor) if factor > 0.0 => gap_ms / factor,
+            Timing::Accelerated(_) | Timing::Immediate => 0.0,
+        };
+        if delay_ms >= 1.0 {
+            tokio::time::sleep(Duration::from_millis(delay_ms as u64)).await;
+        }
+
+        let slot = slots.entry(record.connection).or_default();
+        report.inbound += 1;
+        let response = ws_server::handle_message(record.message.clone(), ctx, slot).await;
+        if response.is_some() {
+            report.responses += 1;
+        }
+        // The recorded response is the next message on the same connection
+        let expected = records[index + 1..]
+            .iter()
+            .find(|r| r.connection == record.connection)
+            .filter(|r| r.direction == Direction::Out)
+            .map(|r| &r.message);
+        if response.as_ref() != expected {
+            report.mismatches.push(format!(
+                "line {}: expected {}, got {}",
+                index + 1,
+                describe(expected),
+                describe(response.as_ref())
+            ));
+        }
+    }
+    for slot in slots.values() {
+        ws_server::disconnect(ctx, slot.as_deref()).await;
+    }
+    report
+}
+
+fn describe(message: Option<&protocol::Message>) -> String {
+    match message {
+        Some(message) => protocol::serialize_message(message).unwrap_or_default(),
+        None => "no response".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client_registry::ClientRegistry;
+    use crate::injection::testing::MockInjector;
+    use crate::ws_server::LogEmitter;
+    use std::sync::{Arc, Mutex};
+
+    fn context() -> (ServerContext, Arc<Mutex<Vec<String>>>) {
+        let (injector, injected) = MockInjector::new();
+        let registry = Arc::new(tokio::sync::Mutex::new(ClientRegistry::new(30)));
+        let ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(LogEmitter));
+        (ctx, injected)
+    }
+
+    fn capture_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ptt-capture-{name}-{}.jsonl", std::process::id()))
+    }
+
+    const SESSION: [&str; 3] = [
+        r#"{"type":"HELLO","clientId":"phone-01","payload":{"deviceModel":"Pixel 8","engine":"Google","capabilities":["WS"]}}"#,
+        r#"{"type":"PTT_START","clientId":"phone-01","payload":{"sessionId":"s-1"}}"#,
+        r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000200,"payload":{"sessionId":"s-1","text":"hello","confidence":0.9}}"#,
+    ];
+
+    /// Record SESSION the way the server loop does
+    async fn record_session(recorder: &CaptureRecorder) {
+        let (ctx, _) = context();
+        let connection = recorder.next_connection();
+        let mut slot = None;
+        for json in SESSION {
+            let message = protocol::parse_message(json).unwrap();
+            recorder.record(connection, Direction::In, &message);
+            if let Some(response) = ws_server::handle_message(message, &ctx, &mut slot).await {
+                recorder.record(connection, Direction::Out, &response);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_capture_round_trip_and_replay() {
+        let path = capture_path("replay");
+        let _ = std::fs::remove_file(&path);
+        let recorder = CaptureRecorder::new();
+        recorder.start(&path).unwrap();
+        record_session(&recorder).await;
+        assert_eq!(recorder.stop(), Some(path.clone()));
+
+        let records = read_capture(&path).unwrap();
+        let directions: Vec<_> = records.iter().map(|r| r.direction).collect();
+        use Direction::{In, Out};
+        assert_eq!(directions, vec![In, Out, In, In, Out]);
+
+        let (ctx, injected) = context();
+        let report = replay(&records, &ctx, Timing::Immediate).await;
+        assert_eq!(report.inbound, 3);
+        assert_eq!(report.responses, 2);
+        assert!(report.mismatches.is_empty(), "{:?}", report.mismatches);
+        assert_eq!(*injected.lock().unwrap(), vec!["hello"]);
+        assert!(ctx.registry.lock().await.get("phone-01").is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_reports_changed_responses() {
+        let path = capture_path("mismatch");
+        let _ = std::fs::remove_file(&path);
+        let recorder = CaptureRecorder::new();
+        recorder.start(&path).unwrap();
+        record_session(&recorder).await;
+        recorder.stop();
+
+        // Same traffic, but the gate now holds the FINAL
+        let (ctx, injected) = context();
+        ctx.confidence
+            .set_policy(crate::confidence::ConfidencePolicy {
+                auto_inject_at: 0.95,
+                reject_below: 0.0,
+            })
+            .unwrap();
+        let records = read_capture(&path).unwrap();
+        let report = replay(&records, &ctx, Timing::Accelerated(1000.0)).await;
+        assert_eq!(report.mismatches.len(), 1);
+        assert!(report.mismatches[0].starts_with("line 4:"));
+        assert!(injected.lock().unwrap().is_empty());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_truncated_last_line_is_ignored() {
+        let path = capture_path("truncated");
+        let record = CaptureRecord {
+            at: 1,
+            connection: 1,
+            direction: Direction::In,
+            message: protocol::parse_message(SESSION[0]).unwrap(),
+        };
+        let line = serde_json::to_string(&record).unwrap();
+        std::fs::write(&path, format!("{line}\n\n{}", &line[..20])).unwrap();
+
+        assert_eq!(read_capture(&path).unwrap(), vec![record]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
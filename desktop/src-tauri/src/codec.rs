@@ -0,0 +1,492 @@
+//! Wire encodings for `protocol::Message`. A connection starts with JSON; a
+//! phone that lists "CBOR" or "MSGPACK" among its HELLO capabilities is told
+//! the chosen encoding in the HELLO ACK, and from then on binary frames in
+//! both directions use it. Text frames are always JSON.
+//!
+//! Binary frames are the JSON structure made compact: field names become the
+//! small integers in `KEYS`, the `type` tag becomes its index in `TYPES`, and
+//! what the connection already knows is left out — `clientId` after HELLO and
+//! `payload.sessionId` of the PTT session in progress (see `Implied`).
+//! Decoding expands a frame back to JSON, so it validates against the same
+//! schema.
+
+use ciborium::Value as Compact;
+use serde_json::Value;
+
+use crate::protocol::{self, Message};
+
+/// Compact key of every protocol field: its index. Append only — phones
+/// hard-code these numbers.
+pub const KEYS: [&str; 17] = [
+    "type",
+    "clientId",
+    "payload",
+    "timestamp",
+    "deviceModel",
+    "engine",
+    "capabilities",
+    "sessionId",
+    "seq",
+    "text",
+    "confidence",
+    "ackType",
+    "status",
+    "heldId",
+    "error",
+    "encoding",
+    "accept",
+];
+
+/// Compact code of every message type: its index. Append only.
+pub const TYPES: [&str; 8] = [
+    "HELLO",
+    "PTT_START",
+    "PARTIAL",
+    "FINAL",
+    "HEARTBEAT",
+    "UNDO",
+    "CONFIRM_HELD",
+    "ACK",
+];
+
+/// Message types whose payload may leave out the current `sessionId`
+const SESSION_TYPES: [&str; 2] = ["PARTIAL", "FINAL"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Json,
+    Cbor,
+    MessagePack,
+}
+
+/// Binary encodings, most preferred first
+const PREFERENCE: [Codec; 2] = [Codec::Cbor, Codec::MessagePack];
+
+/// Fields one binary connection leaves out of its frames: the client id
+/// from HELLO and the session id from the latest PTT_START. A frame that
+/// carries them anyway keeps its own values.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Implied {
+    pub client_id: Option<String>,
+    pub session_id: Option<String>,
+}
+
+impl Implied {
+    /// Track the ids of every message received on the connection, whatever
+    /// its encoding.
+    pub fn observe(&mut self, message: &Message) {
+        match message {
+            Message::Hello { client_id, .. } => self.client_id = Some(client_id.clone()),
+            Message::PttStart { payload, .. } => self.session_id = Some(payload.session_id.clone()),
+            _ => {}
+        }
+    }
+}
+
+impl Codec {
+    /// Name used in HELLO capabilities and the ACK's `encoding`
+    pub fn name(self) -> &'static str {
+        match self {
+            Codec::Json => "JSON",
+            Codec::Cbor => "CBOR",
+            Codec::MessagePack => "MSGPACK",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Codec::Json, Codec::Cbor, Codec::MessagePack]
+            .into_iter()
+            .find(|codec| codec.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The preferred binary encoding the phone offers, or JSON
+    pub fn negotiate(capabilities: &[String]) -> Self {
+        PREFERENCE
+            .into_iter()
+            .find(|codec| {
+                capabilities
+                    .iter()
+                    .any(|c| Codec::from_name(c) == Some(*codec))
+            })
+            .unwrap_or(Codec::Json)
+    }
+
+    pub fn is_binary(self) -> bool {
+        self != Codec::Json
+    }
+
+    /// Encode with every field present
+    pub fn encode(self, message: &Message) -> Result<Vec<u8>, String> {
+        self.encode_with(message, &Implied::default())
+    }
+
+    /// Encode for a connection, leaving out what `implied` already covers
+    pub fn encode_with(self, message: &Message, implied: &Implied) -> Result<Vec<u8>, String> {
+        if self == Codec::Json {
+            return protocol::serialize_message(message)
+                .map(String::into_bytes)
+                .map_err(|e| e.to_string());
+        }
+        let mut value = serde_json::to_value(message).map_err(|e| e.to_string())?;
+        omit_implied(&mut value, implied);
+        let compact = compact(value, true);
+        match self {
+            Codec::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::ser::into_writer(&compact, &mut bytes).map_err(|e| e.to_string())?;
+                Ok(bytes)
+            }
+            _ => rmp_serde::to_vec(&compact).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Decode a frame that carries every field
+    pub fn decode(self, bytes: &[u8]) -> Result<Message, String> {
+        self.decode_with(bytes, &Implied::default())
+    }
+
+    /// Decodes and validates like `protocol::parse_message_validated`, so a
+    /// bad binary message names the wrong field too. Fields left out are
+    /// taken from `implied`.
+    pub fn decode_with(self, bytes: &[u8], implied: &Implied) -> Result<Message, String> {
+        let compact: Compact = match self {
+            Codec::Json => {
+                let value = serde_json::from_slice(bytes).map_err(|e| e.to_string())?;
+                return protocol::message_from_value(value);
+            }
+            Codec::Cbor => ciborium::de::from_reader(bytes).map_err(|e| format!("CBOR: {e}"))?,
+            Codec::MessagePack => {
+                rmp_serde::from_slice(bytes).map_err(|e| format!("MessagePack: {e}"))?
+            }
+        };
+        let mut value = expand(compact, true)?;
+        fill_implied(&mut value, implied);
+        protocol::message_from_value(value)
+    }
+}
+
+fn in_session(message: &serde_json::Map<String, Value>) -> bool {
+    message
+        .get("type")
+        .and_then(Value::as_str)
+        .is_some_and(|t| SESSION_TYPES.contains(&t))
+}
+
+fn omit_implied(value: &mut Value, implied: &Implied) {
+    let Value::Object(message) = value else {
+        return;
+    };
+    let is_implied = |field: Option<&Value>, id: &Option<String>| {
+        id.is_some() && field.and_then(Value::as_str) == id.as_deref()
+    };
+    if is_implied(message.get("clientId"), &implied.client_id) {
+        message.remove("clientId");
+    }
+    let in_session = in_session(message);
+    if let Some(Value::Object(payload)) = message.get_mut("payload") {
+        if in_session && is_implied(payload.get("sessionId"), &implied.session_id) {
+            payload.remove("sessionId");
+        }
+    }
+}
+
+fn fill_implied(value: &mut Value, implied: &Implied) {
+    let Value::Object(message) = value else {
+        return;
+    };
+    if let Some(client_id) = &implied.client_id {
+        message
+            .entry("clientId")
+            .or_insert_with(|| client_id.clone().into());
+    }
+    let in_session = in_session(message);
+    if let (true, Some(session_id), Some(Value::Object(payload))) =
+        (in_session, &implied.session_id, message.get_mut("payload"))
+    {
+        payload
+            .entry("sessionId")
+            .or_insert_with(|| session_id.clone().into());
+    }
+}
+
+/// Index of `name` in `table` as a compact integer, or the name itself
+fn code(table: &[&str], name: String) -> Compact {
+    match table.iter().position(|entry| *entry == name) {
+        Some(index) => Compact::Integer(index.into()),
+        None => Compact::Text(name),
+    }
+}
+
+/// Name behind a compact integer, or the text as sent
+fn name(table: &[&str], what: &str, value: Compact) -> Result<String, String> {
+    match value {
+        Compact::Text(text) => Ok(text),
+        Compact::Integer(code) => usize::try_from(code)
+            .ok()
+            .and_then(|index| table.get(index))
+            .map(|name| name.to_string())
+            .ok_or_else(|| format!("unknown {what} {}", i128::from(code))),
+        other => Err(format!("{what} must be an integer or text, got {other:?}")),
+    }
+}
+
+/// JSON to the compact binary form; `top` is the message map itself, the
+/// only place a `type` tag appears.
+fn compact(value: Value, top: bool) -> Compact {
+    match value {
+        Value::Null => Compact::Null,
+        Value::Bool(b) => Compact::Bool(b),
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(u), _) => Compact::Integer(u.into()),
+            (_, Some(i)) => Compact::Integer(i.into()),
+            _ => Compact::Float(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => Compact::Text(s),
+        Value::Array(items) => {
+            Compact::Array(items.into_iter().map(|v| compact(v, false)).collect())
+        }
+        Value::Object(map) => Compact::Map(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let value = match (top && key == "type", value) {
+                        (true, Value::String(tag)) => code(&TYPES, tag),
+                        (_, value) => compact(value, false),
+                    };
+                    (code(&KEYS, key), value)
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn expand(value: Compact, top: bool) -> Result<Value, String> {
+    Ok(match value {
+        Compact::Null => Value::Null,
+        Compact::Bool(b) => Value::Bool(b),
+        Compact::Integer(i) => {
+            let i = i128::from(i);
+            match (u64::try_from(i), i64::try_from(i)) {
+                (Ok(u), _) => u.into(),
+                (_, Ok(i)) => i.into(),
+                _ => return Err(format!("integer {i} out of range")),
+            }
+        }
+        Compact::Float(f) => serde_json::Number::from_f64(f)
+            .map(Value::Number)
+            .ok_or_else(|| format!("number {f} is not finite"))?,
+        Compact::Text(s) => Value::String(s),
+        Compact::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|v| expand(v, false))
+                .collect::<Result<_, _>>()?,
+        ),
+        Compact::Map(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = name(&KEYS, "key", key)?;
+                    let value = match top && key == "type" {
+                        true => Value::String(name(&TYPES, "message type", value)?),
+                        false => expand(value, false)?,
+                    };
+                    Ok((key, value))
+                })
+                .collect::<Result<_, String>>()?,
+        ),
+        Compact::Tag(_, inner) => expand(*inner, top)?,
+        other => return Err(format!("unsupported value {other:?}")),
+    })
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{AckPayload, FinalPayload, PartialPayload};
+
+    fn final_message() -> Message {
+        Message::Final {
+            client_id: "phone-01".to_string(),
+            timestamp: 1_700_000_000_000,
+            payload: FinalPayload {
+                session_id: "sess-42".to_string(),
+                text: "안녕하세요 meeting at three".to_string(),
+                confidence: 0.5,
+            },
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_every_codec() {
+        let message = final_message();
+        for codec in [Codec::Json, Codec::Cbor, Codec::MessagePack] {
+            let bytes = codec.encode(&message).unwrap();
+            assert_eq!(codec.decode(&bytes).unwrap(), message, "{codec:?}");
+        }
+    }
+
+    fn connection() -> Implied {
+        Implied {
+            client_id: Some("phone-01".to_string()),
+            session_id: Some("sess-42".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_binary_frames_meet_the_size_target() {
+        let text = "meeting at three";
+        let partial = Message::Partial {
+            client_id: "phone-01".to_string(),
+            timestamp: 1_700_000_000_000,
+            payload: PartialPayload {
+                session_id: "sess-42".to_string(),
+                seq: 12,
+                text: text.to_string(),
+                confidence: 0.62,
+            },
+        };
+        let final_message = Message::Final {
+            client_id: "phone-01".to_string(),
+            timestamp: 1_700_000_000_000,
+            payload: FinalPayload {
+                session_id: "sess-42".to_string(),
+                text: text.to_string(),
+                confidence: 0.95,
+            },
+        };
+        let mut ack_payload = AckPayload::new("FINAL");
+        ack_payload.status = Some("injected".to_string());
+        let ack = Message::Ack {
+            client_id: "phone-01".to_string(),
+            payload: ack_payload,
+        };
+
+        for codec in PREFERENCE {
+            let size = |m: &Message| codec.encode_with(m, &connection()).unwrap().len();
+            // Type, timestamp, seq and confidence: at most 30 bytes besides the text
+            assert!(size(&partial) <= text.len() + 30, "{codec:?} PARTIAL");
+            assert!(size(&final_message) <= text.len() + 28, "{codec:?} FINAL");
+            assert!(size(&ack) <= 24, "{codec:?} ACK");
+            // A third of the JSON or less
+            let json = Codec::Json.encode(&final_message).unwrap().len();
+            assert!(size(&final_message) * 3 <= json, "{codec:?} vs JSON");
+        }
+    }
+
+    #[test]
+    fn test_connection_leaves_out_implied_ids() {
+        let message = final_message();
+        for codec in PREFERENCE {
+            let bytes = codec.encode_with(&message, &connection()).unwrap();
+            assert!(!bytes.windows(8).any(|w| w == b"phone-01"), "{codec:?}");
+            assert!(!bytes.windows(7).any(|w| w == b"sess-42"), "{codec:?}");
+            assert_eq!(codec.decode_with(&bytes, &connection()).unwrap(), message);
+            assert_eq!(
+                codec.decode(&bytes),
+                Err("clientId: missing".to_string()),
+                "{codec:?}"
+            );
+        }
+
+        // Ids that differ from the connection's are sent and kept
+        let mut other = connection();
+        other.session_id = Some("sess-41".to_string());
+        let bytes = Codec::Cbor.encode_with(&message, &other).unwrap();
+        assert_eq!(Codec::Cbor.decode_with(&bytes, &other).unwrap(), message);
+    }
+
+    #[test]
+    fn test_observe_tracks_hello_and_ptt_start() {
+        let mut implied = Implied::default();
+        implied.observe(&protocol::parse_message(
+            r#"{"type":"HELLO","clientId":"phone-01","payload":{"deviceModel":"Pixel 8","engine":"Google","capabilities":["CBOR"]}}"#,
+        ).unwrap());
+        implied.observe(
+            &protocol::parse_message(
+                r#"{"type":"PTT_START","clientId":"phone-01","payload":{"sessionId":"sess-42"}}"#,
+            )
+            .unwrap(),
+        );
+        assert_eq!(implied, connection());
+    }
+
+    #[test]
+    fn test_compact_tables_cover_the_schema() {
+        fn collect(schema: &serde_json::Value, keys: &mut Vec<String>, types: &mut Vec<String>) {
+            match schema {
+                serde_json::Value::Object(map) => {
+                    if let Some(serde_json::Value::Object(properties)) = map.get("properties") {
+                        keys.extend(properties.keys().cloned());
+                        if let Some(tag) = properties.get("type").and_then(|t| t["const"].as_str())
+                        {
+                            types.push(tag.to_string());
+                        }
+                    }
+                    map.values().for_each(|v| collect(v, keys, types));
+                }
+                serde_json::Value::Array(items) => {
+                    items.iter().for_each(|v| collect(v, keys, types))
+                }
+                _ => {}
+            }
+        }
+        let (mut keys, mut types) = (Vec::new(), Vec::new());
+        collect(crate::schema::protocol_schema(), &mut keys, &mut types);
+
+        assert!(!types.is_empty());
+        for key in keys {
+            assert!(KEYS.contains(&key.as_str()), "no compact key for {key}");
+        }
+        for tag in types {
+            assert!(TYPES.contains(&tag.as_str()), "no compact code for {tag}");
+        }
+    }
+
+    #[test]
+    fn test_unknown_compact_codes_are_rejected() {
+        let frame = Compact::Map(vec![(
+            Compact::Integer(0.into()),
+            Compact::Integer(99.into()),
+        )]);
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&frame, &mut bytes).unwrap();
+        assert_eq!(
+            Codec::Cbor.decode(&bytes),
+            Err("unknown message type 99".to_string())
+        );
+
+        let frame = Compact::Map(vec![(Compact::Integer(200.into()), Compact::Null)]);
+        let bytes = rmp_serde::to_vec(&frame).unwrap();
+        assert_eq!(
+            Codec::MessagePack.decode(&bytes),
+            Err("unknown key 200".to_string())
+        );
+    }
+
+    #[test]
+    fn test_negotiate_prefers_cbor() {
+        let caps = |list: &[&str]| list.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        assert_eq!(Codec::negotiate(&caps(&["WS"])), Codec::Json);
+        assert_eq!(
+            Codec::negotiate(&caps(&["WS", "msgpack"])),
+            Codec::MessagePack
+        );
+        assert_eq!(Codec::negotiate(&caps(&["MSGPACK", "CBOR"])), Codec::Cbor);
+    }
+
+    #[test]
+    fn test_decode_reports_field_and_garbage() {
+        let mut value = serde_json::to_value(final_message()).unwrap();
+        value["payload"]["confidence"] = "high".into();
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&value, &mut bytes).unwrap();
+        assert_eq!(
+            Codec::Cbor.decode(&bytes),
+            Err("payload.confidence: expected number, got string".to_string())
+        );
+        assert!(Codec::MessagePack.decode(&[0xc1]).is_err());
+        assert!(Codec::Cbor.decode(&[]).is_err());
+    }
+}
@@ -0,0 +1,274 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+/// Held FINALs beyond this are rejected; nobody confirms a backlog that long
+pub const MAX_HELD: usize = 50;
+
+/// What happens to a FINAL of a given confidence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gate {
+    Inject,
+    /// Queued until confirmed from the desktop UI or the phone
+    Hold,
+    Reject,
+}
+
+/// Confidence thresholds. The defaults inject everything, since some
+/// engines always report 0.0.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfidencePolicy {
+    /// At or above this, inject automatically
+    pub auto_inject_at: f64,
+    /// Below this, refuse; in between, hold for confirmation
+    pub reject_below: f64,
+}
+
+impl Default for ConfidencePolicy {
+    fn default() -> Self {
+        Self {
+            auto_inject_at: 0.0,
+            reject_below: 0.0,
+        }
+    }
+}
+
+impl ConfidencePolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        let in_range = |v: f64| (0.0..=1.0).contains(&v);
+        if !in_range(self.auto_inject_at) || !in_range(self.reject_below) {
+            return Err("Thresholds must be between 0.0 and 1.0".to_string());
+        }
+        if self.reject_below > self.auto_inject_at {
+            return Err("rejectBelow must not exceed autoInjectAt".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn evaluate(&self, confidence: f64) -> Gate {
+        if confidence >= self.auto_inject_at {
+            Gate::Inject
+        } else if confidence < self.reject_below {
+            Gate::Reject
+        } else {
+            Gate::Hold
+        }
+    }
+}
+
+/// Why a FINAL is waiting for confirmation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HoldReason {
+    LowConfidence,
+    /// A redaction detector asked for confirmation
+    SensitiveData,
+    /// Review mode is on but the review queue could not be saved
+    ReviewUnavailable,
+}
+
+/// FINAL waiting for confirmation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeldText {
+    pub id: u64,
+    pub client_id: String,
+    pub session_id: String,
+    pub text: String,
+    pub confidence: f64,
+    pub reason: HoldReason,
+}
+
+/// Applies the policy and keeps the queue of held FINALs.
+pub struct ConfidenceGate {
+    policy: RwLock<ConfidencePolicy>,
+    next_id: AtomicU64,
+    held: Mutex<Vec<HeldText>>,
+}
+
+impl ConfidenceGate {
+    pub fn new(policy: ConfidencePolicy) -> Self {
+        Self {
+            policy: RwLock::new(policy),
+            next_id: AtomicU64::new(1),
+            held: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn policy(&self) -> ConfidencePolicy {
+        *self.policy.read().unwrap()
+    }
+
+    pub fn set_policy(&self, policy: ConfidencePolicy) -> Result<(), String> {
+        policy.validate()?;
+        *self.policy.write().unwrap() = policy;
+        Ok(())
+    }
+
+    pub fn evaluate(&self, confidence: f64) -> Gate {
+        self.policy().evaluate(confidence)
+    }
+
+    /// Queue a FINAL and return it with its id. Fails when `MAX_HELD` texts
+    /// are already waiting.
+    pub fn hold(
+        &self,
+        client_id: &str,
+        session_id: &str,
+        text: &str,
+        confidence: f64,
+        reason: HoldReason,
+    ) -> Result<HeldText, String> {
+        let mut queue = self.held.lock().unwrap();
+        if queue.len() >= MAX_HELD {
+            return Err(format!("{MAX_HELD} texts are already held"));
+        }
+        let held = HeldText {
+            id: self.next_id.fetch_add(1, Ordering::SeqCst),
+            client_id: client_id.to_string(),
+            session_id: session_id.to_string(),
+            text: text.to_string(),
+            confidence,
+            reason,
+        };
+        queue.push(held.clone());
+        Ok(held)
+    }
+
+    /// Held FINALs, oldest first
+    pub fn held(&self) -> Vec<HeldText> {
+        self.held.lock().unwrap().clone()
+    }
+
+    pub fn get(&self, id: u64) -> Result<HeldText, String> {
+        self.held
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|h| h.id == id)
+            .cloned()
+            .ok_or_else(|| format!("No held text with id {id}"))
+    }
+
+    /// Remove a held FINAL from the queue (to inject or to drop it).
+    pub fn take(&self, id: u64) -> Result<HeldText, String> {
+        let mut held = self.held.lock().unwrap();
+        let index = held
+            .iter()
+            .position(|h| h.id == id)
+            .ok_or_else(|| format!("No held text with id {id}"))?;
+        Ok(held.remove(index))
+    }
+
+    /// Put a taken FINAL back in its place, e.g. after its injection failed.
+    pub fn restore(&self, text: HeldText) {
+        let mut held = self.held.lock().unwrap();
+        let index = held.partition_point(|h| h.id < text.id);
+        held.insert(index, text);
+    }
+}
+
+impl Default for ConfidenceGate {
+    fn default() -> Self {
+        Self::new(ConfidencePolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> ConfidencePolicy {
+        ConfidencePolicy {
+            auto_inject_at: 0.8,
+            reject_below: 0.3,
+        }
+    }
+
+    #[test]
+    fn test_policy_bands() {
+        let policy = policy();
+        assert_eq!(policy.evaluate(0.95), Gate::Inject);
+        assert_eq!(policy.evaluate(0.8), Gate::Inject);
+        assert_eq!(policy.evaluate(0.5), Gate::Hold);
+        assert_eq!(policy.evaluate(0.3), Gate::Hold);
+        assert_eq!(policy.evaluate(0.1), Gate::Reject);
+    }
+
+    #[test]
+    fn test_default_injects_everything() {
+        assert_eq!(ConfidencePolicy::default().evaluate(0.0), Gate::Inject);
+    }
+
+    #[test]
+    fn test_invalid_policy_is_refused() {
+        let gate = ConfidenceGate::default();
+        let inverted = ConfidencePolicy {
+            auto_inject_at: 0.2,
+            reject_below: 0.5,
+        };
+        assert!(gate.set_policy(inverted).is_err());
+        assert!(gate
+            .set_policy(ConfidencePolicy {
+                auto_inject_at: 1.5,
+                reject_below: 0.0
+            })
+            .is_err());
+        assert!(gate.set_policy(policy()).is_ok());
+        assert_eq!(gate.policy(), policy());
+    }
+
+    #[test]
+    fn test_hold_and_take() {
+        let gate = ConfidenceGate::new(policy());
+        let first = gate
+            .hold(
+                "phone-01",
+                "s-1",
+                "maybe this",
+                0.5,
+                HoldReason::LowConfidence,
+            )
+            .unwrap();
+        let second = gate
+            .hold("phone-02", "s-2", "or that", 0.4, HoldReason::LowConfidence)
+            .unwrap();
+        assert_ne!(first.id, second.id);
+        assert_eq!(gate.held().len(), 2);
+
+        let taken = gate.take(first.id).unwrap();
+        assert_eq!(taken.text, "maybe this");
+        assert!(gate.take(first.id).is_err());
+        assert_eq!(gate.held(), vec![second.clone()]);
+
+        gate.restore(taken.clone());
+        assert_eq!(gate.held(), vec![taken, second]);
+    }
+
+    #[test]
+    fn test_held_queue_is_bounded() {
+        let gate = ConfidenceGate::new(policy());
+        for i in 0..MAX_HELD {
+            gate.hold(
+                "phone-01",
+                &format!("s-{i}"),
+                "maybe",
+                0.5,
+                HoldReason::LowConfidence,
+            )
+            .unwrap();
+        }
+        assert!(gate
+            .hold(
+                "phone-01",
+                "s-late",
+                "too many",
+                0.5,
+                HoldReason::LowConfidence
+            )
+            .is_err());
+        assert_eq!(gate.held().len(), MAX_HELD);
+    }
+}
@@ -0,0 +1,450 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::history::{HistoryEntry, Outcome};
+
+/// Shortest cue shown when a session has no usable end time
+const MIN_CUE_MS: i64 = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExportFormat {
+    /// Notes grouped by day
+    Markdown,
+    /// One JSON object per session with the protocol-level fields
+    Jsonl,
+    Csv,
+    Srt,
+    Vtt,
+}
+
+impl ExportFormat {
+    /// Parse a format name or file extension ("md", "markdown", "srt", ...)
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            "jsonl" | "ndjson" => Ok(ExportFormat::Jsonl),
+            "csv" => Ok(ExportFormat::Csv),
+            "srt" => Ok(ExportFormat::Srt),
+            "vtt" | "webvtt" => Ok(ExportFormat::Vtt),
+            other => Err(format!("Unknown export format: {other}")),
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Srt => "srt",
+            ExportFormat::Vtt => "vtt",
+        }
+    }
+}
+
+/// JSONL line, named after the protocol fields
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonlRecord<'a> {
+    client_id: &'a str,
+    session_id: &'a str,
+    device_model: &'a str,
+    engine: &'a str,
+    /// PTT_START received (desktop clock)
+    started_at: i64,
+    ended_at: Option<i64>,
+    /// First PARTIAL's `timestamp`
+    first_partial_timestamp: Option<i64>,
+    /// FINAL's `timestamp`
+    final_timestamp: Option<i64>,
+    partial_count: u32,
+    text: Option<&'a str>,
+    confidence: Option<f64>,
+    outcome: Option<&'a str>,
+}
+
+/// Render `entries` (oldest first, as returned by `History::select`).
+/// Dates and times are shown in `offset`. Markdown and subtitles keep only
+/// the FINALs that were injected, or every FINAL with `all_outcomes`;
+/// JSONL and CSV keep every session.
+pub fn render(
+    entries: &[HistoryEntry],
+    format: ExportFormat,
+    offset: FixedOffset,
+    all_outcomes: bool,
+) -> String {
+    let texts = || {
+        entries
+            .iter()
+            .filter(move |e| all_outcomes || e.outcome == Some(Outcome::Injected))
+            .filter_map(|e| final_text(e).map(|text| (e, text)))
+    };
+    match format {
+        ExportFormat::Markdown => markdown(texts(), offset),
+        ExportFormat::Jsonl => jsonl(entries),
+        ExportFormat::Csv => csv(entries, offset),
+        ExportFormat::Srt => subtitles(texts().collect(), false),
+        ExportFormat::Vtt => subtitles(texts().collect(), true),
+    }
+}
+
+fn local_time(ms: i64, offset: FixedOffset) -> DateTime<FixedOffset> {
+    DateTime::from_timestamp_millis(ms)
+        .unwrap_or_default()
+        .with_timezone(&offset)
+}
+
+fn final_text(entry: &HistoryEntry) -> Option<&str> {
+    entry
+        .final_text
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+}
+
+fn markdown<'a>(
+    texts: impl Iterator<Item = (&'a HistoryEntry, &'a str)>,
+    offset: FixedOffset,
+) -> String {
+    let mut out = String::from("# Dictation\n");
+    let mut current_day = None;
+    for (entry, text) in texts {
+        let started = local_time(entry.started_at, offset);
+        let day = started.date_naive();
+        if current_day != Some(day) {
+            out.push_str(&format!("\n## {}\n\n", day.format("%Y-%m-%d")));
+            current_day = Some(day);
+        }
+        // Keep multi-line FINALs inside their list item
+        let text = text.replace('\n', "\n  ");
+        out.push_str(&format!(
+            "- **{}** _{}_ — {}\n",
+            started.format("%H:%M:%S"),
+            entry.device_model,
+            text
+        ));
+    }
+    out
+}
+
+fn jsonl(entries: &[HistoryEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| {
+            let record = JsonlRecord {
+                client_id: &entry.client_id,
+                session_id: &entry.session_id,
+                device_model: &entry.device_model,
+                engine: &entry.engine,
+                started_at: entry.started_at,
+                ended_at: entry.ended_at,
+                first_partial_timestamp: entry.speech_started_at,
+                final_timestamp: entry.speech_ended_at,
+                partial_count: entry.partial_count,
+                text: entry.final_text.as_deref(),
+                confidence: entry.confidence,
+                outcome: entry.outcome.map(|o| o.as_str()),
+            };
+            // Plain data, always serializable
+            serde_json::to_string(&record).unwrap() + "\n"
+        })
+        .collect()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv(entries: &[HistoryEntry], offset: FixedOffset) -> String {
+    let mut out = String::from(
+        "started_at,ended_at,client_id,device_model,engine,session_id,partial_count,confidence,outcome,text\r\n",
+    );
+    for entry in entries {
+        let time = |ms: i64| local_time(ms, offset).to_rfc3339();
+        let fields = [
+            time(entry.started_at),
+            entry.ended_at.map(time).unwrap_or_default(),
+            entry.client_id.clone(),
+            entry.device_model.clone(),
+            entry.engine.clone(),
+            entry.session_id.clone(),
+            entry.partial_count.to_string(),
+            entry
+                .confidence
+                .map(|c| format!("{c:.2}"))
+                .unwrap_or_default(),
+            entry
+                .outcome
+                .map(|o| o.as_str().to_string())
+                .unwrap_or_default(),
+            entry.final_text.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&row.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Cues use the phone's clock (first PARTIAL to FINAL) only when every
+/// session has both timestamps and comes from the same phone; otherwise all
+/// of them use the desktop's (PTT_START to FINAL arrival). Two clocks in one
+/// file would put cues out of order.
+fn phone_clock(entries: &[(&HistoryEntry, &str)]) -> bool {
+    entries.iter().all(|(e, _)| {
+        e.speech_started_at.is_some()
+            && e.speech_ended_at.is_some()
+            && e.client_id == entries[0].0.client_id
+    })
+}
+
+fn cue_span(entry: &HistoryEntry, phone_clock: bool) -> (i64, i64) {
+    let (start, end) = if phone_clock {
+        (
+            entry.speech_started_at.unwrap_or_default(),
+            entry.speech_ended_at,
+        )
+    } else {
+        (entry.started_at, entry.ended_at)
+    };
+    let end = end
+        .filter(|&end| end > start)
+        .unwrap_or(start.saturating_add(MIN_CUE_MS));
+    (start, end)
+}
+
+fn cue_time(ms: i64, vtt: bool) -> String {
+    let ms = ms.max(0);
+    let separator = if vtt { '.' } else { ',' };
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1_000 % 60,
+        separator,
+        ms % 1_000
+    )
+}
+
+/// SRT or WebVTT with the first cue at 00:00:00
+fn subtitles(texts: Vec<(&HistoryEntry, &str)>, vtt: bool) -> String {
+    let phone_clock = phone_clock(&texts);
+    let cues: Vec<(i64, i64, &str)> = texts
+        .iter()
+        .map(|(entry, text)| {
+            let (start, end) = cue_span(entry, phone_clock);
+            (start, end, *text)
+        })
+        .collect();
+    let origin = cues.iter().map(|c| c.0).min().unwrap_or(0);
+
+    let mut out = String::new();
+    if vtt {
+        out.push_str("WEBVTT\n\n");
+    }
+    for (index, (start, end, text)) in cues.iter().enumerate() {
+        // A blank line would end the cue early
+        let text = text
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !vtt {
+            out.push_str(&format!("{}\n", index + 1));
+        }
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            cue_time(start.saturating_sub(origin), vtt),
+            cue_time(end.saturating_sub(origin), vtt),
+            text
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T0: i64 = 1_700_000_000_000; // 2023-11-14 22:13:20 UTC
+
+    fn entry(session_id: &str, started_at: i64, text: Option<&str>) -> HistoryEntry {
+        HistoryEntry {
+            id: 1,
+            client_id: "phone-01".to_string(),
+            device_model: "Pixel 8".to_string(),
+            engine: "Google".to_string(),
+            session_id: session_id.to_string(),
+            started_at,
+            ended_at: Some(started_at + 2_000),
+            speech_started_at: Some(started_at + 500),
+            speech_ended_at: Some(started_at + 1_750),
+            partial_count: 3,
+            final_text: text.map(str::to_string),
+            confidence: Some(0.9),
+            outcome: Some(Outcome::Injected),
+        }
+    }
+
+    fn utc() -> FixedOffset {
+        FixedOffset::east_opt(0).unwrap()
+    }
+
+    #[test]
+    fn test_markdown_groups_by_local_day() {
+        let entries = vec![
+            entry("s-1", T0, Some("Late note.")),
+            entry("s-2", T0 + 10_000, None),
+            entry("s-3", T0 + 7_200_000, Some("Next day.")),
+        ];
+
+        let out = render(&entries, ExportFormat::Markdown, utc(), false);
+        assert_eq!(
+            out,
+            "# Dictation\n\n## 2023-11-14\n\n- **22:13:20** _Pixel 8_ — Late note.\n\n\
+             ## 2023-11-15\n\n- **00:13:20** _Pixel 8_ — Next day.\n"
+        );
+
+        let seoul = FixedOffset::east_opt(9 * 3600).unwrap();
+        let out = render(&entries, ExportFormat::Markdown, seoul, false);
+        assert_eq!(out.matches("## ").count(), 1);
+    }
+
+    #[test]
+    fn test_jsonl_keeps_protocol_fields() {
+        let entries = vec![entry("s-1", T0, Some("hi")), entry("s-2", T0, None)];
+
+        let out = render(&entries, ExportFormat::Jsonl, utc(), false);
+        let lines: Vec<serde_json::Value> = out
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["clientId"], "phone-01");
+        assert_eq!(lines[0]["sessionId"], "s-1");
+        assert_eq!(lines[0]["firstPartialTimestamp"], T0 + 500);
+        assert_eq!(lines[0]["finalTimestamp"], T0 + 1_750);
+        assert_eq!(lines[0]["outcome"], "injected");
+        assert!(lines[1]["text"].is_null());
+    }
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let entries = vec![entry("s-1", T0, Some("Say \"hi\", then\nleave"))];
+
+        let out = render(&entries, ExportFormat::Csv, utc(), false);
+        let mut lines = out.split("\r\n");
+        assert!(lines.next().unwrap().starts_with("started_at,ended_at,"));
+        assert_eq!(
+            lines.next().unwrap(),
+            "2023-11-14T22:13:20+00:00,2023-11-14T22:13:22+00:00,phone-01,Pixel 8,Google,s-1,3,0.90,injected,\"Say \"\"hi\"\", then\nleave\""
+        );
+    }
+
+    #[test]
+    fn test_subtitles_use_protocol_timestamps() {
+        let entries = vec![
+            entry("s-1", T0, Some("First.")),
+            entry("s-2", T0 + 3_661_000, Some("Second.")),
+        ];
+
+        assert_eq!(
+            render(&entries, ExportFormat::Srt, utc(), false),
+            "1\n00:00:00,000 --> 00:00:01,250\nFirst.\n\n\
+             2\n01:01:01,000 --> 01:01:02,250\nSecond.\n\n"
+        );
+        assert_eq!(
+            render(&entries, ExportFormat::Vtt, utc(), false),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:01.250\nFirst.\n\n\
+             01:01:01.000 --> 01:01:02.250\nSecond.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_cue_text_has_no_blank_lines() {
+        let entries = vec![
+            entry("s-1", T0, Some("One\n\n\nTwo")),
+            entry("s-2", T0 + 5_000, Some("Three\r\n\r\nFour\n \nFive")),
+        ];
+
+        assert_eq!(
+            render(&entries, ExportFormat::Srt, utc(), false),
+            "1\n00:00:00,000 --> 00:00:01,250\nOne\nTwo\n\n\
+             2\n00:00:05,000 --> 00:00:06,250\nThree\nFour\nFive\n\n"
+        );
+    }
+
+    #[test]
+    fn test_subtitles_fall_back_to_the_desktop_clock_for_all_cues() {
+        let mut fallback = entry("s-2", T0 + 3_661_000, Some("Second."));
+        fallback.speech_started_at = None;
+        fallback.ended_at = None;
+        let mut other_phone = entry("s-3", T0 + 7_200_000, Some("Third."));
+        other_phone.client_id = "phone-02".to_string();
+
+        assert_eq!(
+            render(
+                &[entry("s-1", T0, Some("First.")), fallback],
+                ExportFormat::Srt,
+                utc(),
+                false
+            ),
+            "1\n00:00:00,000 --> 00:00:02,000\nFirst.\n\n\
+             2\n01:01:01,000 --> 01:01:02,000\nSecond.\n\n"
+        );
+        // Two phones means two unrelated clocks
+        assert_eq!(
+            render(
+                &[entry("s-1", T0, Some("First.")), other_phone],
+                ExportFormat::Srt,
+                utc(),
+                false
+            ),
+            "1\n00:00:00,000 --> 00:00:02,000\nFirst.\n\n\
+             2\n02:00:00,000 --> 02:00:02,000\nThird.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_notes_and_subtitles_keep_only_injected_finals() {
+        let mut rejected = entry("s-2", T0 + 1_000, Some("Not typed."));
+        rejected.outcome = Some(Outcome::Rejected);
+        let mut held = entry("s-3", T0 + 2_000, Some("Still held."));
+        held.outcome = Some(Outcome::Held);
+        let entries = vec![entry("s-1", T0, Some("Typed.")), rejected, held];
+
+        let notes = render(&entries, ExportFormat::Markdown, utc(), false);
+        assert!(
+            notes.contains("Typed.") && !notes.contains("Not typed.") && !notes.contains("held")
+        );
+        let cues = render(&entries, ExportFormat::Vtt, utc(), false);
+        assert_eq!(cues.matches(" --> ").count(), 1);
+
+        let all = render(&entries, ExportFormat::Markdown, utc(), true);
+        assert!(all.contains("Not typed.") && all.contains("Still held."));
+        // Data exports keep every session either way
+        let csv = render(&entries, ExportFormat::Csv, utc(), false);
+        assert_eq!(csv.lines().count(), 4);
+    }
+
+    #[test]
+    fn test_extreme_timestamps_do_not_overflow() {
+        let mut late = entry("s-1", T0, Some("Late."));
+        late.started_at = i64::MAX - 10;
+        late.speech_started_at = None;
+        late.ended_at = None;
+        let out = render(&[late], ExportFormat::Srt, utc(), false);
+        assert!(out.starts_with("1\n00:00:00,000 --> "));
+    }
+
+    #[test]
+    fn test_format_names() {
+        assert_eq!(ExportFormat::from_name("MD"), Ok(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::from_name("webvtt"), Ok(ExportFormat::Vtt));
+        assert!(ExportFormat::from_name("docx").is_err());
+    }
+}
@@ -0,0 +1,228 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// After this much silence the next dictation starts a fresh context
+pub const DEFAULT_RESET_GAP: Duration = Duration::from_secs(30);
+
+/// Korean, Japanese and Chinese scripts plus CJK punctuation and full-width forms
+pub fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x11FF       // Hangul Jamo
+        | 0x3000..=0x303F     // CJK symbols and punctuation
+        | 0x3040..=0x30FF     // Hiragana, Katakana
+        | 0x3130..=0x318F     // Hangul compatibility Jamo
+        | 0x3400..=0x4DBF     // CJK extension A
+        | 0x4E00..=0x9FFF     // CJK unified ideographs
+        | 0xAC00..=0xD7A3     // Hangul syllables
+        | 0xFF00..=0xFFEF     // Half-width and full-width forms
+    )
+}
+
+/// Punctuation that attaches to the previous word
+fn is_closing(c: char) -> bool {
+    matches!(c, '.' | ',' | '!' | '?' | ';' | ':' | ')' | ']' | '}' | '…')
+}
+
+fn ends_sentence(tail: &str) -> bool {
+    let trimmed = tail.trim_end_matches([' ', '\t']);
+    trimmed.is_empty()
+        || trimmed.ends_with('\n')
+        || trimmed.ends_with(['.', '!', '?', '。', '！', '？'])
+}
+
+/// Join `text` onto `previous` (None = fresh context): decide the leading
+/// separator and capitalize sentence starts.
+pub fn join(previous: Option<&str>, text: &str) -> String {
+    let Some(first) = text.chars().next() else {
+        return String::new();
+    };
+    let mut output = String::with_capacity(text.len() + 1);
+
+    if let Some(last) = previous.and_then(|p| p.chars().last()) {
+        let attached = last.is_whitespace()
+            || first.is_whitespace()
+            || is_closing(first)
+            || (is_cjk(last) && is_cjk(first));
+        if !attached {
+            output.push(' ');
+        }
+    }
+
+    if previous.is_none_or(ends_sentence) && first.is_lowercase() {
+        output.extend(first.to_uppercase());
+        output.push_str(&text[first.len_utf8()..]);
+    } else {
+        output.push_str(text);
+    }
+    output
+}
+
+struct LastInjection {
+    target: String,
+    text: String,
+    at: Instant,
+}
+
+/// Formats consecutive FINALs so they read as one text ("Hello there. How
+/// are you"). Off by default: the desktop can't see which window has focus,
+/// so a dictation into a new field would be joined onto the old one.
+/// Context is dropped when the injection backend changes (paste vs typing,
+/// not the focused app), after a long pause, on undo and commands, and on an
+/// explicit `reset` — the user's "new field" action is the only focus signal.
+pub struct JoinFormatter {
+    enabled: AtomicBool,
+    reset_gap: Duration,
+    last: Mutex<Option<LastInjection>>,
+}
+
+impl JoinFormatter {
+    pub fn new(reset_gap: Duration) -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            reset_gap,
+            last: Mutex::new(None),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+        self.reset();
+    }
+
+    pub fn reset(&self) {
+        *self.last.lock().unwrap() = None;
+    }
+
+    /// Format `text` for injection through backend `target`, joined onto
+    /// the last recorded injection.
+    pub fn format(&self, target: &str, text: &str, now: Instant) -> String {
+        if !self.is_enabled() || text.is_empty() {
+            return text.to_string();
+        }
+        let last = self.last.lock().unwrap();
+        let previous = last
+            .as_ref()
+            .filter(|l| l.target == target && now.duration_since(l.at) <= self.reset_gap)
+            .map(|l| l.text.as_str());
+        join(previous, text)
+    }
+
+    /// Remember `formatted` as the new context once it reached `target`;
+    /// text that failed to inject must not be joined onto.
+    pub fn record(&self, target: &str, formatted: &str, now: Instant) {
+        if !self.is_enabled() || formatted.is_empty() {
+            return;
+        }
+        *self.last.lock().unwrap() = Some(LastInjection {
+            target: target.to_string(),
+            text: formatted.to_string(),
+            at: now,
+        });
+    }
+}
+
+impl Default for JoinFormatter {
+    fn default() -> Self {
+        Self::new(DEFAULT_RESET_GAP)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fresh_context_capitalizes_without_space() {
+        assert_eq!(join(None, "hello there."), "Hello there.");
+    }
+
+    #[test]
+    fn test_inserts_space_and_capitalizes_after_sentence() {
+        assert_eq!(join(Some("Hello there."), "how are you"), " How are you");
+    }
+
+    #[test]
+    fn test_mid_sentence_keeps_case() {
+        assert_eq!(join(Some("I think"), "so"), " so");
+        assert_eq!(join(Some("I think"), ", maybe"), ", maybe");
+    }
+
+    #[test]
+    fn test_no_space_between_cjk_runs() {
+        assert_eq!(join(Some("안녕하세요"), "반갑습니다"), "반갑습니다");
+        assert_eq!(join(Some("今日は"), "いい天気"), "いい天気");
+        assert_eq!(join(Some("안녕하세요."), "반갑습니다"), " 반갑습니다");
+        assert_eq!(join(Some("회의"), "agenda"), " agenda");
+    }
+
+    #[test]
+    fn test_no_space_after_newline() {
+        assert_eq!(join(Some("Dear team,\n"), "thanks"), "Thanks");
+    }
+
+    /// Format and record, as a successful injection does
+    fn inject(formatter: &JoinFormatter, target: &str, text: &str, now: Instant) -> String {
+        let formatted = formatter.format(target, text, now);
+        formatter.record(target, &formatted, now);
+        formatted
+    }
+
+    #[test]
+    fn test_formatter_tracks_target_and_gap() {
+        let formatter = JoinFormatter::new(Duration::from_secs(10));
+        formatter.set_enabled(true);
+        let t0 = Instant::now();
+
+        assert_eq!(inject(&formatter, "paste", "one.", t0), "One.");
+        assert_eq!(
+            inject(&formatter, "paste", "two", t0 + Duration::from_secs(1)),
+            " Two"
+        );
+        // Different target: fresh context
+        assert_eq!(
+            inject(&formatter, "typing", "three", t0 + Duration::from_secs(2)),
+            "Three"
+        );
+        // Long pause: fresh context
+        assert_eq!(
+            inject(&formatter, "typing", "four", t0 + Duration::from_secs(30)),
+            "Four"
+        );
+    }
+
+    #[test]
+    fn test_unrecorded_text_is_not_joined_onto() {
+        let formatter = JoinFormatter::default();
+        formatter.set_enabled(true);
+        let now = Instant::now();
+
+        inject(&formatter, "paste", "one.", now);
+        // Formatted, but the injection failed
+        assert_eq!(formatter.format("paste", "lost", now), " Lost");
+        assert_eq!(inject(&formatter, "paste", "two", now), " Two");
+        assert_eq!(inject(&formatter, "paste", "three", now), " three");
+    }
+
+    #[test]
+    fn test_reset_and_disable() {
+        let formatter = JoinFormatter::default();
+        let now = Instant::now();
+        assert_eq!(
+            inject(&formatter, "paste", "off by default", now),
+            "off by default"
+        );
+
+        formatter.set_enabled(true);
+        inject(&formatter, "paste", "one.", now);
+        formatter.reset();
+        assert_eq!(inject(&formatter, "paste", "two", now), "Two");
+
+        formatter.set_enabled(false);
+        assert_eq!(inject(&formatter, "paste", "three", now), "three");
+    }
+}
@@ -0,0 +1,491 @@
+//! Fragmentation for links with small packets, like BLE GATT writes: an
+//! encoded message (JSON or a binary codec) is split into MTU-sized
+//! fragments, each with a header, and a reassembler on the other side puts
+//! it back together, tolerating duplicates, reordering and lost fragments.
+//!
+//! Fragment layout, big-endian:
+//! | message id u16 | index u16 | count u16 | CRC-32 u32 | payload |
+//! The CRC covers the first six header bytes and the payload, so a corrupt
+//! fragment is rejected on its own instead of poisoning the message.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+pub const HEADER_LEN: usize = 10;
+
+/// Incomplete messages kept at once; the oldest is dropped beyond this
+const MAX_PENDING: usize = 32;
+/// Completed message ids remembered so late duplicates are ignored
+const RECENT_IDS: usize = 64;
+/// Reassembled size limit a receiver may use; far above any dictation
+pub const DEFAULT_MAX_MESSAGE_LEN: usize = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fragment {
+    pub message_id: u16,
+    pub index: u16,
+    pub count: u16,
+    pub payload: Vec<u8>,
+}
+
+impl Fragment {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.payload.len());
+        bytes.extend_from_slice(&self.message_id.to_be_bytes());
+        bytes.extend_from_slice(&self.index.to_be_bytes());
+        bytes.extend_from_slice(&self.count.to_be_bytes());
+        let crc = crc32(&[&bytes, &self.payload]);
+        bytes.extend_from_slice(&crc.to_be_bytes());
+        bytes.extend_from_slice(&self.payload);
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER_LEN {
+            return Err(format!("Fragment too short: {} bytes", bytes.len()));
+        }
+        let field = |at: usize| u16::from_be_bytes([bytes[at], bytes[at + 1]]);
+        let (message_id, index, count) = (field(0), field(2), field(4));
+        let crc = u32::from_be_bytes([bytes[6], bytes[7], bytes[8], bytes[9]]);
+        let payload = &bytes[HEADER_LEN..];
+        if crc32(&[&bytes[..6], payload]) != crc {
+            return Err(format!(
+                "Checksum mismatch in fragment {index} of message {message_id}"
+            ));
+        }
+        if count == 0 || index >= count {
+            return Err(format!(
+                "Invalid fragment {index}/{count} of message {message_id}"
+            ));
+        }
+        Ok(Self {
+            message_id,
+            index,
+            count,
+            payload: payload.to_vec(),
+        })
+    }
+}
+
+/// CRC-32 (IEEE 802.3, as in zlib) over several slices
+fn crc32(parts: &[&[u8]]) -> u32 {
+    let mut crc = !0u32;
+    for byte in parts.iter().flat_map(|part| part.iter()) {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+// --- Sending ---
+
+/// Splits messages into fragments of at most `mtu` bytes, numbering them
+pub struct Fragmenter {
+    mtu: usize,
+    next_id: u16,
+}
+
+impl Fragmenter {
+    pub fn new(mtu: usize) -> Result<Self, String> {
+        if mtu <= HEADER_LEN {
+            return Err(format!(
+                "MTU {mtu} leaves no room for data after the {HEADER_LEN}-byte header"
+            ));
+        }
+        Ok(Self { mtu, next_id: 0 })
+    }
+
+    /// Encoded fragments in order; an empty message is one empty fragment
+    pub fn split(&mut self, message: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+        let chunk = self.mtu - HEADER_LEN;
+        let count = message.len().div_ceil(chunk).max(1);
+        let count = u16::try_from(count).map_err(|_| {
+            format!(
+                "Message of {} bytes needs {count} fragments at MTU {}",
+                message.len(),
+                self.mtu
+            )
+        })?;
+        let message_id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+
+        let chunks: Vec<&[u8]> = if message.is_empty() {
+            vec![&[]]
+        } else {
+            message.chunks(chunk).collect()
+        };
+        Ok(chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, payload)| {
+                Fragment {
+                    message_id,
+                    index: index as u16,
+                    count,
+                    payload: payload.to_vec(),
+                }
+                .encode()
+            })
+            .collect())
+    }
+}
+
+// --- Receiving ---
+
+struct Pending {
+    parts: Vec<Option<Vec<u8>>>,
+    received: usize,
+    /// Payload bytes buffered so far
+    len: usize,
+    started: Instant,
+}
+
+/// Collects fragments until a message is complete. Fragments may arrive in
+/// any order and more than once; a message missing fragments for longer
+/// than the timeout is dropped, so a newer message reusing its id starts
+/// afresh. Use one per connection, since message ids start over with each
+/// sender's `Fragmenter`.
+pub struct Reassembler {
+    timeout: Duration,
+    max_message_len: usize,
+    pending: HashMap<u16, Pending>,
+    recent: VecDeque<u16>,
+}
+
+impl Reassembler {
+    /// Messages longer than `max_message_len` bytes are refused.
+    pub fn new(timeout: Duration, max_message_len: usize) -> Self {
+        Self {
+            timeout,
+            max_message_len,
+            pending: HashMap::new(),
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Adds one received fragment; returns the message once it is complete.
+    /// Corrupt or malformed fragments are errors, duplicates are ignored.
+    /// A message that outgrows the size limit is dropped with an error.
+    pub fn push(&mut self, bytes: &[u8], now: Instant) -> Result<Option<Vec<u8>>, String> {
+        for id in self.expire(now) {
+            log::warn!("Dropping incomplete message {id}: timed out");
+        }
+        let fragment = Fragment::decode(bytes)?;
+        let id = fragment.message_id;
+        // Every fragment but the last carries at least one byte
+        if fragment.count as usize > self.max_message_len.max(1) {
+            return Err(format!(
+                "Message {id} of {} fragments exceeds {} bytes",
+                fragment.count, self.max_message_len
+            ));
+        }
+        // A retransmit of something already delivered. Ids wrap at 65536,
+        // long after they leave the recent list.
+        if self.recent.contains(&id) {
+            return Ok(None);
+        }
+
+        // Same id with a different count: the old message was abandoned
+        if self
+            .pending
+            .get(&id)
+            .is_some_and(|p| p.parts.len() != fragment.count as usize)
+        {
+            self.pending.remove(&id);
+        }
+        if !self.pending.contains_key(&id) && self.pending.len() >= MAX_PENDING {
+            self.drop_oldest();
+        }
+        let pending = self.pending.entry(id).or_insert_with(|| Pending {
+            parts: vec![None; fragment.count as usize],
+            received: 0,
+            len: 0,
+            started: now,
+        });
+        let slot = &mut pending.parts[fragment.index as usize];
+        if slot.is_some() {
+            return Ok(None);
+        }
+        if pending.len + fragment.payload.len() > self.max_message_len {
+            self.pending.remove(&id);
+            return Err(format!(
+                "Message {id} exceeds {} bytes",
+                self.max_message_len
+            ));
+        }
+        pending.len += fragment.payload.len();
+        *slot = Some(fragment.payload);
+        pending.received += 1;
+        if pending.received < pending.parts.len() {
+            return Ok(None);
+        }
+
+        let pending = self.pending.remove(&id).expect("pending message");
+        self.remember(id);
+        Ok(Some(
+            pending.parts.into_iter().flatten().flatten().collect(),
+        ))
+    }
+
+    /// Drops messages still incomplete after the timeout, returning their ids
+    pub fn expire(&mut self, now: Instant) -> Vec<u16> {
+        let mut expired: Vec<u16> = self
+            .pending
+            .iter()
+            .filter(|(_, p)| now.saturating_duration_since(p.started) >= self.timeout)
+            .map(|(id, _)| *id)
+            .collect();
+        expired.sort_unstable();
+        for id in &expired {
+            self.pending.remove(id);
+        }
+        expired
+    }
+
+    /// Indexes of fragments not yet received for an incomplete message,
+    /// for links that can ask the sender to retransmit
+    pub fn missing(&self, message_id: u16) -> Vec<u16> {
+        self.pending
+            .get(&message_id)
+            .map(|p| {
+                p.parts
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, part)| part.is_none())
+                    .map(|(index, _)| index as u16)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn drop_oldest(&mut self) {
+        let oldest = self
+            .pending
+            .iter()
+            .min_by_key(|(_, p)| p.started)
+            .map(|(id, _)| *id);
+        if let Some(id) = oldest {
+            log::warn!("Dropping incomplete message {id}: too many pending");
+            self.pending.remove(&id);
+        }
+    }
+
+    fn remember(&mut self, id: u16) {
+        self.recent.push_back(id);
+        if self.recent.len() > RECENT_IDS {
+            self.recent.pop_front();
+        }
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    fn long_final() -> Vec<u8> {
+        let text = "회의는 세 시에 시작합니다, and the quarterly numbers look fine. ".repeat(20);
+        format!(
+            r#"{{"type":"FINAL","clientId":"phone-01","timestamp":1,"payload":{{"sessionId":"s","text":"{text}","confidence":0.9}}}}"#
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_crc32_check_value() {
+        assert_eq!(crc32(&[b"1234", b"56789"]), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_roundtrip_at_several_mtus() {
+        let message = long_final();
+        for mtu in [11, 20, 23, 185, 512, 4096] {
+            let fragments = Fragmenter::new(mtu).unwrap().split(&message).unwrap();
+            assert!(fragments.iter().all(|f| f.len() <= mtu));
+            let mut reassembler = Reassembler::new(TIMEOUT, DEFAULT_MAX_MESSAGE_LEN);
+            let now = Instant::now();
+            let (last, rest) = fragments.split_last().unwrap();
+            for fragment in rest {
+                assert_eq!(reassembler.push(fragment, now).unwrap(), None);
+            }
+            assert_eq!(reassembler.push(last, now).unwrap(), Some(message.clone()));
+            assert_eq!(reassembler.pending_count(), 0);
+        }
+    }
+
+    #[test]
+    fn test_empty_message_is_one_fragment() {
+        let fragments = Fragmenter::new(20).unwrap().split(&[]).unwrap();
+        assert_eq!(fragments.len(), 1);
+        let mut reassembler = Reassembler::new(TIMEOUT, DEFAULT_MAX_MESSAGE_LEN);
+        assert_eq!(
+            reassembler.push(&fragments[0], Instant::now()).unwrap(),
+            Some(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_out_of_order_duplicated_and_interleaved() {
+        let mut fragmenter = Fragmenter::new(40).unwrap();
+        let first = long_final();
+        let second = b"{\"type\":\"HEARTBEAT\",\"clientId\":\"phone-01\"}".repeat(3);
+        let a = fragmenter.split(&first).unwrap();
+        let b = fragmenter.split(&second).unwrap();
+
+        let mut order: Vec<&Vec<u8>> = Vec::new();
+        for i in (0..a.len().max(b.len())).rev() {
+            order.extend(a.get(i));
+            order.extend(b.get(i));
+            order.extend(a.get(i)); // duplicate
+        }
+        let mut reassembler = Reassembler::new(TIMEOUT, DEFAULT_MAX_MESSAGE_LEN);
+        let now = Instant::now();
+        let complete: Vec<Vec<u8>> = order
+            .into_iter()
+            .filter_map(|f| reassembler.push(f, now).unwrap())
+            .collect();
+        assert_eq!(complete.len(), 2);
+        assert!(complete.contains(&first));
+        assert!(complete.contains(&second));
+
+        // A late retransmit after delivery is not delivered again
+        assert_eq!(reassembler.push(&a[0], now).unwrap(), None);
+        assert_eq!(reassembler.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_lost_fragment_times_out() {
+        let fragments = Fragmenter::new(64).unwrap().split(&long_final()).unwrap();
+        let mut reassembler = Reassembler::new(TIMEOUT, DEFAULT_MAX_MESSAGE_LEN);
+        let start = Instant::now();
+        for (i, fragment) in fragments.iter().enumerate() {
+            if i != 3 {
+                assert_eq!(reassembler.push(fragment, start).unwrap(), None);
+            }
+        }
+        assert_eq!(reassembler.missing(0), vec![3]);
+        assert!(reassembler
+            .expire(start + Duration::from_secs(1))
+            .is_empty());
+        assert_eq!(reassembler.expire(start + TIMEOUT), vec![0]);
+        assert_eq!(reassembler.pending_count(), 0);
+        assert!(reassembler.missing(0).is_empty());
+    }
+
+    #[test]
+    fn test_stale_message_is_not_completed_by_a_newer_one() {
+        let stale = Fragmenter::new(64).unwrap().split(&long_final()).unwrap();
+        let mut reassembler = Reassembler::new(TIMEOUT, DEFAULT_MAX_MESSAGE_LEN);
+        let start = Instant::now();
+        // The sender restarts and reuses id 0 for a message of the same length
+        let newer: Vec<u8> = long_final()
+            .iter()
+            .map(|b| b.to_ascii_uppercase())
+            .collect();
+        let fresh = Fragmenter::new(64).unwrap().split(&newer).unwrap();
+        assert_eq!(stale.len(), fresh.len());
+
+        assert_eq!(reassembler.push(&stale[0], start).unwrap(), None);
+        let later = start + TIMEOUT;
+        let complete: Vec<Vec<u8>> = fresh[1..]
+            .iter()
+            .chain(&fresh[..1])
+            .filter_map(|f| reassembler.push(f, later).unwrap())
+            .collect();
+        assert_eq!(complete, vec![newer]);
+    }
+
+    #[test]
+    fn test_oversized_message_is_refused() {
+        let message = long_final();
+        let fragments = Fragmenter::new(64).unwrap().split(&message).unwrap();
+        let mut reassembler = Reassembler::new(TIMEOUT, message.len() - 1);
+        let now = Instant::now();
+        let (last, rest) = fragments.split_last().unwrap();
+        for fragment in rest {
+            assert_eq!(reassembler.push(fragment, now).unwrap(), None);
+        }
+        let err = reassembler.push(last, now).unwrap_err();
+        assert!(err.contains("exceeds"), "{err}");
+        assert_eq!(reassembler.pending_count(), 0);
+
+        // More fragments than the limit has bytes can't be a valid message
+        let mut small = Reassembler::new(TIMEOUT, 16);
+        let many = Fragment {
+            message_id: 1,
+            index: 0,
+            count: 17,
+            payload: vec![0],
+        };
+        assert!(small.push(&many.encode(), now).is_err());
+        assert_eq!(small.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_retransmit_fills_the_gap() {
+        let fragments = Fragmenter::new(64).unwrap().split(&long_final()).unwrap();
+        let mut reassembler = Reassembler::new(TIMEOUT, DEFAULT_MAX_MESSAGE_LEN);
+        let now = Instant::now();
+        for fragment in &fragments[1..] {
+            assert_eq!(reassembler.push(fragment, now).unwrap(), None);
+        }
+        assert_eq!(reassembler.missing(0), vec![0]);
+        assert_eq!(
+            reassembler.push(&fragments[0], now).unwrap(),
+            Some(long_final())
+        );
+    }
+
+    #[test]
+    fn test_corrupt_and_malformed_fragments_are_rejected() {
+        let fragments = Fragmenter::new(32).unwrap().split(&long_final()).unwrap();
+        let mut reassembler = Reassembler::new(TIMEOUT, DEFAULT_MAX_MESSAGE_LEN);
+        let now = Instant::now();
+
+        let mut corrupt = fragments[0].clone();
+        corrupt[HEADER_LEN + 2] ^= 0x40;
+        let err = reassembler.push(&corrupt, now).unwrap_err();
+        assert!(err.contains("Checksum mismatch"), "{err}");
+        assert!(reassembler.push(&fragments[0][..4], now).is_err());
+
+        let bad_index = Fragment {
+            message_id: 9,
+            index: 2,
+            count: 2,
+            payload: vec![1],
+        };
+        assert!(reassembler.push(&bad_index.encode(), now).is_err());
+        assert_eq!(reassembler.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_message_ids_wrap_and_mtu_is_checked() {
+        assert!(Fragmenter::new(HEADER_LEN).is_err());
+        let mut fragmenter = Fragmenter::new(20).unwrap();
+        fragmenter.next_id = u16::MAX;
+        let ids: Vec<u16> = [b"a", b"b"]
+            .iter()
+            .map(|m| {
+                Fragment::decode(&fragmenter.split(*m).unwrap()[0])
+                    .unwrap()
+                    .message_id
+            })
+            .collect();
+        assert_eq!(ids, vec![u16::MAX, 0]);
+        let too_long = vec![0u8; (u16::MAX as usize + 1) * 10];
+        assert!(fragmenter.split(&too_long).is_err());
+    }
+}
@@ -0,0 +1,688 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// What happened to a session's FINAL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Outcome {
+    Injected,
+    /// Queued for review
+    Pending,
+    /// Waiting for confirmation (low confidence or sensitive data)
+    Held,
+    Rejected,
+    /// The FINAL could not be inserted
+    Failed,
+    /// Client disconnected or started over before sending a FINAL
+    Abandoned,
+}
+
+impl Outcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Outcome::Injected => "injected",
+            Outcome::Pending => "pending",
+            Outcome::Held => "held",
+            Outcome::Rejected => "rejected",
+            Outcome::Failed => "failed",
+            Outcome::Abandoned => "abandoned",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        [
+            Outcome::Injected,
+            Outcome::Pending,
+            Outcome::Held,
+            Outcome::Rejected,
+            Outcome::Failed,
+            Outcome::Abandoned,
+        ]
+        .into_iter()
+        .find(|o| o.as_str() == value)
+    }
+}
+
+/// One recorded dictation session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub client_id: String,
+    pub device_model: String,
+    pub engine: String,
+    pub session_id: String,
+    /// Unix milliseconds
+    pub started_at: i64,
+    pub ended_at: Option<i64>,
+    /// Protocol timestamps (phone clock, Unix ms) of the first PARTIAL and the FINAL
+    pub speech_started_at: Option<i64>,
+    pub speech_ended_at: Option<i64>,
+    pub partial_count: u32,
+    pub final_text: Option<String>,
+    pub confidence: Option<f64>,
+    /// None while the session is still open
+    pub outcome: Option<Outcome>,
+}
+
+/// Identifies the session being recorded
+#[derive(Debug, Clone, Copy)]
+pub struct SessionRef<'a> {
+    pub client_id: &'a str,
+    pub device_model: &'a str,
+    pub engine: &'a str,
+    pub session_id: &'a str,
+}
+
+/// Limits applied after every finished session; `None` keeps everything.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Retention {
+    pub max_entries: Option<u32>,
+    pub max_age_days: Option<u32>,
+}
+
+/// Sessions to export; every filter is optional
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistorySelection {
+    /// Exactly these entries; the other filters still apply
+    pub ids: Option<Vec<i64>>,
+    /// Unix ms bounds on `started_at`, inclusive
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub client_id: Option<String>,
+    /// Case-insensitive substring of the final text
+    pub text: Option<String>,
+}
+
+/// Filter and page for `History::query`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryQuery {
+    /// Case-insensitive substring of the final text
+    pub text: Option<String>,
+    pub client_id: Option<String>,
+    pub offset: u32,
+    /// 0 means the default page size
+    pub limit: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPage {
+    pub entries: Vec<HistoryEntry>,
+    /// Matching entries across all pages
+    pub total: u32,
+}
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    client_id     TEXT NOT NULL,
+    device_model  TEXT NOT NULL,
+    engine        TEXT NOT NULL,
+    session_id    TEXT NOT NULL,
+    started_at    INTEGER NOT NULL,
+    ended_at      INTEGER,
+    partial_count INTEGER NOT NULL DEFAULT 0,
+    final_text    TEXT,
+    confidence    REAL,
+    outcome       TEXT
+);
+CREATE INDEX IF NOT EXISTS sessions_started_at ON sessions (started_at);
+CREATE INDEX IF NOT EXISTS sessions_open ON sessions (client_id, session_id) WHERE outcome IS NULL;
+CREATE TABLE IF NOT EXISTS settings (
+    key   TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+";
+
+/// Applied in order on top of `SCHEMA`; `PRAGMA user_version` counts the applied ones.
+const MIGRATIONS: &[&str] = &[
+    // Protocol timestamps, for subtitle export timing
+    "ALTER TABLE sessions ADD COLUMN speech_started_at INTEGER;
+     ALTER TABLE sessions ADD COLUMN speech_ended_at INTEGER;",
+];
+
+/// SQLite-backed dictation history. Recording errors are returned to the
+/// caller, which logs them; history never blocks dictation.
+pub struct History {
+    conn: Mutex<Connection>,
+    retention: Mutex<Retention>,
+}
+
+impl History {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Create {}: {e}", dir.display()))?;
+        }
+        let conn = Connection::open(path).map_err(|e| format!("Open {}: {e}", path.display()))?;
+        Self::with_connection(conn)
+    }
+
+    /// Throwaway in-memory database
+    pub fn in_memory() -> Self {
+        let conn = Connection::open_in_memory().expect("in-memory SQLite");
+        Self::with_connection(conn).expect("history schema")
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, String> {
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| format!("Create history schema: {e}"))?;
+        let version: usize = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| format!("Read history schema version: {e}"))?;
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            conn.execute_batch(&format!(
+                "BEGIN; {migration} PRAGMA user_version = {}; COMMIT;",
+                index + 1
+            ))
+            .map_err(|e| format!("Migrate history schema: {e}"))?;
+        }
+        let stored: Option<String> = conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = 'retention'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Read history settings: {e}"))?;
+        let retention = match stored {
+            Some(json) => {
+                serde_json::from_str(&json).map_err(|e| format!("Parse history retention: {e}"))?
+            }
+            None => Retention::default(),
+        };
+        Ok(Self {
+            conn: Mutex::new(conn),
+            retention: Mutex::new(retention),
+        })
+    }
+
+    pub fn retention(&self) -> Retention {
+        *self.retention.lock().unwrap()
+    }
+
+    /// Store the limits and prune right away.
+    pub fn set_retention(&self, retention: Retention, now_ms: i64) -> Result<(), String> {
+        let json = serde_json::to_string(&retention)
+            .map_err(|e| format!("Encode history retention: {e}"))?;
+        {
+            let conn = self.conn.lock().unwrap();
+            conn.execute(
+                "INSERT OR REPLACE INTO settings (key, value) VALUES ('retention', ?1)",
+                params![json],
+            )
+            .map_err(|e| format!("Save history retention: {e}"))?;
+        }
+        *self.retention.lock().unwrap() = retention;
+        self.prune(now_ms)
+    }
+
+    /// Open a session on PTT_START. A session the client left open is abandoned.
+    pub fn start(&self, session: SessionRef, now_ms: i64) -> Result<(), String> {
+        self.abandon_open(session.client_id, now_ms)?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO sessions (client_id, device_model, engine, session_id, started_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                session.client_id,
+                session.device_model,
+                session.engine,
+                session.session_id,
+                now_ms
+            ],
+        )
+        .map_err(|e| format!("Record session start: {e}"))?;
+        Ok(())
+    }
+
+    /// Count a PARTIAL; `timestamp` is its protocol timestamp.
+    pub fn partial(&self, client_id: &str, session_id: &str, timestamp: i64) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE sessions SET partial_count = partial_count + 1,
+                 speech_started_at = COALESCE(speech_started_at, ?3)
+             WHERE client_id = ?1 AND session_id = ?2 AND outcome IS NULL",
+            params![client_id, session_id, timestamp],
+        )
+        .map_err(|e| format!("Record partial: {e}"))?;
+        Ok(())
+    }
+
+    /// Close the session with its FINAL (`timestamp` is the FINAL's protocol
+    /// timestamp). Sessions without a PTT_START are recorded as starting and
+    /// ending now.
+    pub fn finish(
+        &self,
+        session: SessionRef,
+        text: &str,
+        confidence: f64,
+        timestamp: i64,
+        outcome: Outcome,
+        now_ms: i64,
+    ) -> Result<(), String> {
+        {
+            let conn = self.conn.lock().unwrap();
+            let updated = conn
+                .execute(
+                    "UPDATE sessions
+                     SET ended_at = ?3, final_text = ?4, confidence = ?5, outcome = ?6,
+                         speech_ended_at = ?7
+                     WHERE client_id = ?1 AND session_id = ?2 AND outcome IS NULL",
+                    params![
+                        session.client_id,
+                        session.session_id,
+                        now_ms,
+                        text,
+                        confidence,
+                        outcome.as_str(),
+                        timestamp
+                    ],
+                )
+                .map_err(|e| format!("Record FINAL: {e}"))?;
+            if updated == 0 {
+                conn.execute(
+                    "INSERT INTO sessions (client_id, device_model, engine, session_id,
+                         started_at, ended_at, final_text, confidence, outcome, speech_ended_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        session.client_id,
+                        session.device_model,
+                        session.engine,
+                        session.session_id,
+                        now_ms,
+                        text,
+                        confidence,
+                        outcome.as_str(),
+                        timestamp
+                    ],
+                )
+                .map_err(|e| format!("Record FINAL: {e}"))?;
+            }
+        }
+        self.prune(now_ms)
+    }
+
+    /// Update the outcome once a held or queued FINAL is decided.
+    pub fn set_outcome(
+        &self,
+        client_id: &str,
+        session_id: &str,
+        outcome: Outcome,
+    ) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE sessions SET outcome = ?3 WHERE client_id = ?1 AND session_id = ?2",
+            params![client_id, session_id, outcome.as_str()],
+        )
+        .map_err(|e| format!("Record outcome: {e}"))?;
+        Ok(())
+    }
+
+    /// Mark the client's open sessions as abandoned (disconnect, new PTT_START).
+    pub fn abandon_open(&self, client_id: &str, now_ms: i64) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE sessions SET ended_at = ?2, outcome = ?3
+             WHERE client_id = ?1 AND outcome IS NULL",
+            params![client_id, now_ms, Outcome::Abandoned.as_str()],
+        )
+        .map_err(|e| format!("Record abandoned session: {e}"))?;
+        Ok(())
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<HistoryEntry>, String> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            &format!("SELECT {COLUMNS} FROM sessions WHERE id = ?1"),
+            params![id],
+            row_to_entry,
+        )
+        .optional()
+        .map_err(|e| format!("Read history: {e}"))
+    }
+
+    /// Newest first
+    pub fn query(&self, query: &HistoryQuery) -> Result<HistoryPage, String> {
+        let text = query
+            .text
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(|t| format!("%{}%", escape_like(t)));
+        let limit = match query.limit {
+            0 => DEFAULT_PAGE_SIZE,
+            n => n,
+        };
+        let filter = "(?1 IS NULL OR final_text LIKE ?1 ESCAPE '\\')
+                      AND (?2 IS NULL OR client_id = ?2)";
+
+        let conn = self.conn.lock().unwrap();
+        let total: u32 = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM sessions WHERE {filter}"),
+                params![text, query.client_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Count history: {e}"))?;
+        let mut statement = conn
+            .prepare(&format!(
+                "SELECT {COLUMNS} FROM sessions WHERE {filter}
+                 ORDER BY started_at DESC, id DESC LIMIT ?3 OFFSET ?4"
+            ))
+            .map_err(|e| format!("Query history: {e}"))?;
+        let entries = statement
+            .query_map(
+                params![text, query.client_id, limit, query.offset],
+                row_to_entry,
+            )
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Query history: {e}"))?;
+        Ok(HistoryPage { entries, total })
+    }
+
+    /// Oldest first, for export
+    pub fn select(&self, selection: &HistorySelection) -> Result<Vec<HistoryEntry>, String> {
+        let text = selection
+            .text
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(|t| format!("%{}%", escape_like(t)));
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn
+            .prepare(&format!(
+                "SELECT {COLUMNS} FROM sessions
+                 WHERE (?1 IS NULL OR final_text LIKE ?1 ESCAPE '\\')
+                   AND (?2 IS NULL OR client_id = ?2)
+                   AND (?3 IS NULL OR started_at >= ?3)
+                   AND (?4 IS NULL OR started_at <= ?4)
+                 ORDER BY started_at, id"
+            ))
+            .map_err(|e| format!("Query history: {e}"))?;
+        let entries = statement
+            .query_map(
+                params![text, selection.client_id, selection.from, selection.to],
+                row_to_entry,
+            )
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Query history: {e}"))?;
+        Ok(match &selection.ids {
+            Some(ids) => entries
+                .into_iter()
+                .filter(|e| ids.contains(&e.id))
+                .collect(),
+            None => entries,
+        })
+    }
+
+    pub fn delete(&self, id: i64) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM sessions WHERE id = ?1", params![id])
+            .map_err(|e| format!("Delete history entry: {e}"))?;
+        Ok(())
+    }
+
+    pub fn clear(&self) -> Result<(), String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM sessions", [])
+            .map_err(|e| format!("Clear history: {e}"))?;
+        Ok(())
+    }
+
+    fn prune(&self, now_ms: i64) -> Result<(), String> {
+        let retention = self.retention();
+        let conn = self.conn.lock().unwrap();
+        if let Some(days) = retention.max_age_days {
+            conn.execute(
+                "DELETE FROM sessions WHERE started_at < ?1",
+                params![now_ms - i64::from(days) * DAY_MS],
+            )
+            .map_err(|e| format!("Prune history: {e}"))?;
+        }
+        if let Some(max) = retention.max_entries {
+            conn.execute(
+                "DELETE FROM sessions WHERE id NOT IN
+                     (SELECT id FROM sessions ORDER BY started_at DESC, id DESC LIMIT ?1)",
+                params![max],
+            )
+            .map_err(|e| format!("Prune history: {e}"))?;
+        }
+        Ok(())
+    }
+}
+
+const COLUMNS: &str = "id, client_id, device_model, engine, session_id, started_at, ended_at,
+                       partial_count, final_text, confidence, outcome,
+                       speech_started_at, speech_ended_at";
+
+fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<HistoryEntry> {
+    let outcome: Option<String> = row.get(10)?;
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        client_id: row.get(1)?,
+        device_model: row.get(2)?,
+        engine: row.get(3)?,
+        session_id: row.get(4)?,
+        started_at: row.get(5)?,
+        ended_at: row.get(6)?,
+        speech_started_at: row.get(11)?,
+        speech_ended_at: row.get(12)?,
+        partial_count: row.get(7)?,
+        final_text: row.get(8)?,
+        confidence: row.get(9)?,
+        outcome: outcome.as_deref().and_then(Outcome::parse),
+    })
+}
+
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T0: i64 = 1_700_000_000_000;
+
+    fn session(session_id: &str) -> SessionRef<'_> {
+        SessionRef {
+            client_id: "phone-01",
+            device_model: "Pixel 8",
+            engine: "Google",
+            session_id,
+        }
+    }
+
+    fn finish(history: &History, session_id: &str, text: &str, now_ms: i64) {
+        history
+            .finish(
+                session(session_id),
+                text,
+                0.9,
+                now_ms,
+                Outcome::Injected,
+                now_ms,
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_session_lifecycle_is_recorded() {
+        let history = History::in_memory();
+        history.start(session("s-1"), T0).unwrap();
+        history.partial("phone-01", "s-1", T0 + 300).unwrap();
+        history.partial("phone-01", "s-1", T0 + 600).unwrap();
+        finish(&history, "s-1", "Hello there.", T0 + 1500);
+
+        let page = history.query(&HistoryQuery::default()).unwrap();
+        assert_eq!(page.total, 1);
+        let entry = &page.entries[0];
+        assert_eq!(entry.device_model, "Pixel 8");
+        assert_eq!(entry.started_at, T0);
+        assert_eq!(entry.ended_at, Some(T0 + 1500));
+        assert_eq!(entry.speech_started_at, Some(T0 + 300));
+        assert_eq!(entry.speech_ended_at, Some(T0 + 1500));
+        assert_eq!(entry.partial_count, 2);
+        assert_eq!(entry.final_text.as_deref(), Some("Hello there."));
+        assert_eq!(entry.outcome, Some(Outcome::Injected));
+        assert_eq!(history.get(entry.id).unwrap().as_ref(), Some(entry));
+    }
+
+    #[test]
+    fn test_open_sessions_are_abandoned() {
+        let history = History::in_memory();
+        history.start(session("s-1"), T0).unwrap();
+        // New PTT_START before a FINAL
+        history.start(session("s-2"), T0 + 10).unwrap();
+        history.abandon_open("phone-01", T0 + 20).unwrap();
+
+        let page = history.query(&HistoryQuery::default()).unwrap();
+        assert!(page
+            .entries
+            .iter()
+            .all(|e| e.outcome == Some(Outcome::Abandoned)));
+        assert_eq!(page.entries[0].session_id, "s-2");
+    }
+
+    #[test]
+    fn test_search_and_paging() {
+        let history = History::in_memory();
+        for i in 0..5 {
+            finish(&history, &format!("s-{i}"), &format!("note {i}"), T0 + i);
+        }
+        finish(&history, "s-x", "100% done", T0 + 10);
+
+        let page = history
+            .query(&HistoryQuery {
+                text: Some("NOTE".to_string()),
+                offset: 1,
+                limit: 2,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(page.total, 5);
+        let texts: Vec<_> = page
+            .entries
+            .iter()
+            .map(|e| e.final_text.clone().unwrap())
+            .collect();
+        assert_eq!(texts, vec!["note 3", "note 2"]);
+
+        let percent = HistoryQuery {
+            text: Some("%".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(history.query(&percent).unwrap().total, 1);
+    }
+
+    #[test]
+    fn test_retention_limits() {
+        let history = History::in_memory();
+        finish(&history, "old", "last month", T0 - 40 * DAY_MS);
+        for i in 0..4 {
+            finish(&history, &format!("s-{i}"), "recent", T0 + i);
+        }
+
+        history
+            .set_retention(
+                Retention {
+                    max_entries: None,
+                    max_age_days: Some(30),
+                },
+                T0,
+            )
+            .unwrap();
+        assert_eq!(history.query(&HistoryQuery::default()).unwrap().total, 4);
+
+        history
+            .set_retention(
+                Retention {
+                    max_entries: Some(2),
+                    max_age_days: None,
+                },
+                T0,
+            )
+            .unwrap();
+        let page = history.query(&HistoryQuery::default()).unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.entries[0].session_id, "s-3");
+
+        history.clear().unwrap();
+        assert_eq!(history.query(&HistoryQuery::default()).unwrap().total, 0);
+    }
+
+    #[test]
+    fn test_history_and_retention_survive_reopen() {
+        let path = std::env::temp_dir().join(format!("ptt-history-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let history = History::open(&path).unwrap();
+        let retention = Retention {
+            max_entries: Some(100),
+            max_age_days: Some(7),
+        };
+        history.set_retention(retention, T0).unwrap();
+        finish(&history, "s-1", "kept", T0);
+        drop(history);
+
+        let reopened = History::open(&path).unwrap();
+        assert_eq!(reopened.retention(), retention);
+        assert_eq!(reopened.query(&HistoryQuery::default()).unwrap().total, 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_select_for_export() {
+        let history = History::in_memory();
+        for i in 0..4 {
+            finish(&history, &format!("s-{i}"), &format!("note {i}"), T0 + i);
+        }
+
+        let all = history.select(&HistorySelection::default()).unwrap();
+        let sessions: Vec<_> = all.iter().map(|e| e.session_id.as_str()).collect();
+        assert_eq!(sessions, vec!["s-0", "s-1", "s-2", "s-3"]);
+
+        let selection = HistorySelection {
+            ids: Some(vec![all[0].id, all[2].id, all[3].id]),
+            from: Some(T0 + 1),
+            ..Default::default()
+        };
+        let picked = history.select(&selection).unwrap();
+        let sessions: Vec<_> = picked.iter().map(|e| e.session_id.as_str()).collect();
+        assert_eq!(sessions, vec!["s-2", "s-3"]);
+    }
+
+    #[test]
+    fn test_schema_migrates_existing_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn.execute(
+            "INSERT INTO sessions (client_id, device_model, engine, session_id, started_at,
+                 final_text, outcome)
+             VALUES ('phone-01', 'Pixel 8', 'Google', 's-1', ?1, 'before', 'injected')",
+            params![T0],
+        )
+        .unwrap();
+
+        let history = History::with_connection(conn).unwrap();
+        finish(&history, "s-2", "after", T0 + 10);
+
+        let entries = history.select(&HistorySelection::default()).unwrap();
+        assert_eq!(entries[0].speech_ended_at, None);
+        assert_eq!(entries[1].speech_ended_at, Some(T0 + 10));
+    }
+}
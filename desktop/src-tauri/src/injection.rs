@@ -1,6 +1,85 @@
+pub mod chain;
+pub mod fanout;
+pub mod mode;
+pub mod sink;
+pub mod undo;
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ws_server::{EventEmitter, ServerEvent};
+
+/// Editing key stroke sent to the focused app instead of literal text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyAction {
+    /// Shift+Enter — a line break that doesn't submit chat inputs
+    NewLine,
+    /// Two line breaks
+    NewParagraph,
+    Tab,
+    /// Plain Enter — submits forms and chat messages
+    Enter,
+    /// Cmd+A
+    SelectAll,
+}
+
+impl KeyAction {
+    /// Characters the action leaves before the cursor, if any can be erased again
+    pub fn inserted_chars(self) -> Option<usize> {
+        match self {
+            KeyAction::NewLine | KeyAction::Tab => Some(1),
+            KeyAction::NewParagraph => Some(2),
+            KeyAction::Enter | KeyAction::SelectAll => None,
+        }
+    }
+}
+
 /// Trait for text injection — enables testing without OS interaction
 pub trait TextInjector: Send + Sync {
     fn inject(&self, text: &str) -> Result<(), String>;
+
+    /// Delete `count` characters before the cursor (used by live typing).
+    fn erase(&self, _count: usize) -> Result<(), String> {
+        Err("Erase not supported by this injector".to_string())
+    }
+
+    /// Send an editing key stroke (used by voice commands).
+    fn press(&self, _action: KeyAction) -> Result<(), String> {
+        Err("Key actions not supported by this injector".to_string())
+    }
+
+    /// Name of the backend that handles calls right now, for undo bookkeeping.
+    fn target(&self) -> Option<String> {
+        None
+    }
+
+    /// Whether the most recent successful call put text at the cursor of the
+    /// focused app, where `erase` can take it back. Clipboard, history and
+    /// sink backends accept text without typing it.
+    fn inserts_at_cursor(&self) -> bool {
+        true
+    }
+
+    /// Check that the backend can currently inject (permissions, display, ...).
+    fn health_check(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Run an OS interaction on the main thread and wait for its result.
+fn run_on_main<F>(app_handle: &tauri::AppHandle, f: F) -> Result<(), String>
+where
+    F: FnOnce() -> Result<(), String> + Send + 'static,
+{
+    let (tx, rx) = std::sync::mpsc::channel();
+    app_handle
+        .run_on_main_thread(move || {
+            let _ = tx.send(f());
+        })
+        .map_err(|e| format!("Dispatch to main thread: {e}"))?;
+    rx.recv().map_err(|e| format!("Main thread recv: {e}"))?
 }
 
 /// macOS: clipboard backup -> set text -> Cmd+V -> restore clipboard
@@ -15,16 +94,23 @@ impl ClipboardPasteInjector {
     }
 }
 
+fn open_clipboard() -> Result<arboard::Clipboard, String> {
+    arboard::Clipboard::new().map_err(|e| format!("Clipboard init: {e}"))
+}
+
+fn set_clipboard_text(clipboard: &mut arboard::Clipboard, text: &str) -> Result<(), String> {
+    clipboard
+        .set_text(text)
+        .map_err(|e| format!("Clipboard set: {e}"))
+}
+
 fn do_paste(text: &str) -> Result<(), String> {
-    use arboard::Clipboard;
     use enigo::{Direction, Enigo, Key, Keyboard, Settings};
 
-    let mut clipboard = Clipboard::new().map_err(|e| format!("Clipboard init: {e}"))?;
+    let mut clipboard = open_clipboard()?;
     let backup = clipboard.get_text().ok();
 
-    clipboard
-        .set_text(text)
-        .map_err(|e| format!("Clipboard set: {e}"))?;
+    set_clipboard_text(&mut clipboard, text)?;
 
     let mut enigo = Enigo::new(&Settings::default()).map_err(|e| format!("Enigo init: {e}"))?;
     enigo
@@ -46,28 +132,184 @@ fn do_paste(text: &str) -> Result<(), String> {
     Ok(())
 }
 
+fn do_erase(count: usize) -> Result<(), String> {
+    use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+
+    let mut enigo = Enigo::new(&Settings::default()).map_err(|e| format!("Enigo init: {e}"))?;
+    for _ in 0..count {
+        enigo
+            .key(Key::Backspace, Direction::Click)
+            .map_err(|e| format!("Key click: {e}"))?;
+    }
+    Ok(())
+}
+
+fn do_press(action: KeyAction) -> Result<(), String> {
+    use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+
+    let mut enigo = Enigo::new(&Settings::default()).map_err(|e| format!("Enigo init: {e}"))?;
+    let (modifier, key, times) = match action {
+        KeyAction::NewLine => (Some(Key::Shift), Key::Return, 1),
+        KeyAction::NewParagraph => (Some(Key::Shift), Key::Return, 2),
+        KeyAction::Tab => (None, Key::Tab, 1),
+        KeyAction::Enter => (None, Key::Return, 1),
+        KeyAction::SelectAll => (Some(Key::Meta), Key::Unicode('a'), 1),
+    };
+    if let Some(modifier) = modifier {
+        enigo
+            .key(modifier, Direction::Press)
+            .map_err(|e| format!("Key press: {e}"))?;
+    }
+    let result = (0..times).try_for_each(|_| {
+        enigo
+            .key(key, Direction::Click)
+            .map_err(|e| format!("Key click: {e}"))
+    });
+    if let Some(modifier) = modifier {
+        enigo
+            .key(modifier, Direction::Release)
+            .map_err(|e| format!("Key release: {e}"))?;
+    }
+    result
+}
+
+fn do_type(text: &str) -> Result<(), String> {
+    use enigo::{Enigo, Keyboard, Settings};
+
+    let mut enigo = Enigo::new(&Settings::default()).map_err(|e| format!("Enigo init: {e}"))?;
+    enigo.text(text).map_err(|e| format!("Key text: {e}"))
+}
+
+fn do_copy(text: &str) -> Result<(), String> {
+    let mut clipboard = open_clipboard()?;
+    set_clipboard_text(&mut clipboard, text)
+}
+
+fn check_clipboard() -> Result<(), String> {
+    open_clipboard().map(|_| ())
+}
+
+fn check_keyboard() -> Result<(), String> {
+    enigo::Enigo::new(&enigo::Settings::default())
+        .map(|_| ())
+        .map_err(|e| format!("Enigo init: {e}"))
+}
+
 impl TextInjector for ClipboardPasteInjector {
     fn inject(&self, text: &str) -> Result<(), String> {
         let text = text.to_string();
-        let (tx, rx) = std::sync::mpsc::channel();
-        self.app_handle
-            .run_on_main_thread(move || {
-                let result = do_paste(&text);
-                let _ = tx.send(result);
-            })
-            .map_err(|e| format!("Dispatch to main thread: {e}"))?;
-        rx.recv().map_err(|e| format!("Main thread recv: {e}"))?
+        run_on_main(&self.app_handle, move || do_paste(&text))
+    }
+
+    fn erase(&self, count: usize) -> Result<(), String> {
+        run_on_main(&self.app_handle, move || do_erase(count))
+    }
+
+    fn press(&self, action: KeyAction) -> Result<(), String> {
+        run_on_main(&self.app_handle, move || do_press(action))
+    }
+
+    fn health_check(&self) -> Result<(), String> {
+        run_on_main(&self.app_handle, || {
+            check_clipboard()?;
+            check_keyboard()
+        })
+    }
+}
+
+/// Types the text key by key — slower than pasting but leaves the clipboard alone.
+pub struct TypingInjector {
+    app_handle: tauri::AppHandle,
+}
+
+impl TypingInjector {
+    pub fn new(app_handle: tauri::AppHandle) -> Self {
+        Self { app_handle }
+    }
+}
+
+impl TextInjector for TypingInjector {
+    fn inject(&self, text: &str) -> Result<(), String> {
+        let text = text.to_string();
+        run_on_main(&self.app_handle, move || do_type(&text))
+    }
+
+    fn erase(&self, count: usize) -> Result<(), String> {
+        run_on_main(&self.app_handle, move || do_erase(count))
+    }
+
+    fn press(&self, action: KeyAction) -> Result<(), String> {
+        run_on_main(&self.app_handle, move || do_press(action))
+    }
+
+    fn health_check(&self) -> Result<(), String> {
+        run_on_main(&self.app_handle, check_keyboard)
+    }
+}
+
+/// Puts the text on the clipboard and leaves pasting to the user — for
+/// secure fields and remote desktop sessions where synthetic keystrokes fail.
+pub struct ClipboardOnlyInjector {
+    app_handle: tauri::AppHandle,
+    emitter: Arc<dyn EventEmitter>,
+}
+
+impl ClipboardOnlyInjector {
+    pub fn new(app_handle: tauri::AppHandle, emitter: Arc<dyn EventEmitter>) -> Self {
+        Self {
+            app_handle,
+            emitter,
+        }
+    }
+}
+
+impl TextInjector for ClipboardOnlyInjector {
+    fn inject(&self, text: &str) -> Result<(), String> {
+        let owned = text.to_string();
+        run_on_main(&self.app_handle, move || do_copy(&owned))?;
+        self.emitter.emit(ServerEvent::ClipboardCopied {
+            text: text.to_string(),
+        });
+        Ok(())
+    }
+
+    fn inserts_at_cursor(&self) -> bool {
+        false
+    }
+
+    fn health_check(&self) -> Result<(), String> {
+        run_on_main(&self.app_handle, check_clipboard)
+    }
+}
+
+/// Last resort: accepts every text without touching the OS, so the FINAL
+/// still reaches the frontend history even when nothing can be typed.
+pub struct HistoryOnlyInjector;
+
+impl TextInjector for HistoryOnlyInjector {
+    fn inject(&self, text: &str) -> Result<(), String> {
+        log::info!("History-only injection ({} chars)", text.chars().count());
+        Ok(())
+    }
+
+    fn inserts_at_cursor(&self) -> bool {
+        false
     }
 }
 
 #[cfg(test)]
 pub mod testing {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::{Arc, Mutex};
 
     pub struct MockInjector {
         pub injected: Arc<Mutex<Vec<String>>>,
         pub should_fail: bool,
+        /// Calls that still fail before the mock works again
+        pending_failures: AtomicUsize,
+        /// False for a mock standing in for the clipboard or history
+        at_cursor: bool,
     }
 
     impl MockInjector {
@@ -77,27 +319,84 @@ pub mod testing {
                 Self {
                     injected: Arc::clone(&injected),
                     should_fail: false,
+                    pending_failures: AtomicUsize::new(0),
+                    at_cursor: true,
                 },
                 injected,
             )
         }
 
+        /// Mock that accepts text without typing it, like the clipboard
+        pub fn off_cursor() -> (Self, Arc<Mutex<Vec<String>>>) {
+            let (mut mock, injected) = Self::new();
+            mock.at_cursor = false;
+            (mock, injected)
+        }
+
         pub fn failing() -> Self {
             Self {
                 injected: Arc::new(Mutex::new(Vec::new())),
                 should_fail: true,
+                pending_failures: AtomicUsize::new(0),
+                at_cursor: true,
             }
         }
+
+        /// Make the next `count` calls fail, as a transient error would
+        pub fn fail_next(&self, count: usize) {
+            self.pending_failures.store(count, Ordering::SeqCst);
+        }
+
+        fn fails(&self) -> bool {
+            let transient = self
+                .pending_failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            self.should_fail || transient
+        }
     }
 
     impl TextInjector for MockInjector {
         fn inject(&self, text: &str) -> Result<(), String> {
-            if self.should_fail {
+            if self.fails() {
                 return Err("Mock injection failure".to_string());
             }
             self.injected.lock().unwrap().push(text.to_string());
             Ok(())
         }
+
+        fn erase(&self, count: usize) -> Result<(), String> {
+            if self.fails() {
+                return Err("Mock injection failure".to_string());
+            }
+            self.injected
+                .lock()
+                .unwrap()
+                .push(format!("<erase {count}>"));
+            Ok(())
+        }
+
+        fn press(&self, action: KeyAction) -> Result<(), String> {
+            if self.fails() {
+                return Err("Mock injection failure".to_string());
+            }
+            self.injected
+                .lock()
+                .unwrap()
+                .push(format!("<press {action:?}>"));
+            Ok(())
+        }
+
+        fn inserts_at_cursor(&self) -> bool {
+            self.at_cursor
+        }
+
+        fn health_check(&self) -> Result<(), String> {
+            if self.should_fail {
+                return Err("Mock injector unavailable".to_string());
+            }
+            Ok(())
+        }
     }
 }
 
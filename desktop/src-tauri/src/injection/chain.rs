@@ -0,0 +1,197 @@
+use std::sync::{Arc, Mutex};
+
+use super::{KeyAction, TextInjector};
+
+/// Result of probing one backend of a chain
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct BackendHealth {
+    pub name: String,
+    pub usable: bool,
+    pub error: Option<String>,
+}
+
+struct Backend {
+    name: String,
+    injector: Arc<dyn TextInjector>,
+}
+
+/// Tries injectors in order until one succeeds (e.g. paste -> typing -> history-only).
+/// Every call starts again from the first backend: a fallback only stands in
+/// for the call whose preferred backend failed, so one transient paste failure
+/// can't route later dictations to clipboard-only or history-only.
+pub struct ChainInjector {
+    backends: Vec<Backend>,
+    last_working: Mutex<Option<usize>>,
+}
+
+impl ChainInjector {
+    pub fn new(backends: Vec<(String, Arc<dyn TextInjector>)>) -> Self {
+        Self {
+            backends: backends
+                .into_iter()
+                .map(|(name, injector)| Backend { name, injector })
+                .collect(),
+            last_working: Mutex::new(None),
+        }
+    }
+
+    /// Name of the backend that handled the most recent successful call
+    pub fn last_working(&self) -> Option<String> {
+        let last = *self.last_working.lock().unwrap();
+        last.map(|i| self.backends[i].name.clone())
+    }
+
+    /// Probe every backend and report which ones are usable right now.
+    pub fn self_test(&self) -> Vec<BackendHealth> {
+        self.backends
+            .iter()
+            .map(|b| match b.injector.health_check() {
+                Ok(()) => BackendHealth {
+                    name: b.name.clone(),
+                    usable: true,
+                    error: None,
+                },
+                Err(e) => BackendHealth {
+                    name: b.name.clone(),
+                    usable: false,
+                    error: Some(e),
+                },
+            })
+            .collect()
+    }
+
+    fn run(&self, op: impl Fn(&dyn TextInjector) -> Result<(), String>) -> Result<(), String> {
+        let mut errors = Vec::new();
+        for (i, backend) in self.backends.iter().enumerate() {
+            match op(backend.injector.as_ref()) {
+                Ok(()) => {
+                    *self.last_working.lock().unwrap() = Some(i);
+                    return Ok(());
+                }
+                Err(e) => {
+                    log::warn!("Injector backend '{}' failed: {}", backend.name, e);
+                    errors.push(format!("{}: {}", backend.name, e));
+                }
+            }
+        }
+        if errors.is_empty() {
+            return Err("No injector backends configured".to_string());
+        }
+        Err(errors.join("; "))
+    }
+}
+
+impl TextInjector for ChainInjector {
+    fn inject(&self, text: &str) -> Result<(), String> {
+        self.run(|injector| injector.inject(text))
+    }
+
+    fn erase(&self, count: usize) -> Result<(), String> {
+        self.run(|injector| injector.erase(count))
+    }
+
+    fn press(&self, action: KeyAction) -> Result<(), String> {
+        self.run(|injector| injector.press(action))
+    }
+
+    /// The backend that handled the most recent call.
+    fn target(&self) -> Option<String> {
+        self.last_working()
+    }
+
+    fn inserts_at_cursor(&self) -> bool {
+        let last = *self.last_working.lock().unwrap();
+        last.is_some_and(|i| self.backends[i].injector.inserts_at_cursor())
+    }
+
+    fn health_check(&self) -> Result<(), String> {
+        let report = self.self_test();
+        if report.iter().any(|h| h.usable) {
+            return Ok(());
+        }
+        Err(report
+            .into_iter()
+            .map(|h| format!("{}: {}", h.name, h.error.unwrap_or_default()))
+            .collect::<Vec<_>>()
+            .join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::injection::testing::MockInjector;
+
+    #[test]
+    fn test_falls_back_to_next_backend() {
+        let (typing, injected) = MockInjector::new();
+        let chain = ChainInjector::new(vec![
+            ("paste".to_string(), Arc::new(MockInjector::failing())),
+            ("typing".to_string(), Arc::new(typing)),
+        ]);
+
+        chain.inject("hello").unwrap();
+
+        assert_eq!(*injected.lock().unwrap(), vec!["hello"]);
+        assert_eq!(chain.last_working().as_deref(), Some("typing"));
+    }
+
+    #[test]
+    fn test_transient_failure_does_not_stick_to_fallback() {
+        let (paste, paste_log) = MockInjector::new();
+        let (history, history_log) = MockInjector::new();
+        paste.fail_next(1);
+        let chain = ChainInjector::new(vec![
+            ("paste".to_string(), Arc::new(paste)),
+            ("history-only".to_string(), Arc::new(history)),
+        ]);
+
+        chain.inject("first").unwrap();
+        assert_eq!(chain.target().as_deref(), Some("history-only"));
+        chain.inject("second").unwrap();
+
+        assert_eq!(*history_log.lock().unwrap(), vec!["first"]);
+        assert_eq!(*paste_log.lock().unwrap(), vec!["second"]);
+        assert_eq!(chain.target().as_deref(), Some("paste"));
+    }
+
+    #[test]
+    fn test_all_backends_failing_reports_each_error() {
+        let chain = ChainInjector::new(vec![
+            ("paste".to_string(), Arc::new(MockInjector::failing())),
+            ("typing".to_string(), Arc::new(MockInjector::failing())),
+        ]);
+
+        let err = chain.inject("lost").unwrap_err();
+        assert_eq!(
+            err,
+            "paste: Mock injection failure; typing: Mock injection failure"
+        );
+        assert!(chain.last_working().is_none());
+    }
+
+    #[test]
+    fn test_empty_chain_errors() {
+        let chain = ChainInjector::new(Vec::new());
+        assert!(chain.inject("text").is_err());
+    }
+
+    #[test]
+    fn test_self_test_reports_usable_backends() {
+        let (ok, _) = MockInjector::new();
+        let chain = ChainInjector::new(vec![
+            ("paste".to_string(), Arc::new(MockInjector::failing())),
+            ("history-only".to_string(), Arc::new(ok)),
+        ]);
+
+        let report = chain.self_test();
+        assert_eq!(report.len(), 2);
+        assert!(!report[0].usable);
+        assert_eq!(
+            report[0].error.as_deref(),
+            Some("Mock injector unavailable")
+        );
+        assert!(report[1].usable);
+        assert!(chain.health_check().is_ok());
+    }
+}
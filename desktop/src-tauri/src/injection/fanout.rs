@@ -0,0 +1,259 @@
+use std::sync::{mpsc, Arc};
+
+use super::{KeyAction, TextInjector};
+
+/// Outcome of one sink for a single delivery
+#[derive(Debug, Clone, PartialEq)]
+pub struct SinkResult {
+    pub name: String,
+    pub result: Result<(), String>,
+}
+
+/// Combined outcome of delivering one text to every sink
+#[derive(Debug, Clone, PartialEq)]
+pub struct FanOutReport {
+    pub results: Vec<SinkResult>,
+}
+
+impl FanOutReport {
+    pub fn any_succeeded(&self) -> bool {
+        self.results.iter().any(|r| r.result.is_ok())
+    }
+
+    pub fn failures(&self) -> Vec<&SinkResult> {
+        self.results.iter().filter(|r| r.result.is_err()).collect()
+    }
+
+    /// The first sink's outcome; the others were only logged.
+    fn into_result(self) -> Result<(), String> {
+        match self.results.into_iter().next() {
+            Some(primary) => primary
+                .result
+                .map_err(|e| format!("{}: {}", primary.name, e)),
+            None => Err("No sinks configured".to_string()),
+        }
+    }
+}
+
+/// Delivers each text to several sinks at once (focused app, daily log,
+/// webhook, ...). Sinks run concurrently and fail independently, so a slow
+/// or broken sink never prevents the others from receiving the text.
+/// The first sink is primary: as a `TextInjector`, its result is the result.
+pub struct FanOutInjector {
+    sinks: Vec<(String, Arc<dyn TextInjector>)>,
+}
+
+impl FanOutInjector {
+    pub fn new(sinks: Vec<(String, Arc<dyn TextInjector>)>) -> Self {
+        Self { sinks }
+    }
+
+    fn dispatch(
+        &self,
+        op: impl Fn(&dyn TextInjector) -> Result<(), String> + Sync,
+    ) -> FanOutReport {
+        let op = &op;
+        let results = std::thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .sinks
+                .iter()
+                .map(|(name, sink)| {
+                    let handle = scope.spawn(move || op(sink.as_ref()));
+                    (name, handle)
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|(name, handle)| SinkResult {
+                    name: name.clone(),
+                    result: handle
+                        .join()
+                        .unwrap_or_else(|_| Err("Sink panicked".to_string())),
+                })
+                .collect()
+        });
+        let report = FanOutReport { results };
+        for failure in report.failures() {
+            if let Err(e) = &failure.result {
+                log::warn!("Sink '{}' failed: {}", failure.name, e);
+            }
+        }
+        report
+    }
+
+    /// Deliver the text to every sink and report each outcome.
+    pub fn deliver(&self, text: &str) -> FanOutReport {
+        self.dispatch(|sink| sink.inject(text))
+    }
+}
+
+impl TextInjector for FanOutInjector {
+    fn inject(&self, text: &str) -> Result<(), String> {
+        self.deliver(text).into_result()
+    }
+
+    /// Only sinks that support erasing (i.e. the focused app) can take part;
+    /// append-only sinks report an error that is isolated like any other.
+    fn erase(&self, count: usize) -> Result<(), String> {
+        self.dispatch(|sink| sink.erase(count)).into_result()
+    }
+
+    fn press(&self, action: KeyAction) -> Result<(), String> {
+        self.dispatch(|sink| sink.press(action)).into_result()
+    }
+
+    /// The first sink is the one that erases (the focused app).
+    fn target(&self) -> Option<String> {
+        self.sinks.first().and_then(|(_, sink)| sink.target())
+    }
+
+    fn inserts_at_cursor(&self) -> bool {
+        self.sinks
+            .first()
+            .is_some_and(|(_, sink)| sink.inserts_at_cursor())
+    }
+
+    fn health_check(&self) -> Result<(), String> {
+        self.dispatch(|sink| sink.health_check()).into_result()
+    }
+}
+
+/// Hands texts to a fan-out on a dedicated thread, in order, without waiting
+/// for the sinks. A stalled webhook or a full pipe then only delays later
+/// deliveries to the sinks, never the caller.
+pub struct BackgroundFanOut {
+    tx: mpsc::Sender<String>,
+}
+
+impl BackgroundFanOut {
+    /// Start the delivery thread; it ends once this handle is dropped.
+    pub fn spawn(fanout: FanOutInjector) -> Self {
+        let (tx, rx) = mpsc::channel::<String>();
+        std::thread::spawn(move || {
+            for text in rx {
+                fanout.deliver(&text);
+            }
+        });
+        Self { tx }
+    }
+
+    /// Queue the text for every sink and return immediately.
+    pub fn send(&self, text: &str) {
+        if self.tx.send(text.to_string()).is_err() {
+            log::warn!("Sink delivery thread has stopped");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::injection::testing::MockInjector;
+
+    #[test]
+    fn test_delivers_to_every_sink() {
+        let (app, app_log) = MockInjector::new();
+        let (file, file_log) = MockInjector::new();
+        let fanout = FanOutInjector::new(vec![
+            ("app".to_string(), Arc::new(app)),
+            ("file".to_string(), Arc::new(file)),
+        ]);
+
+        fanout.inject("hello").unwrap();
+
+        assert_eq!(*app_log.lock().unwrap(), vec!["hello"]);
+        assert_eq!(*file_log.lock().unwrap(), vec!["hello"]);
+    }
+
+    #[test]
+    fn test_broken_sink_is_isolated() {
+        let (app, app_log) = MockInjector::new();
+        let fanout = FanOutInjector::new(vec![
+            ("app".to_string(), Arc::new(app)),
+            ("webhook".to_string(), Arc::new(MockInjector::failing())),
+        ]);
+
+        let report = fanout.deliver("still typed");
+
+        assert!(report.any_succeeded());
+        assert_eq!(report.failures().len(), 1);
+        assert_eq!(report.failures()[0].name, "webhook");
+        assert_eq!(*app_log.lock().unwrap(), vec!["still typed"]);
+        assert!(fanout.inject("again").is_ok());
+    }
+
+    #[test]
+    fn test_primary_failure_is_not_masked_by_other_sinks() {
+        let (file, file_log) = MockInjector::new();
+        let fanout = FanOutInjector::new(vec![
+            ("app".to_string(), Arc::new(MockInjector::failing())),
+            ("file".to_string(), Arc::new(file)),
+        ]);
+
+        let err = fanout.inject("logged only").unwrap_err();
+
+        assert_eq!(err, "app: Mock injection failure");
+        assert_eq!(*file_log.lock().unwrap(), vec!["logged only"]);
+    }
+
+    #[test]
+    fn test_report_keeps_sink_order() {
+        let (a, _) = MockInjector::new();
+        let (b, _) = MockInjector::new();
+        let fanout = FanOutInjector::new(vec![
+            ("a".to_string(), Arc::new(a)),
+            ("b".to_string(), Arc::new(b)),
+        ]);
+
+        let names: Vec<String> = fanout
+            .deliver("x")
+            .results
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        assert_eq!(names, vec!["a", "b"]);
+    }
+
+    /// Sink that blocks until the test lets it through
+    struct StalledSink {
+        release: std::sync::Mutex<mpsc::Receiver<()>>,
+        delivered: mpsc::Sender<String>,
+    }
+
+    impl TextInjector for StalledSink {
+        fn inject(&self, text: &str) -> Result<(), String> {
+            self.release
+                .lock()
+                .unwrap()
+                .recv()
+                .map_err(|e| e.to_string())?;
+            self.delivered.send(text.to_string()).unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_background_delivery_does_not_wait_for_stalled_sinks() {
+        let (release, release_rx) = mpsc::channel();
+        let (delivered_tx, delivered) = mpsc::channel();
+        let sink = StalledSink {
+            release: std::sync::Mutex::new(release_rx),
+            delivered: delivered_tx,
+        };
+        let background = BackgroundFanOut::spawn(FanOutInjector::new(vec![(
+            "webhook".to_string(),
+            Arc::new(sink),
+        )]));
+
+        // Both return while the sink is still stuck on the first text
+        background.send("first");
+        background.send("second");
+        assert!(delivered.try_recv().is_err());
+
+        release.send(()).unwrap();
+        release.send(()).unwrap();
+        let timeout = std::time::Duration::from_secs(2);
+        assert_eq!(delivered.recv_timeout(timeout).unwrap(), "first");
+        assert_eq!(delivered.recv_timeout(timeout).unwrap(), "second");
+    }
+}
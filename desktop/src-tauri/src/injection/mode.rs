@@ -0,0 +1,103 @@
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use super::{KeyAction, TextInjector};
+
+/// How FINAL text reaches the user, switchable at runtime from the frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InjectionMode {
+    /// Type into the focused app (paste, with fallbacks)
+    Auto,
+    /// Only copy to the clipboard; the user pastes manually
+    ClipboardOnly,
+}
+
+/// Routes every call to the injector of the currently selected mode.
+pub struct SwitchableInjector {
+    mode: RwLock<InjectionMode>,
+    auto: Arc<dyn TextInjector>,
+    clipboard_only: Arc<dyn TextInjector>,
+}
+
+impl SwitchableInjector {
+    pub fn new(auto: Arc<dyn TextInjector>, clipboard_only: Arc<dyn TextInjector>) -> Self {
+        Self {
+            mode: RwLock::new(InjectionMode::Auto),
+            auto,
+            clipboard_only,
+        }
+    }
+
+    pub fn mode(&self) -> InjectionMode {
+        *self.mode.read().unwrap()
+    }
+
+    pub fn set_mode(&self, mode: InjectionMode) {
+        log::info!("Injection mode set to {:?}", mode);
+        *self.mode.write().unwrap() = mode;
+    }
+
+    fn current(&self) -> &Arc<dyn TextInjector> {
+        match self.mode() {
+            InjectionMode::Auto => &self.auto,
+            InjectionMode::ClipboardOnly => &self.clipboard_only,
+        }
+    }
+}
+
+impl TextInjector for SwitchableInjector {
+    fn inject(&self, text: &str) -> Result<(), String> {
+        self.current().inject(text)
+    }
+
+    fn erase(&self, count: usize) -> Result<(), String> {
+        self.current().erase(count)
+    }
+
+    fn press(&self, action: KeyAction) -> Result<(), String> {
+        self.current().press(action)
+    }
+
+    fn target(&self) -> Option<String> {
+        self.current().target()
+    }
+
+    fn inserts_at_cursor(&self) -> bool {
+        self.current().inserts_at_cursor()
+    }
+
+    fn health_check(&self) -> Result<(), String> {
+        self.current().health_check()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::injection::testing::MockInjector;
+
+    #[test]
+    fn test_routes_by_mode() {
+        let (auto, auto_log) = MockInjector::new();
+        let (clipboard, clipboard_log) = MockInjector::new();
+        let injector = SwitchableInjector::new(Arc::new(auto), Arc::new(clipboard));
+
+        injector.inject("typed").unwrap();
+        injector.set_mode(InjectionMode::ClipboardOnly);
+        injector.inject("copied").unwrap();
+
+        assert_eq!(*auto_log.lock().unwrap(), vec!["typed"]);
+        assert_eq!(*clipboard_log.lock().unwrap(), vec!["copied"]);
+        assert_eq!(injector.mode(), InjectionMode::ClipboardOnly);
+    }
+
+    #[test]
+    fn test_mode_serializes_kebab_case() {
+        let json = serde_json::to_string(&InjectionMode::ClipboardOnly).unwrap();
+        assert_eq!(json, r#""clipboard-only""#);
+        let parsed: InjectionMode = serde_json::from_str(r#""auto""#).unwrap();
+        assert_eq!(parsed, InjectionMode::Auto);
+    }
+}
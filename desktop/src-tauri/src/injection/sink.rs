@@ -0,0 +1,539 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+
+use super::TextInjector;
+
+/// Line format used by the file, stdout and pipe sinks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinkFormat {
+    /// `[2026-10-19 14:03:12] text`
+    Plain,
+    /// `- **2026-10-19 14:03:12** text`
+    Markdown,
+    /// `{"timestamp":"2026-10-19T14:03:12+09:00","text":"text"}`
+    Jsonl,
+}
+
+impl SinkFormat {
+    /// Pick a format from a file extension (`.md`, `.jsonl`), plain otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("md") | Some("markdown") => SinkFormat::Markdown,
+            Some("jsonl") | Some("ndjson") => SinkFormat::Jsonl,
+            _ => SinkFormat::Plain,
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "plain" | "txt" => Ok(SinkFormat::Plain),
+            "markdown" | "md" => Ok(SinkFormat::Markdown),
+            "jsonl" => Ok(SinkFormat::Jsonl),
+            other => Err(format!("Unknown sink format: {other}")),
+        }
+    }
+
+    /// Render one FINAL as a single line, including the trailing newline.
+    pub fn render(&self, text: &str, at: DateTime<Local>) -> String {
+        let stamp = at.format("%Y-%m-%d %H:%M:%S");
+        // Keep one entry per line even for multi-line dictations
+        let flat = text.replace(['\r', '\n'], " ");
+        match self {
+            SinkFormat::Plain => format!("[{stamp}] {flat}\n"),
+            SinkFormat::Markdown => format!("- **{stamp}** {flat}\n"),
+            SinkFormat::Jsonl => {
+                let line = serde_json::json!({
+                    "timestamp": at.to_rfc3339(),
+                    "text": text,
+                });
+                format!("{line}\n")
+            }
+        }
+    }
+}
+
+/// Appends each FINAL with a timestamp to a file (note files, daily logs).
+/// A `{date}` placeholder in the path rolls over to a new file every day.
+pub struct FileAppendInjector {
+    path: PathBuf,
+    format: SinkFormat,
+    lock: Mutex<()>,
+}
+
+impl FileAppendInjector {
+    pub fn new(path: impl Into<PathBuf>, format: SinkFormat) -> Self {
+        Self {
+            path: path.into(),
+            format,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Concrete file for the given moment, with `{date}` expanded.
+    pub fn path_at(&self, at: DateTime<Local>) -> PathBuf {
+        let raw = self.path.to_string_lossy();
+        if !raw.contains("{date}") {
+            return self.path.clone();
+        }
+        PathBuf::from(raw.replace("{date}", &at.format("%Y-%m-%d").to_string()))
+    }
+
+    fn open(&self, path: &Path) -> Result<std::fs::File, String> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Open {}: {e}", path.display()))
+    }
+}
+
+impl TextInjector for FileAppendInjector {
+    fn inject(&self, text: &str) -> Result<(), String> {
+        let _guard = self.lock.lock().unwrap();
+        let now = Local::now();
+        let path = self.path_at(now);
+        self.open(&path)?
+            .write_all(self.format.render(text, now).as_bytes())
+            .map_err(|e| format!("Write {}: {e}", path.display()))
+    }
+
+    fn inserts_at_cursor(&self) -> bool {
+        false
+    }
+
+    fn health_check(&self) -> Result<(), String> {
+        self.open(&self.path_at(Local::now())).map(|_| ())
+    }
+}
+
+/// Writes each FINAL to stdout, for piping the desktop into scripts.
+pub struct StdoutInjector {
+    format: Option<SinkFormat>,
+}
+
+impl StdoutInjector {
+    /// `None` writes the bare text, one dictation per line.
+    pub fn new(format: Option<SinkFormat>) -> Self {
+        Self { format }
+    }
+}
+
+impl TextInjector for StdoutInjector {
+    fn inject(&self, text: &str) -> Result<(), String> {
+        let line = match self.format {
+            Some(format) => format.render(text, Local::now()),
+            None => format!("{text}\n"),
+        };
+        let mut stdout = std::io::stdout().lock();
+        stdout
+            .write_all(line.as_bytes())
+            .and_then(|_| stdout.flush())
+            .map_err(|e| format!("Write stdout: {e}"))
+    }
+
+    fn inserts_at_cursor(&self) -> bool {
+        false
+    }
+}
+
+/// How long a pipe write waits for a slow reader to make room
+const PIPE_WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Writes each FINAL to a named pipe (FIFO) read by another process.
+///
+/// The pipe is opened non-blocking per dictation, so a missing reader fails
+/// the injection instead of stalling the server.
+pub struct NamedPipeInjector {
+    path: PathBuf,
+    format: Option<SinkFormat>,
+}
+
+impl NamedPipeInjector {
+    pub fn new(path: impl Into<PathBuf>, format: Option<SinkFormat>) -> Self {
+        Self {
+            path: path.into(),
+            format,
+        }
+    }
+
+    fn open(&self) -> Result<std::fs::File, String> {
+        let mut options = OpenOptions::new();
+        options.write(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.custom_flags(libc::O_NONBLOCK);
+        }
+        options
+            .open(&self.path)
+            .map_err(|e| format!("Open pipe {}: {e}", self.path.display()))
+    }
+}
+
+/// Write one line to a non-blocking pipe, waiting out a full pipe instead of
+/// giving up halfway. Lines up to PIPE_BUF bytes go in or fail as a whole;
+/// longer ones are only torn when the reader stalls past the timeout, which
+/// is reported with how much got through.
+fn write_line(file: &mut std::fs::File, line: &[u8], timeout: Duration) -> Result<(), String> {
+    let deadline = Instant::now() + timeout;
+    let mut written = 0;
+    while written < line.len() {
+        match file.write(&line[written..]) {
+            Ok(0) => return Err("Pipe closed by reader".to_string()),
+            Ok(n) => written += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                if Instant::now() >= deadline {
+                    return Err(format!(
+                        "Pipe full: wrote {written} of {} bytes",
+                        line.len()
+                    ));
+                }
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    Ok(())
+}
+
+impl TextInjector for NamedPipeInjector {
+    fn inject(&self, text: &str) -> Result<(), String> {
+        let line = match self.format {
+            Some(format) => format.render(text, Local::now()),
+            None => format!("{text}\n"),
+        };
+        write_line(&mut self.open()?, line.as_bytes(), PIPE_WRITE_TIMEOUT)
+            .map_err(|e| format!("Write pipe {}: {e}", self.path.display()))
+    }
+
+    fn inserts_at_cursor(&self) -> bool {
+        false
+    }
+
+    fn health_check(&self) -> Result<(), String> {
+        self.open().map(|_| ())
+    }
+}
+
+/// POSTs each FINAL as JSON (`{"timestamp":..,"text":..}`) to a plain-HTTP webhook.
+/// TLS is not supported; point it at a local relay for HTTPS endpoints.
+pub struct WebhookInjector {
+    host: String,
+    port: u16,
+    path: String,
+    timeout: Duration,
+}
+
+impl WebhookInjector {
+    pub fn new(url: &str, timeout: Duration) -> Result<Self, String> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("Webhook URL must start with http://: {url}"))?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse()
+                    .map_err(|e| format!("Invalid webhook port {port}: {e}"))?,
+            ),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(format!("Webhook URL has no host: {url}"));
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+            timeout,
+        })
+    }
+
+    fn post(&self, body: &str) -> Result<(), String> {
+        use std::io::Read;
+        use std::net::{TcpStream, ToSocketAddrs};
+
+        let addr = (self.host.as_str(), self.port)
+            .to_socket_addrs()
+            .map_err(|e| format!("Resolve {}: {e}", self.host))?
+            .next()
+            .ok_or_else(|| format!("Resolve {}: no address", self.host))?;
+        let mut stream = TcpStream::connect_timeout(&addr, self.timeout)
+            .map_err(|e| format!("Connect {}:{}: {e}", self.host, self.port))?;
+        stream
+            .set_read_timeout(Some(self.timeout))
+            .and_then(|_| stream.set_write_timeout(Some(self.timeout)))
+            .map_err(|e| format!("Webhook socket: {e}"))?;
+
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.path,
+            self.host,
+            body.len(),
+            body
+        );
+        stream
+            .write_all(request.as_bytes())
+            .map_err(|e| format!("Webhook send: {e}"))?;
+
+        let mut response = String::new();
+        stream
+            .read_to_string(&mut response)
+            .map_err(|e| format!("Webhook read: {e}"))?;
+        let status = response
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or("Webhook returned no HTTP status")?;
+        if !(200..300).contains(&status) {
+            return Err(format!("Webhook returned HTTP {status}"));
+        }
+        Ok(())
+    }
+}
+
+impl TextInjector for WebhookInjector {
+    fn inject(&self, text: &str) -> Result<(), String> {
+        let body = serde_json::json!({
+            "timestamp": Local::now().to_rfc3339(),
+            "text": text,
+        });
+        self.post(&body.to_string())
+    }
+
+    fn inserts_at_cursor(&self) -> bool {
+        false
+    }
+}
+
+/// Build a sink from a spec string:
+/// `stdout`, `stdout:jsonl`, `file:/path/notes-{date}.md`, `pipe:/tmp/ptt.fifo`,
+/// `webhook:http://host:port/path`.
+/// File formats follow the extension; stdout takes an optional format suffix.
+pub fn from_spec(spec: &str) -> Result<Arc<dyn TextInjector>, String> {
+    let (kind, arg) = spec.split_once(':').unwrap_or((spec, ""));
+    match kind {
+        "stdout" => {
+            let format = if arg.is_empty() {
+                None
+            } else {
+                Some(SinkFormat::parse(arg)?)
+            };
+            Ok(Arc::new(StdoutInjector::new(format)))
+        }
+        "file" if !arg.is_empty() => {
+            let path = PathBuf::from(arg);
+            let format = SinkFormat::from_path(&path);
+            Ok(Arc::new(FileAppendInjector::new(path, format)))
+        }
+        "pipe" if !arg.is_empty() => Ok(Arc::new(NamedPipeInjector::new(arg, None))),
+        "webhook" => Ok(Arc::new(WebhookInjector::new(arg, Duration::from_secs(3))?)),
+        _ => Err(format!("Invalid sink spec: {spec}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, 19, 14, 3, 12).unwrap()
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ptt-sink-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let at = fixed_time();
+        assert_eq!(
+            SinkFormat::Plain.render("hello", at),
+            "[2026-10-19 14:03:12] hello\n"
+        );
+        assert_eq!(
+            SinkFormat::Markdown.render("line one\nline two", at),
+            "- **2026-10-19 14:03:12** line one line two\n"
+        );
+
+        let jsonl = SinkFormat::Jsonl.render("say \"hi\"", at);
+        let value: serde_json::Value = serde_json::from_str(jsonl.trim_end()).unwrap();
+        assert_eq!(value["text"], "say \"hi\"");
+        assert_eq!(value["timestamp"], at.to_rfc3339());
+    }
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_eq!(
+            SinkFormat::from_path(Path::new("notes.md")),
+            SinkFormat::Markdown
+        );
+        assert_eq!(
+            SinkFormat::from_path(Path::new("log.jsonl")),
+            SinkFormat::Jsonl
+        );
+        assert_eq!(
+            SinkFormat::from_path(Path::new("dictation.txt")),
+            SinkFormat::Plain
+        );
+    }
+
+    #[test]
+    fn test_file_append_injector_appends_lines() {
+        let path = temp_path("append.jsonl");
+        let _ = std::fs::remove_file(&path);
+
+        let injector = FileAppendInjector::new(&path, SinkFormat::Jsonl);
+        injector.inject("first").unwrap();
+        injector.inject("second").unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let texts: Vec<String> = content
+            .lines()
+            .map(|l| {
+                let value: serde_json::Value = serde_json::from_str(l).unwrap();
+                value["text"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(texts, vec!["first", "second"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    fn make_fifo(name: &str) -> PathBuf {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = temp_path(name);
+        let _ = std::fs::remove_file(&path);
+        let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+        path
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_named_pipe_without_reader_fails_fast() {
+        let path = make_fifo("noreader.fifo");
+        let injector = NamedPipeInjector::new(&path, None);
+
+        let started = Instant::now();
+        assert!(injector.inject("nobody listening").is_err());
+        assert!(injector.health_check().is_err());
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_named_pipe_line_larger_than_pipe_arrives_whole() {
+        use std::io::Read;
+        use std::os::unix::fs::OpenOptionsExt;
+        use std::os::unix::io::AsRawFd;
+
+        let path = make_fifo("reader.fifo");
+        // Open the reader first (non-blocking, so it doesn't wait for a writer),
+        // then switch it to blocking reads for the reader thread
+        let mut reader = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)
+            .unwrap();
+        let injector = NamedPipeInjector::new(&path, None);
+        let long = "x".repeat(300_000);
+
+        let writer = std::thread::spawn({
+            let long = long.clone();
+            move || injector.inject(&long)
+        });
+        unsafe { libc::fcntl(reader.as_raw_fd(), libc::F_SETFL, 0) };
+        let mut received = Vec::new();
+        let mut buf = [0u8; 8192];
+        // Reads see EOF until the writer has opened its end
+        while !received.ends_with(b"\n") {
+            match reader.read(&mut buf).unwrap() {
+                0 => std::thread::sleep(Duration::from_millis(5)),
+                n => received.extend_from_slice(&buf[..n]),
+            }
+        }
+
+        writer.join().unwrap().unwrap();
+        assert_eq!(String::from_utf8(received).unwrap(), format!("{long}\n"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_file_path_expands_date() {
+        let injector = FileAppendInjector::new("/notes/{date}.md", SinkFormat::Markdown);
+        assert_eq!(
+            injector.path_at(fixed_time()),
+            PathBuf::from("/notes/2026-10-19.md")
+        );
+    }
+
+    #[test]
+    fn test_webhook_url_parsing() {
+        let hook = WebhookInjector::new("http://localhost:8080/hooks/ptt", Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(hook.host, "localhost");
+        assert_eq!(hook.port, 8080);
+        assert_eq!(hook.path, "/hooks/ptt");
+
+        let hook = WebhookInjector::new("http://example.com", Duration::from_secs(1)).unwrap();
+        assert_eq!((hook.port, hook.path.as_str()), (80, "/"));
+
+        assert!(WebhookInjector::new("https://example.com", Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn test_webhook_posts_json() {
+        use std::io::Read;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let n = stream.read(&mut buf).unwrap();
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_string()
+        });
+
+        let hook = WebhookInjector::new(
+            &format!("http://127.0.0.1:{port}/ptt"),
+            Duration::from_secs(2),
+        )
+        .unwrap();
+        hook.inject("hello hook").unwrap();
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /ptt HTTP/1.1\r\n"));
+        assert!(request.contains(r#""text":"hello hook""#));
+    }
+
+    #[test]
+    fn test_from_spec() {
+        assert!(from_spec("stdout").is_ok());
+        assert!(from_spec("stdout:jsonl").is_ok());
+        assert!(from_spec("file:/tmp/notes.md").is_ok());
+        assert!(from_spec("pipe:/tmp/ptt.fifo").is_ok());
+        assert!(from_spec("stdout:xml").is_err());
+        assert!(from_spec("file:").is_err());
+        assert!(from_spec("webhook:http://localhost:9000/ptt").is_ok());
+        assert!(from_spec("webhook:ftp://localhost").is_err());
+        assert!(from_spec("telegram").is_err());
+    }
+}
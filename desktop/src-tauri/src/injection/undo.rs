@@ -0,0 +1,182 @@
+use std::sync::Mutex;
+
+use super::TextInjector;
+
+/// Older insertions are dropped; undoing far back rarely hits the right text
+const MAX_ENTRIES: usize = 20;
+
+/// Text inserted by one FINAL
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Insertion {
+    pub client_id: String,
+    /// Characters before the cursor that belong to the dictation
+    pub chars: usize,
+    /// Backend that inserted it, when the injector knows
+    pub target: Option<String>,
+}
+
+/// Remembers what recent FINALs inserted so they can be erased again.
+pub struct UndoStack {
+    entries: Mutex<Vec<Insertion>>,
+}
+
+impl UndoStack {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Record an insertion made through `injector`. Empty insertions and text
+    /// that never reached the cursor (clipboard, history, sinks) are ignored.
+    pub fn record(&self, client_id: &str, chars: usize, injector: &dyn TextInjector) {
+        if chars == 0 || !injector.inserts_at_cursor() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        entries.push(Insertion {
+            client_id: client_id.to_string(),
+            chars,
+            target: injector.target(),
+        });
+        if entries.len() > MAX_ENTRIES {
+            entries.remove(0);
+        }
+    }
+
+    /// Most recent insertion, optionally restricted to one client
+    pub fn last(&self, client_id: Option<&str>) -> Option<Insertion> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .rev()
+            .find(|e| client_id.is_none_or(|id| e.client_id == id))
+            .cloned()
+    }
+
+    /// Erase the most recent insertion (of `client_id`, or of anyone).
+    /// Refused when another insertion came after it or input now goes to a
+    /// different backend, since the cursor no longer sits behind that text.
+    /// The entry is only dropped once the erase went through.
+    pub fn undo(
+        &self,
+        client_id: Option<&str>,
+        injector: &dyn TextInjector,
+    ) -> Result<Insertion, String> {
+        let mut entries = self.entries.lock().unwrap();
+        let index = entries
+            .iter()
+            .rposition(|e| client_id.is_none_or(|id| e.client_id == id))
+            .ok_or_else(|| "Nothing to undo".to_string())?;
+        if index + 1 != entries.len() {
+            return Err("Another dictation was inserted since".to_string());
+        }
+        let target = injector.target();
+        if entries[index].target != target {
+            return Err(format!(
+                "Dictation went to {}, input now goes to {}",
+                entries[index].target.as_deref().unwrap_or("unknown"),
+                target.as_deref().unwrap_or("unknown")
+            ));
+        }
+        injector.erase(entries[index].chars)?;
+        Ok(entries.remove(index))
+    }
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::injection::chain::ChainInjector;
+    use crate::injection::testing::MockInjector;
+    use crate::injection::HistoryOnlyInjector;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_undo_erases_last_insertion() {
+        let stack = UndoStack::new();
+        let (mock, log) = MockInjector::new();
+        stack.record("phone-01", 5, &mock);
+        stack.record("phone-01", 12, &mock);
+
+        let undone = stack.undo(None, &mock).unwrap();
+
+        assert_eq!(undone.chars, 12);
+        assert_eq!(*log.lock().unwrap(), vec!["<erase 12>"]);
+        assert_eq!(stack.last(None).unwrap().chars, 5);
+    }
+
+    #[test]
+    fn test_undo_by_client() {
+        let stack = UndoStack::new();
+        let (mock, log) = MockInjector::new();
+        stack.record("phone-01", 3, &mock);
+        stack.record("phone-02", 7, &mock);
+
+        // phone-02's text sits between the cursor and phone-01's
+        assert_eq!(
+            stack.undo(Some("phone-01"), &mock),
+            Err("Another dictation was inserted since".to_string())
+        );
+        stack.undo(Some("phone-02"), &mock).unwrap();
+        stack.undo(Some("phone-01"), &mock).unwrap();
+
+        assert_eq!(*log.lock().unwrap(), vec!["<erase 7>", "<erase 3>"]);
+        assert!(stack.undo(Some("phone-01"), &mock).is_err());
+    }
+
+    #[test]
+    fn test_text_that_never_reached_the_cursor_is_not_recorded() {
+        let stack = UndoStack::new();
+        let (typing, log) = MockInjector::new();
+        let typing = Arc::new(typing);
+        let chain = ChainInjector::new(vec![
+            ("typing".to_string(), typing.clone()),
+            ("history-only".to_string(), Arc::new(HistoryOnlyInjector)),
+        ]);
+        chain.inject("typed").unwrap();
+        stack.record("phone-01", 5, &chain);
+
+        // Typing fails once, so the next FINAL only lands in history
+        typing.fail_next(1);
+        chain.inject("fallback").unwrap();
+        stack.record("phone-01", 8, &chain);
+
+        assert_eq!(stack.last(None).unwrap().chars, 5);
+        assert_eq!(
+            stack.undo(None, &chain),
+            Err("Dictation went to typing, input now goes to history-only".to_string())
+        );
+        assert_eq!(*log.lock().unwrap(), vec!["typed"]);
+    }
+
+    #[test]
+    fn test_failed_erase_keeps_entry() {
+        let stack = UndoStack::new();
+        let (mock, _) = MockInjector::new();
+        stack.record("phone-01", 4, &mock);
+
+        assert!(stack.undo(None, &MockInjector::failing()).is_err());
+        assert_eq!(stack.last(None).unwrap().chars, 4);
+    }
+
+    #[test]
+    fn test_empty_insertions_and_bound() {
+        let stack = UndoStack::new();
+        let (mock, _) = MockInjector::new();
+        stack.record("phone-01", 0, &mock);
+        assert!(stack.last(None).is_none());
+
+        for chars in 1..=MAX_ENTRIES + 5 {
+            stack.record("phone-01", chars, &mock);
+        }
+        assert_eq!(stack.entries.lock().unwrap().len(), MAX_ENTRIES);
+        assert_eq!(stack.entries.lock().unwrap()[0].chars, 6);
+    }
+}
@@ -0,0 +1,73 @@
+//! JSON files the persistent stores (review queue, rules, statistics,
+//! vocabulary) keep their state in.
+
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Read `path`, or the default value if the file doesn't exist yet.
+pub fn load<T: DeserializeOwned + Default>(path: &Path) -> Result<T, String> {
+    match std::fs::read_to_string(path) {
+        Ok(json) => {
+            serde_json::from_str(&json).map_err(|e| format!("Parse {}: {e}", path.display()))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(format!("Read {}: {e}", path.display())),
+    }
+}
+
+/// Encode `value` as pretty JSON and `write` it.
+pub fn save<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Encode {}: {e}", path.display()))?;
+    write(path, &json)
+}
+
+/// Write-then-rename, so a crash never leaves a truncated file behind.
+pub fn write(path: &Path, json: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Create {}: {e}", dir.display()))?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| format!("Write {}: {e}", tmp.display()))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Rename {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_file_loads_default_and_save_roundtrips() {
+        let dir = std::env::temp_dir().join(format!("ptt-json-file-{}", std::process::id()));
+        let path = dir.join("state.json");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(load::<Vec<u32>>(&path).unwrap(), Vec::<u32>::new());
+        save(&path, &vec![1, 2, 3]).unwrap();
+        assert_eq!(load::<Vec<u32>>(&path).unwrap(), vec![1, 2, 3]);
+        assert!(!path.with_extension("json.tmp").exists());
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(load::<Vec<u32>>(&path).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_failed_write_keeps_the_old_file() {
+        let path = std::env::temp_dir().join(format!("ptt-json-keep-{}.json", std::process::id()));
+        let tmp = path.with_extension("json.tmp");
+        let _ = std::fs::remove_file(&path);
+        save(&path, &vec![1]).unwrap();
+
+        // A directory where the temp file goes makes the write fail
+        std::fs::create_dir_all(&tmp).unwrap();
+        assert!(save(&path, &vec![2]).is_err());
+        assert_eq!(load::<Vec<u32>>(&path).unwrap(), vec![1]);
+
+        std::fs::remove_dir(&tmp).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,12 +1,50 @@
+pub mod capture;
 pub mod client_registry;
+pub mod codec;
+pub mod confidence;
+pub mod export;
+pub mod formatting;
+pub mod framing;
+pub mod history;
 pub mod injection;
+pub mod json_file;
+pub mod live_typing;
+pub mod normalize;
+pub mod pipeline;
 pub mod protocol;
+pub mod redaction;
+pub mod review;
+pub mod rule_store;
+pub mod rule_sync;
+pub mod rules;
+pub mod schema;
+pub mod simulator;
+pub mod stats;
+pub mod vocabulary;
+pub mod voice_commands;
 pub mod ws_server;
 
 use std::sync::Arc;
 
 use client_registry::ClientRegistry;
-use injection::ClipboardPasteInjector;
+use confidence::{ConfidencePolicy, HeldText};
+use export::ExportFormat;
+use history::{History, HistoryEntry, HistoryPage, HistoryQuery, HistorySelection, Retention};
+use injection::chain::{BackendHealth, ChainInjector};
+use injection::fanout::{BackgroundFanOut, FanOutInjector};
+use injection::mode::{InjectionMode, SwitchableInjector};
+use injection::{
+    ClipboardOnlyInjector, ClipboardPasteInjector, HistoryOnlyInjector, TypingInjector,
+};
+use live_typing::LiveTyping;
+use normalize::Normalizer;
+use redaction::RedactionConfig;
+use review::{PendingEntry, ReviewQueue};
+use rule_store::RuleStore;
+use rules::{RuleEngine, TextRule};
+use stats::{Statistics, StatsQuery, StatsRow, FLUSH_INTERVAL};
+use vocabulary::{Vocabulary, VocabularyEntry};
+use voice_commands::{CommandPhrase, VoiceCommands};
 
 /// Tauri event emitter — bridges ws_server events to frontend
 struct TauriEventEmitter {
@@ -23,6 +61,12 @@ impl ws_server::EventEmitter for TauriEventEmitter {
             ws_server::ServerEvent::PartialText { .. } => "partial-text",
             ws_server::ServerEvent::FinalText { .. } => "final-text",
             ws_server::ServerEvent::PttStarted { .. } => "ptt-started",
+            ws_server::ServerEvent::ClipboardCopied { .. } => "clipboard-copied",
+            ws_server::ServerEvent::DictationUndone { .. } => "dictation-undone",
+            ws_server::ServerEvent::TextHeld { .. } => "text-held",
+            ws_server::ServerEvent::TextRejected { .. } => "text-rejected",
+            ws_server::ServerEvent::HeldTextResolved { .. } => "held-text-resolved",
+            ws_server::ServerEvent::PendingQueued { .. } => "pending-queued",
         };
         let _ = self.app_handle.emit(event_name, &event);
     }
@@ -33,28 +77,580 @@ async fn get_server_port() -> u16 {
     9876
 }
 
+#[tauri::command]
+fn get_live_typing(live_typing: tauri::State<'_, Arc<LiveTyping>>) -> bool {
+    live_typing.is_enabled()
+}
+
+#[tauri::command]
+fn set_live_typing(live_typing: tauri::State<'_, Arc<LiveTyping>>, enabled: bool) {
+    live_typing.set_enabled(enabled);
+}
+
+#[tauri::command]
+fn get_injection_mode(injector: tauri::State<'_, Arc<SwitchableInjector>>) -> InjectionMode {
+    injector.mode()
+}
+
+#[tauri::command]
+fn set_injection_mode(injector: tauri::State<'_, Arc<SwitchableInjector>>, mode: InjectionMode) {
+    injector.set_mode(mode);
+}
+
+#[tauri::command]
+fn get_text_rules(store: tauri::State<'_, Arc<RuleStore>>) -> Vec<TextRule> {
+    store.active_rules()
+}
+
+/// Returns the new ruleset version, which paired phones pick up on their next sync.
+#[tauri::command]
+fn upsert_text_rules(
+    store: tauri::State<'_, Arc<RuleStore>>,
+    rules: Vec<TextRule>,
+) -> Result<i64, String> {
+    store.upsert(rules)
+}
+
+/// Replace the whole rule set; rules not listed are deleted.
+#[tauri::command]
+fn set_text_rules(
+    store: tauri::State<'_, Arc<RuleStore>>,
+    rules: Vec<TextRule>,
+) -> Result<i64, String> {
+    store.replace_all(rules)
+}
+
+#[tauri::command]
+fn delete_text_rules(
+    store: tauri::State<'_, Arc<RuleStore>>,
+    ids: Vec<String>,
+) -> Result<i64, String> {
+    store.delete(&ids)
+}
+
+#[tauri::command]
+fn set_normalization(normalizer: tauri::State<'_, Arc<Normalizer>>, enabled: bool) {
+    normalizer.set_enabled(enabled);
+}
+
+/// `None` guesses the locale of each FINAL from its script.
+#[tauri::command]
+fn set_normalization_locale(normalizer: tauri::State<'_, Arc<Normalizer>>, locale: Option<String>) {
+    normalizer.set_locale(locale);
+}
+
+/// Only rules for `locale` (or `*`) apply; `None` applies every rule.
+#[tauri::command]
+fn set_rules_locale(engine: tauri::State<'_, Arc<RuleEngine>>, locale: Option<String>) {
+    engine.set_locale(locale);
+}
+
+#[tauri::command]
+fn set_rules_on_partials(engine: tauri::State<'_, Arc<RuleEngine>>, enabled: bool) {
+    engine.set_apply_to_partials(enabled);
+}
+
+#[tauri::command]
+fn get_voice_commands(commands: tauri::State<'_, Arc<VoiceCommands>>) -> Vec<CommandPhrase> {
+    commands.phrases()
+}
+
+#[tauri::command]
+fn set_voice_commands(commands: tauri::State<'_, Arc<VoiceCommands>>, phrases: Vec<CommandPhrase>) {
+    commands.set_phrases(phrases);
+}
+
+#[tauri::command]
+fn get_voice_commands_enabled(commands: tauri::State<'_, Arc<VoiceCommands>>) -> bool {
+    commands.is_enabled()
+}
+
+#[tauri::command]
+fn set_voice_commands_enabled(commands: tauri::State<'_, Arc<VoiceCommands>>, enabled: bool) {
+    commands.set_enabled(enabled);
+}
+
+/// Probe every injector backend. Runs off the main thread because the
+/// probes themselves dispatch to it.
+#[tauri::command]
+async fn injector_self_test(
+    chain: tauri::State<'_, Arc<ChainInjector>>,
+) -> Result<Vec<BackendHealth>, String> {
+    let chain = Arc::clone(&chain);
+    tauri::async_runtime::spawn_blocking(move || chain.self_test())
+        .await
+        .map_err(|e| format!("Self-test task: {e}"))
+}
+
+#[tauri::command]
+fn get_smart_formatting(ctx: tauri::State<'_, Arc<ws_server::ServerContext>>) -> bool {
+    ctx.formatter.is_enabled()
+}
+
+#[tauri::command]
+fn set_smart_formatting(ctx: tauri::State<'_, Arc<ws_server::ServerContext>>, enabled: bool) {
+    ctx.formatter.set_enabled(enabled);
+}
+
+/// Forget the previous dictation, e.g. after the user clicked into another
+/// field. Focus changes aren't detected, so this is the only way to say so.
+#[tauri::command]
+fn reset_text_formatting(ctx: tauri::State<'_, Arc<ws_server::ServerContext>>) {
+    ctx.formatter.reset();
+}
+
+/// Erase the characters the most recent FINAL inserted. Returns how many
+/// were erased; runs off the main thread because erasing dispatches to it.
+#[tauri::command]
+async fn undo_last_dictation(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+) -> Result<usize, String> {
+    let ctx = Arc::clone(&ctx);
+    tauri::async_runtime::spawn_blocking(move || ctx.undo_last_dictation(None))
+        .await
+        .map_err(|e| format!("Undo task: {e}"))?
+}
+
+#[tauri::command]
+fn get_confidence_policy(ctx: tauri::State<'_, Arc<ws_server::ServerContext>>) -> ConfidencePolicy {
+    ctx.confidence.policy()
+}
+
+#[tauri::command]
+fn set_confidence_policy(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+    policy: ConfidencePolicy,
+) -> Result<(), String> {
+    ctx.confidence.set_policy(policy)
+}
+
+#[tauri::command]
+fn get_held_texts(ctx: tauri::State<'_, Arc<ws_server::ServerContext>>) -> Vec<HeldText> {
+    ctx.confidence.held()
+}
+
+/// Inject (`accept`) or discard a held FINAL.
+#[tauri::command]
+async fn confirm_held_text(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+    id: u64,
+    accept: bool,
+) -> Result<HeldText, String> {
+    let ctx = Arc::clone(&ctx);
+    tauri::async_runtime::spawn_blocking(move || ctx.resolve_held(id, accept))
+        .await
+        .map_err(|e| format!("Confirm task: {e}"))?
+}
+
+#[tauri::command]
+fn get_redaction_config(ctx: tauri::State<'_, Arc<ws_server::ServerContext>>) -> RedactionConfig {
+    ctx.redaction.config()
+}
+
+#[tauri::command]
+fn set_redaction_config(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+    config: RedactionConfig,
+) -> Result<(), String> {
+    ctx.redaction.set_config(config)
+}
+
+#[tauri::command]
+fn get_vocabulary(vocabulary: tauri::State<'_, Arc<Vocabulary>>) -> Vec<VocabularyEntry> {
+    vocabulary.entries()
+}
+
+#[tauri::command]
+fn set_vocabulary(
+    vocabulary: tauri::State<'_, Arc<Vocabulary>>,
+    entries: Vec<VocabularyEntry>,
+) -> Result<(), String> {
+    vocabulary.set_entries(entries)
+}
+
+#[tauri::command]
+fn set_vocabulary_enabled(vocabulary: tauri::State<'_, Arc<Vocabulary>>, enabled: bool) {
+    vocabulary.set_enabled(enabled);
+}
+
+/// Teach the vocabulary that `heard` should be corrected to `term`.
+#[tauri::command]
+fn learn_vocabulary_correction(
+    vocabulary: tauri::State<'_, Arc<Vocabulary>>,
+    heard: String,
+    term: String,
+) -> Result<(), String> {
+    vocabulary.learn(&heard, &term)
+}
+
+#[tauri::command]
+fn export_vocabulary(vocabulary: tauri::State<'_, Arc<Vocabulary>>) -> Result<String, String> {
+    vocabulary.export_json()
+}
+
+/// Import exported JSON, merging unless `replace`. Returns the number of entries read.
+#[tauri::command]
+fn import_vocabulary(
+    vocabulary: tauri::State<'_, Arc<Vocabulary>>,
+    json: String,
+    replace: bool,
+) -> Result<usize, String> {
+    vocabulary.import_json(&json, replace)
+}
+
+/// Page through recorded sessions, newest first, optionally filtered.
+#[tauri::command]
+fn query_history(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+    query: HistoryQuery,
+) -> Result<HistoryPage, String> {
+    ctx.history.query(&query)
+}
+
+#[tauri::command]
+fn get_history_entry(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+    id: i64,
+) -> Result<Option<HistoryEntry>, String> {
+    ctx.history.get(id)
+}
+
+#[tauri::command]
+fn delete_history_entry(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+    id: i64,
+) -> Result<(), String> {
+    ctx.history.delete(id)
+}
+
+#[tauri::command]
+fn clear_history(ctx: tauri::State<'_, Arc<ws_server::ServerContext>>) -> Result<(), String> {
+    ctx.history.clear()
+}
+
+/// Rendered export text; the frontend saves or copies it. Notes and
+/// subtitles hold only injected FINALs unless `all_outcomes` is set.
+#[tauri::command]
+fn export_history(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+    selection: HistorySelection,
+    format: ExportFormat,
+    all_outcomes: Option<bool>,
+) -> Result<String, String> {
+    let entries = ctx.history.select(&selection)?;
+    let offset = *chrono::Local::now().offset();
+    Ok(export::render(
+        &entries,
+        format,
+        offset,
+        all_outcomes.unwrap_or(false),
+    ))
+}
+
+#[tauri::command]
+fn get_history_retention(ctx: tauri::State<'_, Arc<ws_server::ServerContext>>) -> Retention {
+    ctx.history.retention()
+}
+
+#[tauri::command]
+fn set_history_retention(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+    retention: Retention,
+) -> Result<(), String> {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    ctx.history.set_retention(retention, now_ms)
+}
+
+#[tauri::command]
+fn get_usage_stats(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+    query: StatsQuery,
+) -> Vec<StatsRow> {
+    ctx.stats.report(&query)
+}
+
+/// Start recording protocol traffic to a new file under the app data
+/// directory; returns its path.
+#[tauri::command]
+fn start_capture(
+    app: tauri::AppHandle,
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+) -> Result<String, String> {
+    use tauri::Manager;
+    let name = chrono::Local::now()
+        .format("capture-%Y%m%d-%H%M%S.jsonl")
+        .to_string();
+    let path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("App data dir: {e}"))?
+        .join("captures")
+        .join(name);
+    ctx.capture.start(&path)?;
+    Ok(path.display().to_string())
+}
+
+#[tauri::command]
+fn stop_capture(ctx: tauri::State<'_, Arc<ws_server::ServerContext>>) -> Option<String> {
+    ctx.capture.stop().map(|path| path.display().to_string())
+}
+
+#[tauri::command]
+fn get_capture_path(ctx: tauri::State<'_, Arc<ws_server::ServerContext>>) -> Option<String> {
+    ctx.capture.path().map(|path| path.display().to_string())
+}
+
+#[tauri::command]
+fn get_review_mode(ctx: tauri::State<'_, Arc<ws_server::ServerContext>>) -> bool {
+    ctx.review.is_enabled()
+}
+
+#[tauri::command]
+fn set_review_mode(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+    enabled: bool,
+) -> Result<(), String> {
+    ctx.review.set_enabled(enabled)
+}
+
+#[tauri::command]
+fn get_pending_entries(ctx: tauri::State<'_, Arc<ws_server::ServerContext>>) -> Vec<PendingEntry> {
+    ctx.review.entries()
+}
+
+/// Replace the text of a pending entry. Returns the updated queue.
+#[tauri::command]
+fn edit_pending_entry(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+    id: u64,
+    text: String,
+) -> Result<Vec<PendingEntry>, String> {
+    ctx.review.edit(id, &text)?;
+    Ok(ctx.review.entries())
+}
+
+/// Move a pending entry to `index`. Returns the updated queue.
+#[tauri::command]
+fn move_pending_entry(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+    id: u64,
+    index: usize,
+) -> Result<Vec<PendingEntry>, String> {
+    ctx.review.move_to(id, index)?;
+    Ok(ctx.review.entries())
+}
+
+/// Drop a pending entry without injecting it. Returns the updated queue.
+#[tauri::command]
+fn discard_pending_entry(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+    id: u64,
+) -> Result<Vec<PendingEntry>, String> {
+    ctx.discard_pending(id)?;
+    Ok(ctx.review.entries())
+}
+
+/// Inject a pending entry. Returns the updated queue.
+#[tauri::command]
+async fn approve_pending_entry(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+    id: u64,
+) -> Result<Vec<PendingEntry>, String> {
+    let ctx = Arc::clone(&ctx);
+    tauri::async_runtime::spawn_blocking(move || {
+        ctx.approve_pending(id)?;
+        Ok(ctx.review.entries())
+    })
+    .await
+    .map_err(|e| format!("Approve task: {e}"))?
+}
+
+/// Inject every pending entry in queue order.
+#[tauri::command]
+async fn approve_all_pending(
+    ctx: tauri::State<'_, Arc<ws_server::ServerContext>>,
+) -> Result<usize, String> {
+    let ctx = Arc::clone(&ctx);
+    tauri::async_runtime::spawn_blocking(move || ctx.approve_all_pending())
+        .await
+        .map_err(|e| format!("Approve task: {e}"))?
+}
+
+fn log_self_test(report: &[BackendHealth]) {
+    for health in report {
+        match &health.error {
+            None => log::info!("Injector backend '{}' usable", health.name),
+            Some(e) => log::warn!("Injector backend '{}' unusable: {}", health.name, e),
+        }
+    }
+}
+
+/// Extra sinks configured via `PTT_EXTRA_SINKS` (comma-separated sink specs,
+/// see `injection::sink::from_spec`). They only receive reconciled FINALs.
+fn extra_sinks() -> Option<BackgroundFanOut> {
+    let specs = std::env::var("PTT_EXTRA_SINKS").ok()?;
+    let mut sinks = Vec::new();
+    for spec in specs.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        match injection::sink::from_spec(spec) {
+            Ok(sink) => sinks.push((spec.to_string(), sink)),
+            Err(e) => log::error!("Ignoring extra sink: {e}"),
+        }
+    }
+    (!sinks.is_empty()).then(|| BackgroundFanOut::spawn(FanOutInjector::new(sinks)))
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![get_server_port])
+        .invoke_handler(tauri::generate_handler![
+            get_server_port,
+            get_live_typing,
+            set_live_typing,
+            get_injection_mode,
+            set_injection_mode,
+            injector_self_test,
+            get_text_rules,
+            set_text_rules,
+            upsert_text_rules,
+            delete_text_rules,
+            set_rules_on_partials,
+            get_voice_commands,
+            set_voice_commands,
+            get_voice_commands_enabled,
+            set_voice_commands_enabled,
+            undo_last_dictation,
+            get_smart_formatting,
+            set_smart_formatting,
+            reset_text_formatting,
+            set_normalization,
+            set_normalization_locale,
+            set_rules_locale,
+            get_confidence_policy,
+            set_confidence_policy,
+            get_held_texts,
+            confirm_held_text,
+            get_review_mode,
+            set_review_mode,
+            get_pending_entries,
+            edit_pending_entry,
+            move_pending_entry,
+            discard_pending_entry,
+            approve_pending_entry,
+            approve_all_pending,
+            get_redaction_config,
+            set_redaction_config,
+            get_vocabulary,
+            set_vocabulary,
+            set_vocabulary_enabled,
+            learn_vocabulary_correction,
+            export_vocabulary,
+            import_vocabulary,
+            query_history,
+            get_history_entry,
+            delete_history_entry,
+            clear_history,
+            export_history,
+            get_history_retention,
+            set_history_retention,
+            get_usage_stats,
+            start_capture,
+            stop_capture,
+            get_capture_path
+        ])
         .setup(|app| {
+            use tauri::Manager;
+
             let handle = app.handle().clone();
             let registry = Arc::new(tokio::sync::Mutex::new(ClientRegistry::new(15)));
-            let injector: Arc<dyn injection::TextInjector> =
-                Arc::new(ClipboardPasteInjector::new(handle.clone()));
-            let emitter: Arc<dyn ws_server::EventEmitter> =
-                Arc::new(TauriEventEmitter { app_handle: handle });
+            let emitter: Arc<dyn ws_server::EventEmitter> = Arc::new(TauriEventEmitter {
+                app_handle: handle.clone(),
+            });
+            let clipboard_only: Arc<dyn injection::TextInjector> = Arc::new(
+                ClipboardOnlyInjector::new(handle.clone(), Arc::clone(&emitter)),
+            );
+            let chain = Arc::new(ChainInjector::new(vec![
+                (
+                    "paste".to_string(),
+                    Arc::new(ClipboardPasteInjector::new(handle.clone())),
+                ),
+                (
+                    "typing".to_string(),
+                    Arc::new(TypingInjector::new(handle.clone())),
+                ),
+                ("clipboard-only".to_string(), Arc::clone(&clipboard_only)),
+                ("history-only".to_string(), Arc::new(HistoryOnlyInjector)),
+            ]));
+            app.manage(Arc::clone(&chain));
+            let switchable = Arc::new(SwitchableInjector::new(chain.clone(), clipboard_only));
+            app.manage(Arc::clone(&switchable));
+
+            let mut ctx = ws_server::ServerContext::new(registry, switchable, emitter);
+            ctx.extra_sinks = extra_sinks();
+            ctx.review = Arc::new(ReviewQueue::open(
+                app.path().app_data_dir()?.join("review_queue.json"),
+            )?);
+            ctx.history = Arc::new(History::open(
+                app.path().app_data_dir()?.join("history.db"),
+            )?);
+            ctx.stats = Arc::new(Statistics::open(
+                app.path().app_data_dir()?.join("stats.json"),
+            )?);
+            let ctx = Arc::new(ctx);
+            tauri::async_runtime::spawn(Arc::clone(&ctx.stats).flush_periodically(FLUSH_INTERVAL));
+            app.manage(Arc::clone(&ctx.live_typing));
+            app.manage(Arc::clone(&ctx.voice_commands));
+            app.manage(Arc::clone(&ctx));
+
+            let normalizer = Arc::new(Normalizer::with_defaults());
+            ctx.pipeline.push(normalizer.clone());
+            app.manage(normalizer);
+
+            let vocabulary = Arc::new(Vocabulary::open(
+                app.path().app_data_dir()?.join("vocabulary.json"),
+            )?);
+            ctx.pipeline.push(vocabulary.clone());
+            app.manage(vocabulary);
+
+            let rule_store = Arc::new(RuleStore::open(
+                app.path().app_data_dir()?.join("rules.json"),
+            )?);
+            let now_ms = chrono::Utc::now().timestamp_millis();
+            rule_store.seed_if_empty(rules::default_rules(now_ms))?;
+            let rule_engine = Arc::new(RuleEngine::new(Vec::new()));
+            rule_store.bind_engine(Arc::clone(&rule_engine));
+            ctx.pipeline.push(rule_engine.clone());
+            app.manage(rule_engine);
+            app.manage(Arc::clone(&rule_store));
+
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = rule_sync::start_rule_sync_server(9877, rule_store).await {
+                    log::error!("Rule sync server error: {e}");
+                }
+            });
 
             tauri::async_runtime::spawn(async move {
-                if let Err(e) = ws_server::start_server(9876, registry, injector, emitter).await {
+                match tauri::async_runtime::spawn_blocking(move || chain.self_test()).await {
+                    Ok(report) => log_self_test(&report),
+                    Err(e) => log::error!("Injector self-test failed: {e}"),
+                }
+                if let Err(e) = ws_server::serve(9876, ctx).await {
                     log::error!("WebSocket server error: {e}");
                 }
             });
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                use tauri::Manager;
+                let ctx = app.state::<Arc<ws_server::ServerContext>>();
+                if let Err(e) = ctx.stats.flush() {
+                    log::warn!("Statistics: {}", e);
+                }
+            }
+        });
 }
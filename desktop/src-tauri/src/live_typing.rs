@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::injection::TextInjector;
+
+/// Minimal edit that turns already-typed text into the target text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub backspaces: usize,
+    pub insert: String,
+}
+
+impl TextEdit {
+    pub fn is_empty(&self) -> bool {
+        self.backspaces == 0 && self.insert.is_empty()
+    }
+}
+
+/// Diff `typed` against `target` on the longest common char prefix.
+/// Counts are in chars because each backspace removes one character.
+pub fn diff(typed: &str, target: &str) -> TextEdit {
+    let common = typed
+        .chars()
+        .zip(target.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    TextEdit {
+        backspaces: typed.chars().count() - common,
+        insert: target.chars().skip(common).collect(),
+    }
+}
+
+/// Send an edit through the injector: erase first, then type the new tail.
+pub fn apply_edit(injector: &dyn TextInjector, edit: &TextEdit) -> Result<(), String> {
+    if edit.backspaces > 0 {
+        injector.erase(edit.backspaces)?;
+    }
+    if !edit.insert.is_empty() {
+        injector.inject(&edit.insert)?;
+    }
+    Ok(())
+}
+
+/// Tracks what has been typed per client while live typing is enabled.
+///
+/// PARTIALs are diffed against the typed text of the current session and
+/// FINAL reconciles the last diff, so the target app ends up holding exactly
+/// the FINAL text.
+pub struct LiveTyping {
+    enabled: AtomicBool,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+#[derive(Default)]
+struct Session {
+    /// What is on screen, as far as the injector confirmed
+    typed: String,
+    /// Last PARTIAL applied
+    seq: Option<u64>,
+    /// A PARTIAL went somewhere other than the cursor (clipboard, history),
+    /// so the rest of the session is not typed live
+    abandoned: bool,
+}
+
+impl LiveTyping {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: AtomicBool::new(enabled),
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+        if !enabled {
+            self.sessions.lock().unwrap().clear();
+        }
+    }
+
+    /// Start a fresh session for the client (PTT_START).
+    pub fn begin(&self, client_id: &str) {
+        self.sessions.lock().unwrap().remove(client_id);
+    }
+
+    /// Type a PARTIAL. Does nothing when live typing is off or when `seq` is
+    /// not newer than the last applied PARTIAL (reordered or repeated
+    /// messages). The typed state only advances as far as the injector got,
+    /// so a failed edit never leads to backspacing text that wasn't typed.
+    /// Once a PARTIAL lands off the cursor (clipboard-only mode, or a chain
+    /// falling back), live typing stops for the session.
+    pub fn update(
+        &self,
+        client_id: &str,
+        seq: u64,
+        partial: &str,
+        injector: &dyn TextInjector,
+    ) -> Result<(), String> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions.entry(client_id.to_string()).or_default();
+        if session.abandoned || session.seq.is_some_and(|last| seq <= last) {
+            return Ok(());
+        }
+        let edit = diff(&session.typed, partial);
+        if edit.backspaces > 0 {
+            injector.erase(edit.backspaces)?;
+            let kept = session.typed.chars().count() - edit.backspaces;
+            session.typed = session.typed.chars().take(kept).collect();
+        }
+        if !edit.insert.is_empty() {
+            injector.inject(&edit.insert)?;
+            if !injector.inserts_at_cursor() {
+                session.abandoned = true;
+                return Ok(());
+            }
+        }
+        session.typed = partial.to_string();
+        session.seq = Some(seq);
+        Ok(())
+    }
+
+    /// Reconciling edit for the FINAL. None when nothing was typed live for
+    /// this session, in which case the FINAL should be injected as usual.
+    /// An abandoned session erases what it typed and inserts the whole FINAL.
+    pub fn finish(&self, client_id: &str, final_text: &str) -> Option<TextEdit> {
+        let session = self.sessions.lock().unwrap().remove(client_id)?;
+        if !session.abandoned {
+            return Some(diff(&session.typed, final_text));
+        }
+        if session.typed.is_empty() {
+            return None;
+        }
+        Some(TextEdit {
+            backspaces: session.typed.chars().count(),
+            insert: final_text.to_string(),
+        })
+    }
+
+    /// Forget what was typed for the client (e.g. on disconnect).
+    pub fn clear(&self, client_id: &str) {
+        self.sessions.lock().unwrap().remove(client_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::injection::chain::ChainInjector;
+    use crate::injection::testing::MockInjector;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_diff_appends_when_prefix_matches() {
+        let edit = diff("hello", "hello world");
+        assert_eq!(edit.backspaces, 0);
+        assert_eq!(edit.insert, " world");
+    }
+
+    #[test]
+    fn test_diff_backspaces_on_correction() {
+        let edit = diff("hello word", "hello world");
+        assert_eq!(edit.backspaces, 1);
+        assert_eq!(edit.insert, "ld");
+    }
+
+    #[test]
+    fn test_diff_counts_chars_not_bytes() {
+        let edit = diff("안녕하세요", "안녕히");
+        assert_eq!(edit.backspaces, 3);
+        assert_eq!(edit.insert, "히");
+    }
+
+    #[test]
+    fn test_diff_identical_is_empty() {
+        assert!(diff("same", "same").is_empty());
+    }
+
+    #[test]
+    fn test_disabled_ignores_partials() {
+        let live = LiveTyping::new(false);
+        let (mock, injected) = MockInjector::new();
+        live.update("phone-01", 1, "hello", &mock).unwrap();
+        assert!(injected.lock().unwrap().is_empty());
+        assert!(live.finish("phone-01", "hello").is_none());
+    }
+
+    #[test]
+    fn test_session_partials_then_final() {
+        let live = LiveTyping::new(true);
+        let (mock, injected) = MockInjector::new();
+        live.begin("phone-01");
+
+        live.update("phone-01", 1, "hel", &mock).unwrap();
+        live.update("phone-01", 2, "help", &mock).unwrap();
+        live.update("phone-01", 3, "hello", &mock).unwrap();
+        assert_eq!(
+            *injected.lock().unwrap(),
+            vec!["hel", "p", "<erase 1>", "lo"]
+        );
+
+        let edit = live.finish("phone-01", "Hello.").unwrap();
+        assert_eq!((edit.backspaces, edit.insert.as_str()), (5, "Hello."));
+
+        // Session state is consumed by finish
+        assert!(live.finish("phone-01", "Hello.").is_none());
+    }
+
+    #[test]
+    fn test_failed_edit_is_not_recorded_as_typed() {
+        let live = LiveTyping::new(true);
+        live.begin("phone-01");
+        let failing = MockInjector::failing();
+        assert!(live.update("phone-01", 1, "hello", &failing).is_err());
+
+        // Nothing reached the screen, so nothing may be backspaced later
+        let (mock, injected) = MockInjector::new();
+        live.update("phone-01", 2, "hello there", &mock).unwrap();
+        assert_eq!(*injected.lock().unwrap(), vec!["hello there"]);
+        let edit = live.finish("phone-01", "hello there").unwrap();
+        assert!(edit.is_empty());
+    }
+
+    #[test]
+    fn test_partials_falling_back_to_clipboard_stop_live_typing() {
+        let live = LiveTyping::new(true);
+        let (clipboard, copied) = MockInjector::off_cursor();
+        let chain = ChainInjector::new(vec![
+            ("paste".to_string(), Arc::new(MockInjector::failing())),
+            ("clipboard".to_string(), Arc::new(clipboard)),
+        ]);
+        live.begin("phone-01");
+
+        live.update("phone-01", 1, "hel", &chain).unwrap();
+        live.update("phone-01", 2, "help", &chain).unwrap();
+        // Nothing is erased, later PARTIALs don't touch the clipboard
+        assert_eq!(*copied.lock().unwrap(), vec!["hel"]);
+
+        // The FINAL is injected in full rather than as a diff tail
+        assert!(live.finish("phone-01", "Help").is_none());
+    }
+
+    #[test]
+    fn test_abandoned_session_replaces_typed_text_with_final() {
+        let live = LiveTyping::new(true);
+        live.begin("phone-01");
+        let (paste, typed) = MockInjector::new();
+        let (clipboard, copied) = MockInjector::off_cursor();
+        let paste = Arc::new(paste);
+        let chain = ChainInjector::new(vec![
+            (
+                "paste".to_string(),
+                Arc::clone(&paste) as Arc<dyn TextInjector>,
+            ),
+            ("clipboard".to_string(), Arc::new(clipboard)),
+        ]);
+        live.update("phone-01", 1, "meet", &chain).unwrap();
+        paste.fail_next(1);
+        live.update("phone-01", 2, "meeting", &chain).unwrap();
+        assert_eq!(*typed.lock().unwrap(), vec!["meet"]);
+        assert_eq!(*copied.lock().unwrap(), vec!["ing"]);
+
+        let edit = live.finish("phone-01", "Meeting.").unwrap();
+        assert_eq!((edit.backspaces, edit.insert.as_str()), (4, "Meeting."));
+    }
+
+    #[test]
+    fn test_stale_partials_are_ignored() {
+        let live = LiveTyping::new(true);
+        let (mock, injected) = MockInjector::new();
+        live.begin("phone-01");
+        live.update("phone-01", 1, "meet", &mock).unwrap();
+        live.update("phone-01", 3, "meeting at", &mock).unwrap();
+        // Reordered and duplicated PARTIALs arrive late
+        live.update("phone-01", 2, "meeting", &mock).unwrap();
+        live.update("phone-01", 3, "meeting at", &mock).unwrap();
+        assert_eq!(*injected.lock().unwrap(), vec!["meet", "ing at"]);
+
+        // A new session starts counting again
+        live.begin("phone-01");
+        live.update("phone-01", 1, "next", &mock).unwrap();
+        assert_eq!(injected.lock().unwrap().last().unwrap(), "next");
+    }
+
+    #[test]
+    fn test_apply_edit_erases_then_injects() {
+        let (mock, injected) = MockInjector::new();
+        let edit = TextEdit {
+            backspaces: 2,
+            insert: "ld".to_string(),
+        };
+        apply_edit(&mock, &edit).unwrap();
+
+        let recorded = injected.lock().unwrap();
+        assert_eq!(*recorded, vec!["<erase 2>", "ld"]);
+    }
+}
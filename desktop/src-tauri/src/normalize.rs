@@ -0,0 +1,1097 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+use crate::pipeline::{TextKind, TextStage};
+
+/// One normalization step of a locale's rule set
+pub trait NormalizeRule: Send + Sync {
+    fn name(&self) -> &str;
+    fn apply(&self, text: &str) -> String;
+}
+
+// --- Word helpers ---
+
+/// Split trailing sentence punctuation off a word: "PM." -> ("PM", ".")
+fn split_trailing(word: &str) -> (&str, &str) {
+    let core = word.trim_end_matches(['.', ',', '!', '?', ';', ':']);
+    // Keep abbreviations like "p.m." intact apart from the final dot
+    let core = if core.is_empty() { word } else { core };
+    (core, &word[core.len()..])
+}
+
+/// Text split into words plus the whitespace around them, so rules can
+/// rewrite words without collapsing line breaks, tabs or double spaces.
+struct Words {
+    words: Vec<String>,
+    /// `gaps[i]` precedes `words[i]`; the last entry trails the text
+    gaps: Vec<String>,
+}
+
+impl Words {
+    fn new(text: &str) -> Self {
+        let mut words = Vec::new();
+        let mut gaps = vec![String::new()];
+        let mut word = String::new();
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                    gaps.push(String::new());
+                }
+                gaps.last_mut().unwrap().push(c);
+            } else {
+                word.push(c);
+            }
+        }
+        if !word.is_empty() {
+            words.push(word);
+            gaps.push(String::new());
+        }
+        Self { words, gaps }
+    }
+
+    /// Split hyphenated words whose parts all pass `split` ("twenty-five"),
+    /// keeping the hyphen as the gap between the parts.
+    fn split_hyphenated(self, split: impl Fn(&str) -> bool) -> Self {
+        let mut words = Vec::new();
+        let mut gaps = vec![self.gaps[0].clone()];
+        for (word, gap_after) in self.words.iter().zip(&self.gaps[1..]) {
+            let (core, trail) = split_trailing(word);
+            let parts: Vec<&str> = core.split('-').collect();
+            if parts.len() > 1 && parts.iter().all(|p| split(p)) {
+                for (k, part) in parts.iter().enumerate() {
+                    if k > 0 {
+                        gaps.push("-".to_string());
+                    }
+                    words.push(part.to_string());
+                }
+                words.last_mut().unwrap().push_str(trail);
+            } else {
+                words.push(word.clone());
+            }
+            gaps.push(gap_after.clone());
+        }
+        Self { words, gaps }
+    }
+
+    /// Reassemble rewritten pieces, each tagged with the first word it
+    /// replaces; a piece keeps the whitespace in front of that word.
+    fn join(&self, pieces: &[(usize, String)]) -> String {
+        let mut text = String::new();
+        for (start, piece) in pieces {
+            text.push_str(&self.gaps[*start]);
+            text.push_str(piece);
+        }
+        text.push_str(&self.gaps[self.words.len()]);
+        text
+    }
+}
+
+fn is_integer(core: &str) -> bool {
+    !core.is_empty() && core.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_amount(core: &str) -> bool {
+    is_integer(core.replacen('.', "", 1).as_str()) && !core.starts_with('.') && !core.ends_with('.')
+}
+
+/// "5000" -> "5,000"; decimals are left alone.
+fn group_thousands(amount: &str) -> String {
+    let (int, frac) = amount.split_once('.').unwrap_or((amount, ""));
+    let mut grouped = String::new();
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    if !frac.is_empty() {
+        grouped.push('.');
+        grouped.push_str(frac);
+    }
+    grouped
+}
+
+// --- Spoken punctuation ---
+
+/// Whether the words before `end` close a clause: the text ends, the
+/// recognizer punctuated, a line break follows or the next word is capitalized.
+fn at_clause_boundary(tokens: &Words, end: usize) -> bool {
+    let Some(next) = tokens.words.get(end) else {
+        return true;
+    };
+    !split_trailing(&tokens.words[end - 1]).1.is_empty()
+        || tokens.gaps[end].contains('\n')
+        || next.starts_with(char::is_uppercase)
+}
+
+struct Phrase {
+    tokens: Vec<&'static str>,
+    symbol: &'static str,
+    /// Also an ordinary word ("trial period"), so only converted at a clause boundary
+    boundary_only: bool,
+}
+
+/// Replaces spoken punctuation words ("comma", "쉼표") with the symbol,
+/// attached to the preceding word.
+pub struct SpokenPunctuation {
+    phrases: Vec<Phrase>,
+}
+
+impl SpokenPunctuation {
+    pub fn new(table: &[(&'static str, &'static str)]) -> Self {
+        let mut phrases: Vec<Phrase> = table
+            .iter()
+            .map(|(spoken, symbol)| Phrase {
+                tokens: spoken.split(' ').collect(),
+                symbol,
+                boundary_only: false,
+            })
+            .collect();
+        phrases.sort_by_key(|p| std::cmp::Reverse(p.tokens.len()));
+        Self { phrases }
+    }
+
+    /// Only convert these spoken phrases where a clause ends.
+    pub fn boundary_only(mut self, spoken: &[&str]) -> Self {
+        for phrase in &mut self.phrases {
+            if spoken.contains(&phrase.tokens.join(" ").as_str()) {
+                phrase.boundary_only = true;
+            }
+        }
+        self
+    }
+
+    pub fn english() -> Self {
+        Self::new(&[
+            ("comma", ","),
+            ("period", "."),
+            ("full stop", "."),
+            ("question mark", "?"),
+            ("exclamation mark", "!"),
+            ("exclamation point", "!"),
+            ("colon", ":"),
+            ("semicolon", ";"),
+        ])
+        .boundary_only(&["period", "colon"])
+    }
+
+    pub fn korean() -> Self {
+        Self::new(&[
+            ("쉼표", ","),
+            ("마침표", "."),
+            ("물음표", "?"),
+            ("느낌표", "!"),
+            ("콜론", ":"),
+            ("세미콜론", ";"),
+        ])
+    }
+
+    fn match_at(&self, tokens: &Words, i: usize) -> Option<(usize, &'static str)> {
+        self.phrases.iter().find_map(|phrase| {
+            let len = phrase.tokens.len();
+            let candidate = tokens.words.get(i..i + len)?;
+            let matches = candidate
+                .iter()
+                .zip(&phrase.tokens)
+                .enumerate()
+                .all(|(k, (w, t))| {
+                    // Only the last spoken word may carry the recognizer's own punctuation
+                    let core = if k + 1 == len {
+                        split_trailing(w).0
+                    } else {
+                        w.as_str()
+                    };
+                    core.eq_ignore_ascii_case(t)
+                });
+            let placed = !phrase.boundary_only || at_clause_boundary(tokens, i + len);
+            (matches && placed).then_some((len, phrase.symbol))
+        })
+    }
+}
+
+impl NormalizeRule for SpokenPunctuation {
+    fn name(&self) -> &str {
+        "spoken-punctuation"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let tokens = Words::new(text);
+        let words = &tokens.words;
+        let mut out: Vec<(usize, String)> = Vec::with_capacity(words.len());
+        let mut i = 0;
+        while i < words.len() {
+            if let (Some((_, prev)), Some((len, symbol))) =
+                (out.last_mut(), self.match_at(&tokens, i))
+            {
+                let (prev_core, _) = split_trailing(prev);
+                prev.truncate(prev_core.len());
+                prev.push_str(symbol);
+                i += len;
+                continue;
+            }
+            out.push((i, words[i].clone()));
+            i += 1;
+        }
+        tokens.join(&out)
+    }
+}
+
+// --- English ---
+
+const EN_MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+const EN_UNITS: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const EN_TENS: [&str; 8] = [
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const EN_ORDINALS: [&str; 20] = [
+    "",
+    "first",
+    "second",
+    "third",
+    "fourth",
+    "fifth",
+    "sixth",
+    "seventh",
+    "eighth",
+    "ninth",
+    "tenth",
+    "eleventh",
+    "twelfth",
+    "thirteenth",
+    "fourteenth",
+    "fifteenth",
+    "sixteenth",
+    "seventeenth",
+    "eighteenth",
+    "nineteenth",
+];
+
+fn en_unit(word: &str) -> Option<u64> {
+    EN_UNITS.iter().position(|u| *u == word).map(|v| v as u64)
+}
+
+fn en_tens(word: &str) -> Option<u64> {
+    EN_TENS
+        .iter()
+        .position(|t| *t == word)
+        .map(|i| (i as u64 + 2) * 10)
+}
+
+fn en_ordinal(word: &str) -> Option<u64> {
+    match word {
+        "twentieth" => Some(20),
+        "thirtieth" => Some(30),
+        "" => None,
+        _ => EN_ORDINALS
+            .iter()
+            .position(|o| *o == word)
+            .map(|v| v as u64),
+    }
+}
+
+fn en_scale(word: &str) -> Option<u64> {
+    match word {
+        "thousand" => Some(1_000),
+        "million" => Some(1_000_000),
+        _ => None,
+    }
+}
+
+fn is_en_number_word(word: &str) -> bool {
+    en_unit(word).is_some()
+        || en_tens(word).is_some()
+        || en_ordinal(word).is_some()
+        || en_scale(word).is_some()
+        || word == "hundred"
+}
+
+/// Words after which a lone small number is still written in digits
+fn is_en_unit_word(word: &str) -> bool {
+    meridiem(word).is_some()
+        || matches!(
+            word,
+            "o'clock" | "percent" | "dollars" | "dollar" | "bucks" | "euros" | "euro" | "cents"
+        )
+}
+
+fn meridiem(word: &str) -> Option<&'static str> {
+    match word.to_lowercase().replace('.', "").as_str() {
+        "am" => Some("AM"),
+        "pm" => Some("PM"),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Last {
+    Start,
+    Unit,
+    Tens,
+    Hundred,
+    Scale,
+    And,
+}
+
+/// Parse a run of number words at `start`: (value, end, is_ordinal).
+fn parse_en_number(words: &[String], start: usize) -> Option<(u64, usize, bool)> {
+    let (mut total, mut current) = (0u64, 0u64);
+    let mut last = Last::Start;
+    let mut end = start;
+    while let Some(word) = words.get(end) {
+        let (core, trail) = split_trailing(word);
+        let lower = core.to_lowercase();
+        let after_group = matches!(last, Last::Start | Last::Hundred | Last::Scale | Last::And);
+        if let Some(v) = en_ordinal(&lower) {
+            if after_group || (last == Last::Tens && v < 10) {
+                return Some((total + current + v, end + 1, true));
+            }
+            break;
+        } else if let Some(v) = en_unit(&lower) {
+            if !(after_group || (last == Last::Tens && v < 10)) {
+                break;
+            }
+            current += v;
+            last = Last::Unit;
+        } else if let Some(v) = en_tens(&lower) {
+            if !after_group {
+                break;
+            }
+            current += v;
+            last = Last::Tens;
+        } else if lower == "hundred" {
+            if !matches!(last, Last::Unit | Last::Tens) || current >= 100 {
+                break;
+            }
+            current *= 100;
+            last = Last::Hundred;
+        } else if let Some(scale) = en_scale(&lower) {
+            if last == Last::Start || last == Last::And {
+                break;
+            }
+            total += current.max(1) * scale;
+            current = 0;
+            last = Last::Scale;
+        } else if lower == "and" && matches!(last, Last::Hundred | Last::Scale) && trail.is_empty()
+        {
+            let next = words
+                .get(end + 1)
+                .map(|w| split_trailing(w).0.to_lowercase());
+            if !next.is_some_and(|n| en_unit(&n).is_some() || en_tens(&n).is_some()) {
+                break;
+            }
+            last = Last::And;
+        } else {
+            break;
+        }
+        end += 1;
+        if !trail.is_empty() {
+            break;
+        }
+    }
+    (end > start).then_some((total + current, end, false))
+}
+
+/// Spelled-out numbers to digits ("twenty five" -> "25", "March fifth" ->
+/// "March 5"). Lone small numbers stay words ("one of them") unless a unit
+/// follows ("three PM"). "May" with a cardinal and "second" are usually not
+/// a date ("you may one day", "I may second that"), so they only form one
+/// when another number follows.
+pub struct EnglishNumbers;
+
+impl NormalizeRule for EnglishNumbers {
+    fn name(&self) -> &str {
+        "en-numbers"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        // "twenty-five" parses like "twenty five"
+        let tokens =
+            Words::new(text).split_hyphenated(|part| is_en_number_word(&part.to_lowercase()));
+        let words = &tokens.words;
+
+        let mut out: Vec<(usize, String)> = Vec::with_capacity(words.len());
+        let mut i = 0;
+        while i < words.len() {
+            let Some((value, end, ordinal)) = parse_en_number(words, i) else {
+                out.push((i, words[i].clone()));
+                i += 1;
+                continue;
+            };
+            let trail = split_trailing(&words[end - 1]).1;
+            let month = i
+                .checked_sub(1)
+                .map(|p| split_trailing(&words[p]))
+                .filter(|(_, prev_trail)| prev_trail.is_empty())
+                .map(|(prev, _)| prev.to_lowercase())
+                .filter(|prev| EN_MONTHS.contains(&prev.as_str()));
+            let number_follows = trail.is_empty()
+                && words.get(end).is_some_and(|w| {
+                    let next = split_trailing(w).0;
+                    is_integer(next) || is_en_number_word(&next.to_lowercase())
+                });
+            let ambiguous = (month.as_deref() == Some("may") && !ordinal)
+                || (end - i == 1 && words[i].to_lowercase().starts_with("second"));
+            let after_month = month.is_some() && (!ambiguous || number_follows);
+            let next_is_unit = trail.is_empty()
+                && words.get(end).is_some_and(|w| {
+                    let next = split_trailing(w).0.to_lowercase();
+                    is_en_unit_word(&next) || en_unit(&next).is_some() || en_tens(&next).is_some()
+                });
+            let convert = if ordinal {
+                after_month
+            } else {
+                value >= 10 || end - i >= 2 || next_is_unit || after_month
+            };
+            if convert {
+                out.push((i, format!("{value}{trail}")));
+            } else {
+                out.extend((i..end).map(|j| (j, words[j].clone())));
+            }
+            i = end;
+        }
+        tokens.join(&out)
+    }
+}
+
+/// "3 PM" / "3 30 p.m." / "3 o'clock" -> "3 PM" / "3:30 PM" / "3:00"
+pub struct EnglishTimes;
+
+impl NormalizeRule for EnglishTimes {
+    fn name(&self) -> &str {
+        "en-times"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let tokens = Words::new(text);
+        let words = &tokens.words;
+        let mut out: Vec<(usize, String)> = Vec::with_capacity(words.len());
+        let mut i = 0;
+        while i < words.len() {
+            let (core, trail) = split_trailing(&words[i]);
+            let hour = core.parse::<u32>().ok().filter(|h| (1..=12).contains(h));
+            if let (Some(hour), true, true) = (hour, trail.is_empty(), is_integer(core)) {
+                let next = words.get(i + 1).map(|w| split_trailing(w));
+                let after = words.get(i + 2).map(|w| split_trailing(w));
+                if let Some((m, m_trail)) = next {
+                    if let Some(label) = meridiem(m) {
+                        out.push((i, format!("{hour} {label}{m_trail}")));
+                        i += 2;
+                        continue;
+                    }
+                    if m.eq_ignore_ascii_case("o'clock") {
+                        out.push((i, format!("{hour}:00{m_trail}")));
+                        i += 2;
+                        continue;
+                    }
+                    let minute = m.parse::<u32>().ok().filter(|m| *m < 60);
+                    if let (Some(minute), true, Some((a, a_trail))) =
+                        (minute, m_trail.is_empty() && is_integer(m), after)
+                    {
+                        if let Some(label) = meridiem(a) {
+                            out.push((i, format!("{hour}:{minute:02} {label}{a_trail}")));
+                            i += 3;
+                            continue;
+                        }
+                    }
+                }
+            }
+            out.push((i, words[i].clone()));
+            i += 1;
+        }
+        tokens.join(&out)
+    }
+}
+
+/// "25 dollars" -> "$25", "5 dollars and 50 cents" -> "$5.50",
+/// "3000 euros" -> "€3,000", "50 percent" -> "50%"
+pub struct EnglishCurrency;
+
+impl NormalizeRule for EnglishCurrency {
+    fn name(&self) -> &str {
+        "en-currency"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let tokens = Words::new(text);
+        let words = &tokens.words;
+        let lower_core = |j: usize| -> Option<(String, &str)> {
+            words.get(j).map(|w| {
+                let (core, trail) = split_trailing(w);
+                (core.to_lowercase(), trail)
+            })
+        };
+        let mut out: Vec<(usize, String)> = Vec::with_capacity(words.len());
+        let mut i = 0;
+        while i < words.len() {
+            let (core, trail) = split_trailing(&words[i]);
+            if !(trail.is_empty() && is_amount(core)) {
+                out.push((i, words[i].clone()));
+                i += 1;
+                continue;
+            }
+            match lower_core(i + 1) {
+                Some((unit, unit_trail))
+                    if matches!(unit.as_str(), "dollars" | "dollar" | "bucks") =>
+                {
+                    let cents = match (lower_core(i + 2), lower_core(i + 3), lower_core(i + 4)) {
+                        (Some((and, "")), Some((c, "")), Some((label, c_trail)))
+                            if unit_trail.is_empty()
+                                && and == "and"
+                                && matches!(label.as_str(), "cents" | "cent")
+                                && is_integer(&c)
+                                && c.len() <= 2
+                                && !core.contains('.') =>
+                        {
+                            Some((c.parse::<u32>().unwrap_or(0), c_trail))
+                        }
+                        _ => None,
+                    };
+                    match cents {
+                        Some((c, c_trail)) => {
+                            out.push((i, format!("${}.{c:02}{c_trail}", group_thousands(core))));
+                            i += 5;
+                        }
+                        None => {
+                            out.push((i, format!("${}{unit_trail}", group_thousands(core))));
+                            i += 2;
+                        }
+                    }
+                }
+                Some((unit, unit_trail)) if matches!(unit.as_str(), "euros" | "euro") => {
+                    out.push((i, format!("€{}{unit_trail}", group_thousands(core))));
+                    i += 2;
+                }
+                Some((unit, unit_trail)) if unit == "percent" => {
+                    out.push((i, format!("{core}%{unit_trail}")));
+                    i += 2;
+                }
+                _ => {
+                    out.push((i, words[i].clone()));
+                    i += 1;
+                }
+            }
+        }
+        tokens.join(&out)
+    }
+}
+
+// --- Korean ---
+
+fn sino_digit(c: char) -> Option<u64> {
+    "영일이삼사오육칠팔구"
+        .chars()
+        .position(|d| d == c)
+        .map(|v| v as u64)
+}
+
+fn sino_small_unit(c: char) -> Option<u64> {
+    match c {
+        '십' => Some(10),
+        '백' => Some(100),
+        '천' => Some(1_000),
+        _ => None,
+    }
+}
+
+fn sino_big_unit(c: char) -> Option<u64> {
+    match c {
+        '만' => Some(10_000),
+        '억' => Some(100_000_000),
+        _ => None,
+    }
+}
+
+/// Parse Sino-Korean numerals ("삼십오" -> 35, "오천" -> 5000, "이만 삼천" without space).
+fn parse_sino(chars: &[char]) -> Option<u64> {
+    if chars.is_empty() {
+        return None;
+    }
+    let (mut total, mut section) = (0u64, 0u64);
+    let mut digit: Option<u64> = None;
+    for &c in chars {
+        if let Some(d) = sino_digit(c) {
+            if digit.is_some() {
+                return None;
+            }
+            digit = Some(d);
+        } else if let Some(unit) = sino_small_unit(c) {
+            section += digit.take().unwrap_or(1) * unit;
+        } else if let Some(unit) = sino_big_unit(c) {
+            section += digit.take().unwrap_or(0);
+            total += section.max(1) * unit;
+            section = 0;
+        } else {
+            return None;
+        }
+    }
+    Some(total + section + digit.unwrap_or(0))
+}
+
+/// Counters written right after digits, longest first
+const KO_COUNTERS: [&str; 9] = ["개월", "퍼센트", "프로", "원", "월", "일", "년", "분", "초"];
+
+/// Words that look like number + counter but usually aren't ("이분" = this person)
+const KO_AMBIGUOUS: [&str; 4] = ["이분", "사원", "오일", "이일"];
+
+/// Particles and copulas that may follow a counter ("삼월에", "오천 원입니다")
+const KO_PARTICLES: [&str; 17] = [
+    "은",
+    "는",
+    "이",
+    "가",
+    "에",
+    "을",
+    "를",
+    "에서",
+    "부터",
+    "까지",
+    "쯤",
+    "도",
+    "만",
+    "의",
+    "씩",
+    "입니다",
+    "이에요",
+];
+
+/// The counter `word` starts with, if the rest is empty or a particle, so
+/// "원칙" or "월급" after "이" are left alone.
+fn ko_counter(word: &str) -> Option<&'static str> {
+    let (core, _) = split_trailing(word);
+    KO_COUNTERS.iter().copied().find(|c| {
+        core.strip_prefix(c)
+            .is_some_and(|rest| rest.is_empty() || KO_PARTICLES.contains(&rest))
+    })
+}
+
+fn render_ko(value: u64, counter: &str, rest: &str) -> String {
+    match counter {
+        "원" => format!("{}원{rest}", group_thousands(&value.to_string())),
+        "퍼센트" | "프로" => format!("{value}%{rest}"),
+        _ => format!("{value}{counter}{rest}"),
+    }
+}
+
+/// Sino-Korean numbers followed by a counter to digits: "오월 오일" -> "5월 5일",
+/// "오천 원" -> "5,000원", "삼십 퍼센트" -> "30%". Bare numerals are left
+/// alone since "일" and "이" are also ordinary words.
+pub struct KoreanNumbers;
+
+impl KoreanNumbers {
+    /// "삼월에" -> "3월에". After a month the day reading wins ("5월 오일").
+    fn attached(word: &str, after_month: bool) -> Option<String> {
+        let chars: Vec<char> = word.chars().collect();
+        (1..chars.len()).rev().find_map(|split| {
+            let value = parse_sino(&chars[..split])?;
+            let rest: String = chars[split..].iter().collect();
+            let counter = ko_counter(&rest)?;
+            let spoken = format!("{}{counter}", chars[..split].iter().collect::<String>());
+            if KO_AMBIGUOUS.contains(&spoken.as_str()) && !(after_month && counter == "일") {
+                return None;
+            }
+            Some(render_ko(value, counter, &rest[counter.len()..]))
+        })
+    }
+}
+
+impl NormalizeRule for KoreanNumbers {
+    fn name(&self) -> &str {
+        "ko-numbers"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let tokens = Words::new(text);
+        let words = &tokens.words;
+        let mut out: Vec<(usize, String)> = Vec::with_capacity(words.len());
+        let mut i = 0;
+        while i < words.len() {
+            let chars: Vec<char> = words[i].chars().collect();
+            // "오천 원": numeral and counter spoken as separate words
+            if let (Some(value), Some(next)) = (parse_sino(&chars), words.get(i + 1)) {
+                if let Some(counter) = ko_counter(next) {
+                    let spoken = format!("{}{counter}", words[i]);
+                    if !KO_AMBIGUOUS.contains(&spoken.as_str()) {
+                        out.push((i, render_ko(value, counter, &next[counter.len()..])));
+                        i += 2;
+                        continue;
+                    }
+                }
+            }
+            let after_month = out.last().is_some_and(|(_, w)| {
+                w.ends_with('월') && w.starts_with(|c: char| c.is_ascii_digit())
+            });
+            let word = Self::attached(&words[i], after_month).unwrap_or_else(|| words[i].clone());
+            out.push((i, word));
+            i += 1;
+        }
+        tokens.join(&out)
+    }
+}
+
+/// Native Korean hour words, longest first
+const KO_NATIVE_HOURS: [(&str, u32); 12] = [
+    ("열한", 11),
+    ("열두", 12),
+    ("다섯", 5),
+    ("여섯", 6),
+    ("일곱", 7),
+    ("여덟", 8),
+    ("아홉", 9),
+    ("한", 1),
+    ("두", 2),
+    ("세", 3),
+    ("네", 4),
+    ("열", 10),
+];
+
+/// What may follow "시" so that "세 시작" (start) isn't read as an hour
+const KO_HOUR_SUFFIXES: [&str; 8] = ["", "에", "부터", "까지", "쯤", "간", "간에", "간동안"];
+
+fn ko_hour_suffix(word: &str) -> Option<&str> {
+    let (core, trail) = split_trailing(word);
+    let rest = core.strip_prefix('시')?;
+    KO_HOUR_SUFFIXES
+        .contains(&rest)
+        .then(|| &word[word.len() - rest.len() - trail.len()..])
+}
+
+/// Native-number hours to digits: "오후 세 시" -> "오후 3시", "두시간" -> "2시간"
+pub struct KoreanTimes;
+
+impl NormalizeRule for KoreanTimes {
+    fn name(&self) -> &str {
+        "ko-times"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let tokens = Words::new(text);
+        let words = &tokens.words;
+        let mut out: Vec<(usize, String)> = Vec::with_capacity(words.len());
+        let mut i = 0;
+        'scan: while i < words.len() {
+            for (native, hour) in KO_NATIVE_HOURS {
+                if words[i] == native {
+                    if let Some(rest) = words.get(i + 1).and_then(|w| ko_hour_suffix(w)) {
+                        out.push((i, format!("{hour}시{rest}")));
+                        i += 2;
+                        continue 'scan;
+                    }
+                } else if let Some(rest) = words[i].strip_prefix(native).and_then(ko_hour_suffix) {
+                    out.push((i, format!("{hour}시{rest}")));
+                    i += 1;
+                    continue 'scan;
+                }
+            }
+            out.push((i, words[i].clone()));
+            i += 1;
+        }
+        tokens.join(&out)
+    }
+}
+
+// --- Stage ---
+
+fn language(locale: &str) -> String {
+    locale.split(['-', '_']).next().unwrap_or("").to_lowercase()
+}
+
+/// Pipeline stage running the rule set of the FINAL's locale. The locale is
+/// fixed via `set_locale` or, by default, guessed from the script (Hangul ->
+/// Korean, otherwise English). Off until enabled in the settings, since it
+/// rewrites words the user may have meant literally.
+pub struct Normalizer {
+    enabled: AtomicBool,
+    locale: RwLock<Option<String>>,
+    rule_sets: RwLock<HashMap<String, Vec<Arc<dyn NormalizeRule>>>>,
+}
+
+impl Normalizer {
+    /// No rule sets; register your own
+    pub fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            locale: RwLock::new(None),
+            rule_sets: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Built-in English and Korean rule sets
+    pub fn with_defaults() -> Self {
+        let normalizer = Self::new();
+        normalizer.register("en-US", Arc::new(SpokenPunctuation::english()));
+        normalizer.register("en-US", Arc::new(EnglishNumbers));
+        normalizer.register("en-US", Arc::new(EnglishTimes));
+        normalizer.register("en-US", Arc::new(EnglishCurrency));
+        normalizer.register("ko-KR", Arc::new(SpokenPunctuation::korean()));
+        normalizer.register("ko-KR", Arc::new(KoreanTimes));
+        normalizer.register("ko-KR", Arc::new(KoreanNumbers));
+        normalizer
+    }
+
+    /// Append `rule` to the rule set of `locale`'s language.
+    pub fn register(&self, locale: &str, rule: Arc<dyn NormalizeRule>) {
+        self.rule_sets
+            .write()
+            .unwrap()
+            .entry(language(locale))
+            .or_default()
+            .push(rule);
+    }
+
+    pub fn rule_names(&self, locale: &str) -> Vec<String> {
+        self.rule_sets
+            .read()
+            .unwrap()
+            .get(&language(locale))
+            .map(|rules| rules.iter().map(|r| r.name().to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn set_locale(&self, locale: Option<String>) {
+        *self.locale.write().unwrap() = locale;
+    }
+
+    fn locale_for(&self, text: &str) -> String {
+        if let Some(locale) = self.locale.read().unwrap().as_deref() {
+            return language(locale);
+        }
+        let hangul = text.chars().any(|c| ('가'..='힣').contains(&c));
+        if hangul { "ko" } else { "en" }.to_string()
+    }
+
+    pub fn normalize(&self, text: &str) -> String {
+        if !self.enabled.load(Ordering::SeqCst) || text.trim().is_empty() {
+            return text.to_string();
+        }
+        let rule_sets = self.rule_sets.read().unwrap();
+        match rule_sets.get(&self.locale_for(text)) {
+            Some(rules) => rules
+                .iter()
+                .fold(text.to_string(), |acc, rule| rule.apply(&acc)),
+            None => text.to_string(),
+        }
+    }
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+impl TextStage for Normalizer {
+    fn name(&self) -> &str {
+        "normalize"
+    }
+
+    /// PARTIALs are unstable; only FINALs are normalized.
+    fn process(&self, text: &str, kind: TextKind) -> String {
+        match kind {
+            TextKind::Final => self.normalize(text),
+            TextKind::Partial => text.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn en(text: &str) -> String {
+        let normalizer = Normalizer::with_defaults();
+        normalizer.set_enabled(true);
+        normalizer.set_locale(Some("en-US".to_string()));
+        normalizer.normalize(text)
+    }
+
+    fn ko(text: &str) -> String {
+        let normalizer = Normalizer::with_defaults();
+        normalizer.set_enabled(true);
+        normalizer.set_locale(Some("ko-KR".to_string()));
+        normalizer.normalize(text)
+    }
+
+    #[test]
+    fn test_en_spoken_punctuation() {
+        assert_eq!(
+            en("hello comma how are you question mark"),
+            "hello, how are you?"
+        );
+        assert_eq!(en("done full stop"), "done.");
+        // Nothing to attach to at the start
+        assert_eq!(en("comma is a word"), "comma is a word");
+    }
+
+    #[test]
+    fn test_en_numbers() {
+        assert_eq!(en("twenty five people"), "25 people");
+        assert_eq!(en("one hundred and five"), "105");
+        assert_eq!(en("three thousand two hundred"), "3200");
+        assert_eq!(en("twenty-one"), "21");
+        assert_eq!(en("one of them"), "one of them");
+        assert_eq!(en("forty."), "40.");
+    }
+
+    #[test]
+    fn test_en_times() {
+        assert_eq!(en("meet at three PM"), "meet at 3 PM");
+        assert_eq!(en("meet at 3 p.m."), "meet at 3 PM.");
+        assert_eq!(en("three thirty pm"), "3:30 PM");
+        assert_eq!(en("ten o'clock"), "10:00");
+    }
+
+    #[test]
+    fn test_en_dates() {
+        assert_eq!(en("due March fifth"), "due March 5");
+        assert_eq!(en("January twenty first"), "January 21");
+        assert_eq!(en("the second time"), "the second time");
+    }
+
+    #[test]
+    fn test_keeps_original_whitespace() {
+        assert_eq!(
+            en("Dear team,\n\ntwenty five  people\tcame comma right"),
+            "Dear team,\n\n25  people\tcame, right"
+        );
+        assert_eq!(en("  forty-two  "), "  42  ");
+        assert_eq!(ko("안녕하세요\n오천 원"), "안녕하세요\n5,000원");
+    }
+
+    #[test]
+    fn test_en_may_and_second_need_a_number() {
+        assert_eq!(en("I may second that"), "I may second that");
+        assert_eq!(en("you may one day"), "you may one day");
+        assert_eq!(en("March second"), "March second");
+        assert_eq!(en("May fifth"), "May 5");
+        assert_eq!(en("May second 2024"), "May 2 2024");
+    }
+
+    #[test]
+    fn test_en_period_and_colon_only_end_clauses() {
+        assert_eq!(en("the trial period ended"), "the trial period ended");
+        assert_eq!(en("use a colon here"), "use a colon here");
+        assert_eq!(en("it ended period"), "it ended.");
+        assert_eq!(en("agenda colon\nbudget"), "agenda:\nbudget");
+        assert_eq!(en("done period Next item"), "done. Next item");
+        // Unambiguous words still convert anywhere
+        assert_eq!(en("yes comma sure"), "yes, sure");
+    }
+
+    #[test]
+    fn test_en_currency_and_percent() {
+        assert_eq!(en("twenty five dollars"), "$25");
+        assert_eq!(en("five dollars and fifty cents"), "$5.50");
+        assert_eq!(en("three thousand euros"), "€3,000");
+        assert_eq!(en("fifty percent"), "50%");
+    }
+
+    #[test]
+    fn test_ko_spoken_punctuation() {
+        assert_eq!(
+            ko("안녕하세요 쉼표 반갑습니다 마침표"),
+            "안녕하세요, 반갑습니다."
+        );
+    }
+
+    #[test]
+    fn test_ko_dates_and_currency() {
+        assert_eq!(ko("오월 오일에 만나요"), "5월 5일에 만나요");
+        assert_eq!(ko("이십일일"), "21일");
+        assert_eq!(ko("오천 원입니다"), "5,000원입니다");
+        assert_eq!(ko("삼십 퍼센트"), "30%");
+    }
+
+    #[test]
+    fn test_ko_times() {
+        assert_eq!(ko("오후 세 시에 봐요"), "오후 3시에 봐요");
+        assert_eq!(ko("열두시 삼십 분"), "12시 30분");
+        assert_eq!(ko("두시간 걸려요"), "2시간 걸려요");
+    }
+
+    #[test]
+    fn test_ko_leaves_ordinary_words() {
+        assert_eq!(ko("이분이 오일을 샀어요"), "이분이 오일을 샀어요");
+        assert_eq!(ko("네 시작할게요"), "네 시작할게요");
+        assert_eq!(ko("일 이 삼"), "일 이 삼");
+        assert_eq!(ko("이 원칙을 지켜요"), "이 원칙을 지켜요");
+        assert_eq!(ko("이 원인은 몰라요"), "이 원인은 몰라요");
+        assert_eq!(ko("이 월급으로"), "이 월급으로");
+        assert_eq!(ko("이 초안을 봐 주세요"), "이 초안을 봐 주세요");
+        // A counter followed by a particle still counts
+        assert_eq!(ko("오천 원부터"), "5,000원부터");
+        assert_eq!(ko("삼월에."), "3월에.");
+    }
+
+    #[test]
+    fn test_locale_guessed_from_script() {
+        let normalizer = Normalizer::with_defaults();
+        normalizer.set_enabled(true);
+        assert_eq!(normalizer.normalize("twenty dollars"), "$20");
+        assert_eq!(normalizer.normalize("오천 원"), "5,000원");
+    }
+
+    #[test]
+    fn test_custom_rule_and_partials() {
+        struct Shout;
+        impl NormalizeRule for Shout {
+            fn name(&self) -> &str {
+                "shout"
+            }
+            fn apply(&self, text: &str) -> String {
+                text.to_uppercase()
+            }
+        }
+
+        let normalizer = Normalizer::new();
+        normalizer.register("en", Arc::new(Shout));
+        assert_eq!(normalizer.rule_names("en-GB"), vec!["shout"]);
+        // Off by default
+        assert_eq!(normalizer.process("hi", TextKind::Final), "hi");
+        normalizer.set_enabled(true);
+        assert_eq!(normalizer.process("hi", TextKind::Final), "HI");
+        assert_eq!(normalizer.process("hi", TextKind::Partial), "hi");
+    }
+}
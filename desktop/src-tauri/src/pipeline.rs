@@ -0,0 +1,92 @@
+use std::sync::{Arc, RwLock};
+
+/// Which kind of recognition result a stage is looking at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextKind {
+    /// PARTIAL — shown in the UI and typed by live typing
+    Partial,
+    /// FINAL — injected into the target app
+    Final,
+}
+
+/// One rewriting step applied to recognized text before it is shown or injected
+pub trait TextStage: Send + Sync {
+    fn name(&self) -> &str;
+    fn process(&self, text: &str, kind: TextKind) -> String;
+}
+
+/// Ordered list of stages run over every PARTIAL and FINAL
+pub struct TextPipeline {
+    stages: RwLock<Vec<Arc<dyn TextStage>>>,
+}
+
+impl TextPipeline {
+    pub fn new() -> Self {
+        Self {
+            stages: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub fn push(&self, stage: Arc<dyn TextStage>) {
+        self.stages.write().unwrap().push(stage);
+    }
+
+    pub fn stage_names(&self) -> Vec<String> {
+        self.stages
+            .read()
+            .unwrap()
+            .iter()
+            .map(|s| s.name().to_string())
+            .collect()
+    }
+
+    pub fn process(&self, text: &str, kind: TextKind) -> String {
+        let stages = self.stages.read().unwrap();
+        stages
+            .iter()
+            .fold(text.to_string(), |acc, stage| stage.process(&acc, kind))
+    }
+}
+
+impl Default for TextPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Suffix(&'static str);
+
+    impl TextStage for Suffix {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn process(&self, text: &str, kind: TextKind) -> String {
+            match kind {
+                TextKind::Final => format!("{text}{}", self.0),
+                TextKind::Partial => text.to_string(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_pipeline_is_identity() {
+        let pipeline = TextPipeline::new();
+        assert_eq!(pipeline.process("unchanged", TextKind::Final), "unchanged");
+    }
+
+    #[test]
+    fn test_stages_run_in_order() {
+        let pipeline = TextPipeline::new();
+        pipeline.push(Arc::new(Suffix("-a")));
+        pipeline.push(Arc::new(Suffix("-b")));
+
+        assert_eq!(pipeline.process("x", TextKind::Final), "x-a-b");
+        assert_eq!(pipeline.process("x", TextKind::Partial), "x");
+        assert_eq!(pipeline.stage_names(), vec!["-a", "-b"]);
+    }
+}
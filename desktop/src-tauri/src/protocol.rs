@@ -1,8 +1,9 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 // --- Payload structs ---
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct HelloPayload {
     #[serde(rename = "deviceModel")]
     pub device_model: String,
@@ -10,13 +11,13 @@ pub struct HelloPayload {
     pub capabilities: Vec<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct PttStartPayload {
     #[serde(rename = "sessionId")]
     pub session_id: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct PartialPayload {
     #[serde(rename = "sessionId")]
     pub session_id: String,
@@ -25,7 +26,7 @@ pub struct PartialPayload {
     pub confidence: f64,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct FinalPayload {
     #[serde(rename = "sessionId")]
     pub session_id: String,
@@ -33,15 +34,50 @@ pub struct FinalPayload {
     pub confidence: f64,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct AckPayload {
     #[serde(rename = "ackType")]
     pub ack_type: String,
+    /// FINAL: "injected", "pending" (review queue), "held", "rejected" or
+    /// "failed" (injection error); CONFIRM_HELD: "failed" when nothing happened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// FINAL only: queue id when the text is held for confirmation
+    #[serde(rename = "heldId", default, skip_serializing_if = "Option::is_none")]
+    pub held_id: Option<u64>,
+    /// FINAL only: why the FINAL failed or fell back from the review queue
+    /// to being held
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// HELLO only: binary encoding ("CBOR" or "MSGPACK") the desktop accepts
+    /// and answers in from now on; absent means JSON only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+}
+
+impl AckPayload {
+    pub fn new(ack_type: &str) -> Self {
+        Self {
+            ack_type: ack_type.to_string(),
+            status: None,
+            held_id: None,
+            error: None,
+            encoding: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ConfirmHeldPayload {
+    #[serde(rename = "heldId")]
+    pub held_id: u64,
+    /// true injects the held text, false drops it
+    pub accept: bool,
 }
 
 // --- Message enum ---
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type")]
 pub enum Message {
     #[serde(rename = "HELLO")]
@@ -75,6 +111,19 @@ pub enum Message {
         #[serde(rename = "clientId")]
         client_id: String,
     },
+    /// Erase what this client's last FINAL inserted
+    #[serde(rename = "UNDO")]
+    Undo {
+        #[serde(rename = "clientId")]
+        client_id: String,
+    },
+    /// Accept or drop a FINAL the desktop held for confirmation
+    #[serde(rename = "CONFIRM_HELD")]
+    ConfirmHeld {
+        #[serde(rename = "clientId")]
+        client_id: String,
+        payload: ConfirmHeldPayload,
+    },
     #[serde(rename = "ACK")]
     Ack {
         #[serde(rename = "clientId")]
@@ -89,6 +138,21 @@ pub fn parse_message(json: &str) -> Result<Message, serde_json::Error> {
     serde_json::from_str(json)
 }
 
+/// Like `parse_message`, but a message that doesn't match the protocol is
+/// checked against the JSON Schema so the error names the field, e.g.
+/// `payload.confidence: expected number, got string`.
+pub fn parse_message_validated(json: &str) -> Result<Message, String> {
+    let value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    message_from_value(value)
+}
+
+/// Schema-checked conversion of a message already decoded from JSON or one
+/// of the binary codecs
+pub fn message_from_value(value: serde_json::Value) -> Result<Message, String> {
+    crate::schema::validate(&value).map_err(|e| e.to_string())?;
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
 pub fn serialize_message(msg: &Message) -> Result<String, serde_json::Error> {
     serde_json::to_string(msg)
 }
@@ -181,19 +245,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_undo() {
+        let json = r#"{"type":"UNDO","clientId":"phone-01"}"#;
+        let msg = parse_message(json).unwrap();
+        assert_eq!(
+            msg,
+            Message::Undo {
+                client_id: "phone-01".to_string()
+            }
+        );
+    }
+
     #[test]
     fn test_serialize_ack_roundtrip() {
         let msg = Message::Ack {
             client_id: "phone-01".to_string(),
-            payload: AckPayload {
-                ack_type: "HELLO".to_string(),
-            },
+            payload: AckPayload::new("HELLO"),
         };
         let json = serialize_message(&msg).unwrap();
         let parsed = parse_message(&json).unwrap();
         assert_eq!(msg, parsed);
     }
 
+    #[test]
+    fn test_held_ack_fields() {
+        let json = r#"{"type":"ACK","clientId":"phone-01","payload":{"ackType":"FINAL","status":"held","heldId":7}}"#;
+        match parse_message(json).unwrap() {
+            Message::Ack { payload, .. } => {
+                assert_eq!(payload.status.as_deref(), Some("held"));
+                assert_eq!(payload.held_id, Some(7));
+            }
+            _ => panic!("expected Ack variant"),
+        }
+
+        // Plain ACKs keep their original shape
+        let plain = serialize_message(&Message::Ack {
+            client_id: "phone-01".to_string(),
+            payload: AckPayload::new("HELLO"),
+        })
+        .unwrap();
+        assert!(!plain.contains("heldId"));
+    }
+
+    #[test]
+    fn test_parse_confirm_held() {
+        let json =
+            r#"{"type":"CONFIRM_HELD","clientId":"phone-01","payload":{"heldId":3,"accept":true}}"#;
+        match parse_message(json).unwrap() {
+            Message::ConfirmHeld { client_id, payload } => {
+                assert_eq!(client_id, "phone-01");
+                assert_eq!(payload.held_id, 3);
+                assert!(payload.accept);
+            }
+            _ => panic!("expected ConfirmHeld variant"),
+        }
+    }
+
     #[test]
     fn test_parse_invalid_type() {
         let json = r#"{"type":"UNKNOWN","clientId":"phone-01"}"#;
@@ -205,4 +313,20 @@ mod tests {
         let json = r#"{not valid json"#;
         assert!(parse_message(json).is_err());
     }
+
+    #[test]
+    fn test_parse_validated_names_field() {
+        let json = r#"{"type":"FINAL","clientId":"phone-01","timestamp":1,"payload":{"sessionId":"s","text":"hi","confidence":"high"}}"#;
+        assert_eq!(
+            parse_message_validated(json),
+            Err("payload.confidence: expected number, got string".to_string())
+        );
+        let json = r#"{"type":"HEARTBEAT","clientId":"phone-01"}"#;
+        assert_eq!(
+            parse_message_validated(json),
+            Ok(Message::Heartbeat {
+                client_id: "phone-01".to_string()
+            })
+        );
+    }
 }
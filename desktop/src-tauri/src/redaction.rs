@@ -0,0 +1,443 @@
+use std::sync::RwLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::pipeline::TextKind;
+
+/// What to do with a detected span
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RedactAction {
+    /// Replace with `*` (cards keep their last four digits)
+    Mask,
+    /// Remove from the text
+    Drop,
+    /// Keep, but hold the FINAL until the user confirms it. PARTIALs are
+    /// masked so live typing never types the span.
+    Confirm,
+}
+
+/// Per-detector actions; `None` turns a detector off. The default redacts nothing.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionConfig {
+    pub card_numbers: Option<RedactAction>,
+    pub emails: Option<RedactAction>,
+    pub phone_numbers: Option<RedactAction>,
+    pub words: Option<RedactAction>,
+    /// Matched as whole words, case-insensitively
+    pub word_list: Vec<String>,
+}
+
+/// Result of running the detectors over one text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redacted {
+    pub text: String,
+    /// A `Confirm` detector matched a FINAL
+    pub needs_confirmation: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Detector {
+    CardNumber,
+    Email,
+    PhoneNumber,
+    Word,
+}
+
+struct Finding {
+    start: usize,
+    end: usize,
+    detector: Detector,
+    action: RedactAction,
+}
+
+struct Detectors {
+    config: RedactionConfig,
+    words: Option<Regex>,
+}
+
+/// Sensitive-data filter applied after the text pipeline, right before
+/// display and injection.
+pub struct Redactor {
+    card: Regex,
+    email: Regex,
+    phone: Regex,
+    /// Digits at the end of a PARTIAL that may grow into a card or phone number
+    number_tail: Regex,
+    /// Last word of a PARTIAL once it has an `@`
+    email_tail: Regex,
+    detectors: RwLock<Detectors>,
+}
+
+impl Redactor {
+    pub fn new(config: RedactionConfig) -> Result<Self, String> {
+        let redactor = Self {
+            card: Regex::new(r"\b(?:\d[ -]?){12,18}\d\b").unwrap(),
+            email: Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b").unwrap(),
+            phone: Regex::new(
+                r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{2,4}\)[ .-]?|\b\d{2,4}[ .-])\d{3,4}[ .-]\d{4}\b|\+\d{8,14}\b",
+            )
+            .unwrap(),
+            number_tail: Regex::new(r"(?:^|\s)[+(]?\d[\d ().-]*$").unwrap(),
+            email_tail: Regex::new(r"(?:^|\s)\S*@\S*$").unwrap(),
+            detectors: RwLock::new(Detectors {
+                config: RedactionConfig::default(),
+                words: None,
+            }),
+        };
+        redactor.set_config(config)?;
+        Ok(redactor)
+    }
+
+    pub fn config(&self) -> RedactionConfig {
+        self.detectors.read().unwrap().config.clone()
+    }
+
+    pub fn set_config(&self, config: RedactionConfig) -> Result<(), String> {
+        let mut words: Vec<&str> = config
+            .word_list
+            .iter()
+            .map(|w| w.trim())
+            .filter(|w| !w.is_empty())
+            .collect();
+        // Longest first so "foo bar" wins over "foo"
+        words.sort_by_key(|w| std::cmp::Reverse(w.chars().count()));
+        let pattern = words
+            .iter()
+            .map(|w| regex::escape(w))
+            .collect::<Vec<_>>()
+            .join("|");
+        let words = if pattern.is_empty() {
+            None
+        } else {
+            Some(
+                Regex::new(&format!(r"(?i)\b(?:{pattern})\b"))
+                    .map_err(|e| format!("Invalid word list: {e}"))?,
+            )
+        };
+        *self.detectors.write().unwrap() = Detectors { config, words };
+        Ok(())
+    }
+
+    pub fn redact(&self, text: &str, kind: TextKind) -> Redacted {
+        let findings = self.find(text);
+        let text = match kind {
+            TextKind::Partial => self.hold_back_tail(text, &findings),
+            TextKind::Final => text,
+        };
+        if findings.is_empty() {
+            return Redacted {
+                text: text.to_string(),
+                needs_confirmation: false,
+            };
+        }
+        let mut output = String::with_capacity(text.len());
+        let mut needs_confirmation = false;
+        let mut cursor = 0;
+        for finding in &findings {
+            output.push_str(&text[cursor..finding.start]);
+            let span = &text[finding.start..finding.end];
+            match (finding.action, kind) {
+                (RedactAction::Confirm, TextKind::Final) => {
+                    needs_confirmation = true;
+                    output.push_str(span);
+                }
+                (RedactAction::Mask, _) | (RedactAction::Confirm, TextKind::Partial) => {
+                    output.push_str(&mask(span, finding.detector));
+                }
+                (RedactAction::Drop, _) => {
+                    // Don't leave a double space or a space before punctuation
+                    let rest = &text[finding.end..];
+                    if output.ends_with(' ')
+                        && rest.starts_with(|c: char| c.is_whitespace() || ",.!?".contains(c))
+                    {
+                        output.pop();
+                    }
+                }
+            }
+            cursor = finding.end;
+        }
+        output.push_str(&text[cursor..]);
+        if findings.iter().any(|f| f.action == RedactAction::Drop) {
+            output = output.trim().to_string();
+        }
+        Redacted {
+            text: output,
+            needs_confirmation,
+        }
+    }
+
+    /// Mask every detected span whatever its action, e.g. to keep a held
+    /// FINAL out of the history in the clear.
+    pub fn mask_all(&self, text: &str) -> String {
+        let mut output = String::with_capacity(text.len());
+        let mut cursor = 0;
+        for finding in self.find(text) {
+            output.push_str(&text[cursor..finding.start]);
+            output.push_str(&mask(&text[finding.start..finding.end], finding.detector));
+            cursor = finding.end;
+        }
+        output.push_str(&text[cursor..]);
+        output
+    }
+
+    /// A PARTIAL can end in the first digits of a card number or in half an
+    /// email address that no detector matches yet. Cut that tail off so live
+    /// typing never types it in the clear; a later PARTIAL or the FINAL
+    /// brings it back once the detectors can see all of it.
+    fn hold_back_tail<'a>(&self, text: &'a str, findings: &[Finding]) -> &'a str {
+        let (numbers, emails) = {
+            let config = &self.detectors.read().unwrap().config;
+            (
+                config.card_numbers.is_some() || config.phone_numbers.is_some(),
+                config.emails.is_some(),
+            )
+        };
+        let tail = [(numbers, &self.number_tail), (emails, &self.email_tail)]
+            .into_iter()
+            .filter(|(enabled, _)| *enabled)
+            .filter_map(|(_, regex)| regex.find(text))
+            .map(|m| m.start())
+            .min();
+        // A tail that is already part of a finding gets redacted as usual
+        let detected_up_to = findings.last().map_or(0, |f| f.end);
+        match tail {
+            Some(start) if start >= detected_up_to => text[..start].trim_end(),
+            _ => text,
+        }
+    }
+
+    /// Non-overlapping findings in text order. Detectors earlier in the list
+    /// win overlaps (a card number is not also a phone number).
+    fn find(&self, text: &str) -> Vec<Finding> {
+        let detectors = self.detectors.read().unwrap();
+        let config = &detectors.config;
+        let enabled = [
+            (Detector::CardNumber, config.card_numbers, Some(&self.card)),
+            (Detector::Email, config.emails, Some(&self.email)),
+            (
+                Detector::PhoneNumber,
+                config.phone_numbers,
+                Some(&self.phone),
+            ),
+            (Detector::Word, config.words, detectors.words.as_ref()),
+        ];
+
+        let mut findings: Vec<Finding> = Vec::new();
+        for (detector, action, regex) in enabled {
+            let (Some(action), Some(regex)) = (action, regex) else {
+                continue;
+            };
+            for m in regex.find_iter(text) {
+                if detector == Detector::CardNumber && !luhn_valid(m.as_str()) {
+                    continue;
+                }
+                let overlaps = findings
+                    .iter()
+                    .any(|f| m.start() < f.end && f.start < m.end());
+                if !overlaps {
+                    findings.push(Finding {
+                        start: m.start(),
+                        end: m.end(),
+                        detector,
+                        action,
+                    });
+                }
+            }
+        }
+        findings.sort_by_key(|f| f.start);
+        findings
+    }
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(RedactionConfig::default()).unwrap()
+    }
+}
+
+/// Luhn checksum over the digits of `number`, for 13–19 digit card numbers
+fn luhn_valid(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match i % 2 {
+            0 => d,
+            _ if d * 2 > 9 => d * 2 - 9,
+            _ => d * 2,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// Replace letters and digits with `*`, keeping separators. Card numbers
+/// keep their last four digits.
+fn mask(span: &str, detector: Detector) -> String {
+    let total = span.chars().filter(char::is_ascii_digit).count();
+    let keep_from = match detector {
+        Detector::CardNumber => total.saturating_sub(4),
+        _ => usize::MAX,
+    };
+    let mut seen = 0;
+    span.chars()
+        .map(|c| {
+            if c.is_ascii_digit() {
+                seen += 1;
+                if seen > keep_from {
+                    return c;
+                }
+            }
+            if c.is_alphanumeric() {
+                '*'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor(config: RedactionConfig) -> Redactor {
+        Redactor::new(config).unwrap()
+    }
+
+    fn final_text(redactor: &Redactor, text: &str) -> String {
+        redactor.redact(text, TextKind::Final).text
+    }
+
+    #[test]
+    fn test_default_config_redacts_nothing() {
+        let redactor = Redactor::default();
+        let text = "card 4111 1111 1111 1111, mail me at a@b.com";
+        assert_eq!(final_text(&redactor, text), text);
+    }
+
+    #[test]
+    fn test_card_numbers_need_valid_luhn() {
+        let redactor = redactor(RedactionConfig {
+            card_numbers: Some(RedactAction::Mask),
+            ..Default::default()
+        });
+        assert_eq!(
+            final_text(&redactor, "my card is 4111 1111 1111 1111 thanks"),
+            "my card is **** **** **** 1111 thanks"
+        );
+        assert_eq!(
+            final_text(&redactor, "order 4111 1111 1111 1112 shipped"),
+            "order 4111 1111 1111 1112 shipped"
+        );
+    }
+
+    #[test]
+    fn test_email_and_phone_actions() {
+        let redactor = redactor(RedactionConfig {
+            emails: Some(RedactAction::Drop),
+            phone_numbers: Some(RedactAction::Mask),
+            ..Default::default()
+        });
+        assert_eq!(
+            final_text(
+                &redactor,
+                "write to jane.doe@example.com, or call 010-1234-5678."
+            ),
+            "write to, or call ***-****-****."
+        );
+        assert_eq!(
+            final_text(&redactor, "call (555) 123-4567 today"),
+            "call (***) ***-**** today"
+        );
+    }
+
+    #[test]
+    fn test_word_list_is_whole_word_and_case_insensitive() {
+        let redactor = redactor(RedactionConfig {
+            words: Some(RedactAction::Mask),
+            word_list: vec!["darn".to_string(), "project x".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(
+            final_text(&redactor, "Darn, Project X slipped again, darnit"),
+            "****, ******* * slipped again, darnit"
+        );
+    }
+
+    #[test]
+    fn test_confirm_holds_finals_and_masks_partials() {
+        let redactor = redactor(RedactionConfig {
+            emails: Some(RedactAction::Confirm),
+            ..Default::default()
+        });
+        let text = "send it to ops@example.com";
+
+        let final_result = redactor.redact(text, TextKind::Final);
+        assert!(final_result.needs_confirmation);
+        assert_eq!(final_result.text, text);
+
+        let partial = redactor.redact(text, TextKind::Partial);
+        assert!(!partial.needs_confirmation);
+        assert_eq!(partial.text, "send it to ***@*******.***");
+    }
+
+    #[test]
+    fn test_card_is_not_also_a_phone_number() {
+        let redactor = redactor(RedactionConfig {
+            card_numbers: Some(RedactAction::Drop),
+            phone_numbers: Some(RedactAction::Confirm),
+            ..Default::default()
+        });
+        let result = redactor.redact("pay with 4111-1111-1111-1111 now", TextKind::Final);
+        assert_eq!(result.text, "pay with now");
+        assert!(!result.needs_confirmation);
+    }
+
+    #[test]
+    fn test_growing_partials_never_show_the_number() {
+        let redactor = redactor(RedactionConfig {
+            card_numbers: Some(RedactAction::Mask),
+            emails: Some(RedactAction::Confirm),
+            ..Default::default()
+        });
+        let partials = [
+            "my card is 4",
+            "my card is 4111",
+            "my card is 4111 1111",
+            "my card is 4111 1111 1111",
+            "my card is 4111 1111 1111 1111",
+            "my card is 4111 1111 1111 1111 and mail ops@",
+            "my card is 4111 1111 1111 1111 and mail ops@example",
+            "my card is 4111 1111 1111 1111 and mail ops@example.com",
+        ];
+        let shown: Vec<String> = partials
+            .iter()
+            .map(|p| redactor.redact(p, TextKind::Partial).text)
+            .collect();
+
+        assert!(shown
+            .iter()
+            .all(|t| !t.contains("4111") && !t.contains("ops@")));
+        assert_eq!(shown[3], "my card is");
+        assert_eq!(shown[4], "my card is **** **** **** 1111");
+        assert_eq!(shown[6], "my card is **** **** **** 1111 and mail");
+        assert_eq!(
+            shown[7],
+            "my card is **** **** **** 1111 and mail ***@*******.***"
+        );
+        // Without detectors nothing is held back
+        assert_eq!(
+            Redactor::default()
+                .redact("my card is 4111", TextKind::Partial)
+                .text,
+            "my card is 4111"
+        );
+    }
+}
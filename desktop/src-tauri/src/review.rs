@@ -0,0 +1,295 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::json_file;
+
+/// FINAL waiting in the review queue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingEntry {
+    pub id: u64,
+    pub client_id: String,
+    pub session_id: String,
+    /// Processed text, possibly edited by the user
+    pub text: String,
+    pub confidence: f64,
+    /// Unix milliseconds
+    pub queued_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewState {
+    enabled: bool,
+    next_id: u64,
+    entries: Vec<PendingEntry>,
+}
+
+/// Entry taken out of the queue while it is being injected
+#[derive(Debug)]
+pub struct Claimed {
+    pub entry: PendingEntry,
+    index: usize,
+}
+
+/// Review-before-insert queue persisted as JSON.
+///
+/// While enabled, FINALs are queued here instead of being injected; the user
+/// edits, reorders, approves or discards them from the desktop UI. Both the
+/// entries and the enabled flag survive restarts.
+pub struct ReviewQueue {
+    path: Option<PathBuf>,
+    state: Mutex<ReviewState>,
+}
+
+impl ReviewQueue {
+    /// Load the queue from `path`, starting empty if the file doesn't exist yet.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let state: ReviewState = json_file::load(&path)?;
+        Ok(Self {
+            path: Some(path),
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Queue without a backing file
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            state: Mutex::new(ReviewState::default()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.state.lock().unwrap().enabled
+    }
+
+    /// Turning review off keeps queued entries; they can still be approved.
+    pub fn set_enabled(&self, enabled: bool) -> Result<(), String> {
+        self.mutate(|state| {
+            state.enabled = enabled;
+            Ok(())
+        })
+    }
+
+    /// Pending entries in insertion order
+    pub fn entries(&self) -> Vec<PendingEntry> {
+        self.state.lock().unwrap().entries.clone()
+    }
+
+    pub fn get(&self, id: u64) -> Result<PendingEntry, String> {
+        let state = self.state.lock().unwrap();
+        Ok(state.entries[position(&state, id)?].clone())
+    }
+
+    /// Append a FINAL to the queue.
+    pub fn push(
+        &self,
+        client_id: &str,
+        session_id: &str,
+        text: &str,
+        confidence: f64,
+    ) -> Result<PendingEntry, String> {
+        self.mutate(|state| {
+            state.next_id += 1;
+            let entry = PendingEntry {
+                id: state.next_id,
+                client_id: client_id.to_string(),
+                session_id: session_id.to_string(),
+                text: text.to_string(),
+                confidence,
+                queued_at: chrono::Utc::now().timestamp_millis(),
+            };
+            state.entries.push(entry.clone());
+            Ok(entry)
+        })
+    }
+
+    pub fn edit(&self, id: u64, text: &str) -> Result<(), String> {
+        self.mutate(|state| {
+            let index = position(state, id)?;
+            state.entries[index].text = text.to_string();
+            Ok(())
+        })
+    }
+
+    /// Move an entry to `index` (clamped to the end of the queue).
+    pub fn move_to(&self, id: u64, index: usize) -> Result<(), String> {
+        self.mutate(|state| {
+            let entry = state.entries.remove(position(state, id)?);
+            let index = index.min(state.entries.len());
+            state.entries.insert(index, entry);
+            Ok(())
+        })
+    }
+
+    /// Remove an entry from the queue (to inject or to discard it).
+    pub fn remove(&self, id: u64) -> Result<PendingEntry, String> {
+        self.mutate(|state| {
+            let index = position(state, id)?;
+            Ok(state.entries.remove(index))
+        })
+    }
+
+    /// Take an entry out of the queue to inject it, so a concurrent approval
+    /// can't claim (and type) it too. The file keeps listing it until the
+    /// claim is completed.
+    pub fn claim(&self, id: u64) -> Result<Claimed, String> {
+        let mut state = self.state.lock().unwrap();
+        let index = position(&state, id)?;
+        let entry = state.entries.remove(index);
+        Ok(Claimed { entry, index })
+    }
+
+    /// Put a claimed entry back where it was, after its injection failed.
+    pub fn release(&self, claimed: Claimed) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        let index = claimed.index.min(state.entries.len());
+        state.entries.insert(index, claimed.entry);
+        self.save(&state)
+    }
+
+    /// Drop a claimed entry for good once it was injected. It stays out of
+    /// memory even if the save fails, so it is never approved twice.
+    pub fn complete(&self, _claimed: Claimed) -> Result<(), String> {
+        let state = self.state.lock().unwrap();
+        self.save(&state)
+    }
+
+    /// Apply `f` to a copy of the state and keep it only once it is saved.
+    fn mutate<T>(
+        &self,
+        f: impl FnOnce(&mut ReviewState) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut state = self.state.lock().unwrap();
+        let mut next = state.clone();
+        let result = f(&mut next)?;
+        self.save(&next)?;
+        *state = next;
+        Ok(result)
+    }
+
+    fn save(&self, state: &ReviewState) -> Result<(), String> {
+        match &self.path {
+            Some(path) => json_file::save(path, state),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for ReviewQueue {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+fn position(state: &ReviewState, id: u64) -> Result<usize, String> {
+    state
+        .entries
+        .iter()
+        .position(|e| e.id == id)
+        .ok_or_else(|| format!("No pending entry with id {id}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(queue: &ReviewQueue) -> Vec<String> {
+        queue.entries().into_iter().map(|e| e.text).collect()
+    }
+
+    #[test]
+    fn test_edit_reorder_and_remove() {
+        let queue = ReviewQueue::in_memory();
+        let first = queue.push("phone-01", "s-1", "one", 0.9).unwrap();
+        let second = queue.push("phone-01", "s-2", "two", 0.9).unwrap();
+        queue.push("phone-02", "s-3", "three", 0.9).unwrap();
+
+        queue.edit(second.id, "two, edited").unwrap();
+        queue.move_to(second.id, 0).unwrap();
+        assert_eq!(texts(&queue), vec!["two, edited", "one", "three"]);
+
+        queue.move_to(second.id, 99).unwrap();
+        assert_eq!(texts(&queue), vec!["one", "three", "two, edited"]);
+
+        assert_eq!(queue.remove(first.id).unwrap().text, "one");
+        assert!(queue.remove(first.id).is_err());
+        assert!(queue.edit(first.id, "gone").is_err());
+        assert_eq!(texts(&queue), vec!["three", "two, edited"]);
+    }
+
+    #[test]
+    fn test_queue_survives_reopen() {
+        let path = std::env::temp_dir().join(format!("ptt-review-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let queue = ReviewQueue::open(&path).unwrap();
+        queue.set_enabled(true).unwrap();
+        let entry = queue
+            .push("phone-01", "s-1", "kept for later", 0.8)
+            .unwrap();
+        drop(queue);
+
+        let reopened = ReviewQueue::open(&path).unwrap();
+        assert!(reopened.is_enabled());
+        assert_eq!(reopened.entries(), vec![entry.clone()]);
+        // Ids keep counting up across restarts
+        assert!(reopened.push("phone-01", "s-2", "next", 0.8).unwrap().id > entry.id);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_failed_save_changes_nothing() {
+        let path =
+            std::env::temp_dir().join(format!("ptt-review-fail-{}.json", std::process::id()));
+        let tmp = path.with_extension("json.tmp");
+        let _ = std::fs::remove_file(&path);
+        let queue = ReviewQueue::open(&path).unwrap();
+        let entry = queue.push("phone-01", "s-1", "kept", 0.8).unwrap();
+
+        // A directory where the temp file goes makes the write fail
+        std::fs::create_dir_all(&tmp).unwrap();
+        assert!(queue.push("phone-01", "s-2", "lost", 0.8).is_err());
+        assert!(queue.remove(entry.id).is_err());
+        assert!(queue.set_enabled(true).is_err());
+
+        assert!(!queue.is_enabled());
+        assert_eq!(queue.entries(), vec![entry.clone()]);
+        assert_eq!(queue.get(entry.id).unwrap(), entry);
+
+        std::fs::remove_dir(&tmp).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_claimed_entry_leaves_memory_even_if_save_fails() {
+        let path =
+            std::env::temp_dir().join(format!("ptt-review-claim-{}.json", std::process::id()));
+        let tmp = path.with_extension("json.tmp");
+        let _ = std::fs::remove_file(&path);
+        let queue = ReviewQueue::open(&path).unwrap();
+        let first = queue.push("phone-01", "s-1", "first", 0.8).unwrap();
+        let second = queue.push("phone-01", "s-2", "second", 0.8).unwrap();
+
+        // Claimed entries can't be claimed again; released ones keep their place
+        let claimed = queue.claim(first.id).unwrap();
+        assert!(queue.claim(first.id).is_err());
+        assert_eq!(queue.entries(), vec![second.clone()]);
+        queue.release(claimed).unwrap();
+        assert_eq!(queue.entries(), vec![first.clone(), second.clone()]);
+
+        std::fs::create_dir_all(&tmp).unwrap();
+        let claimed = queue.claim(first.id).unwrap();
+        assert!(queue.complete(claimed).is_err());
+        assert_eq!(queue.entries(), vec![second]);
+        assert!(queue.claim(first.id).is_err());
+
+        std::fs::remove_dir(&tmp).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
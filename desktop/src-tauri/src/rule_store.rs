@@ -0,0 +1,392 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::json_file;
+use crate::rules::{RuleEngine, TextRule};
+
+/// Rule plus sync bookkeeping: the ruleset version that last touched it and
+/// whether it is a tombstone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredRule {
+    #[serde(flatten)]
+    rule: TextRule,
+    version: i64,
+    deleted: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RuleStoreState {
+    ruleset_version: i64,
+    rules: Vec<StoredRule>,
+}
+
+/// One entry of an incremental sync, matching Android's `RuleChangePayload`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleChangePayload {
+    pub id: String,
+    pub category: String,
+    pub trigger: String,
+    pub replacement: String,
+    pub locale: String,
+    pub enabled: bool,
+    pub priority: i32,
+    pub updated_at: i64,
+    pub deleted: bool,
+}
+
+/// Matches Android's `RuleVersionResponse`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleVersionResponse {
+    pub ruleset_version: i64,
+}
+
+/// Matches Android's `RuleDeltaResponse`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleDeltaResponse {
+    pub ruleset_version: i64,
+    pub changes: Vec<RuleChangePayload>,
+}
+
+/// Versioned rule store persisted as JSON.
+///
+/// Every mutation that changes a rule bumps the ruleset version and stamps
+/// the touched rules with it; deletions leave tombstones so phones can sync
+/// incrementally. Changes only become visible once they are on disk.
+pub struct RuleStore {
+    path: Option<PathBuf>,
+    state: Mutex<RuleStoreState>,
+    engine: Mutex<Option<Arc<RuleEngine>>>,
+}
+
+impl RuleStore {
+    /// Load the store from `path`, starting empty if the file doesn't exist yet.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let state: RuleStoreState = json_file::load(&path)?;
+        Ok(Self {
+            path: Some(path),
+            state: Mutex::new(state),
+            engine: Mutex::new(None),
+        })
+    }
+
+    /// Store without a backing file; rules last until it is dropped
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            state: Mutex::new(RuleStoreState::default()),
+            engine: Mutex::new(None),
+        }
+    }
+
+    /// Keep `engine` in sync with the active rules from now on.
+    pub fn bind_engine(&self, engine: Arc<RuleEngine>) {
+        engine.set_rules(self.active_rules());
+        *self.engine.lock().unwrap() = Some(engine);
+    }
+
+    pub fn version(&self) -> i64 {
+        self.state.lock().unwrap().ruleset_version
+    }
+
+    pub fn active_rules(&self) -> Vec<TextRule> {
+        let state = self.state.lock().unwrap();
+        state
+            .rules
+            .iter()
+            .filter(|r| !r.deleted)
+            .map(|r| r.rule.clone())
+            .collect()
+    }
+
+    pub fn count_rules(&self) -> usize {
+        self.active_rules().len()
+    }
+
+    /// Seed with defaults on first run (mirrors Android's `initialize`).
+    pub fn seed_if_empty(&self, defaults: Vec<TextRule>) -> Result<(), String> {
+        if self.state.lock().unwrap().rules.is_empty() {
+            self.upsert(defaults)?;
+        }
+        Ok(())
+    }
+
+    /// Insert or replace rules by id. Returns the new ruleset version.
+    pub fn upsert(&self, rules: Vec<TextRule>) -> Result<i64, String> {
+        self.mutate(|state, version| {
+            for rule in rules {
+                let stored = StoredRule {
+                    rule,
+                    version,
+                    deleted: false,
+                };
+                match state.rules.iter_mut().find(|r| r.rule.id == stored.rule.id) {
+                    Some(existing) => *existing = stored,
+                    None => state.rules.push(stored),
+                }
+            }
+        })
+    }
+
+    /// Tombstone rules by id. Unknown ids are ignored. Returns the new ruleset version.
+    pub fn delete(&self, ids: &[String]) -> Result<i64, String> {
+        self.mutate(|state, version| {
+            for stored in state.rules.iter_mut() {
+                if ids.contains(&stored.rule.id) && !stored.deleted {
+                    stored.deleted = true;
+                    stored.version = version;
+                }
+            }
+        })
+    }
+
+    /// Make `rules` the whole rule set: tombstone every other rule and upsert
+    /// the given ones. Returns the new ruleset version.
+    pub fn replace_all(&self, rules: Vec<TextRule>) -> Result<i64, String> {
+        self.mutate(|state, version| {
+            for stored in state.rules.iter_mut() {
+                if !stored.deleted && !rules.iter().any(|r| r.id == stored.rule.id) {
+                    stored.deleted = true;
+                    stored.version = version;
+                }
+            }
+            for rule in rules {
+                let stored = StoredRule {
+                    rule,
+                    version,
+                    deleted: false,
+                };
+                match state.rules.iter_mut().find(|r| r.rule.id == stored.rule.id) {
+                    Some(existing) => *existing = stored,
+                    None => state.rules.push(stored),
+                }
+            }
+        })
+    }
+
+    /// Everything touched after `since_version`, tombstones included.
+    pub fn changes_since(&self, since_version: i64) -> RuleDeltaResponse {
+        let state = self.state.lock().unwrap();
+        let mut changed: Vec<&StoredRule> = state
+            .rules
+            .iter()
+            .filter(|r| r.version > since_version)
+            .collect();
+        changed.sort_by(|a, b| a.version.cmp(&b.version).then(a.rule.id.cmp(&b.rule.id)));
+        RuleDeltaResponse {
+            ruleset_version: state.ruleset_version,
+            changes: changed
+                .into_iter()
+                .map(|r| RuleChangePayload {
+                    id: r.rule.id.clone(),
+                    category: r.rule.category.clone(),
+                    trigger: r.rule.trigger.clone(),
+                    replacement: r.rule.replacement.clone(),
+                    locale: r.rule.locale.clone(),
+                    enabled: r.rule.enabled,
+                    priority: r.rule.priority,
+                    updated_at: r.rule.updated_at,
+                    deleted: r.deleted,
+                })
+                .collect(),
+        }
+    }
+
+    /// Apply `f` to a copy of the state and swap it in once saved, so a
+    /// failed write changes nothing. A no-op keeps the current version.
+    fn mutate(&self, f: impl FnOnce(&mut RuleStoreState, i64)) -> Result<i64, String> {
+        let version = {
+            let mut state = self.state.lock().unwrap();
+            let version = state.ruleset_version + 1;
+            let mut next = state.clone();
+            f(&mut next, version);
+            if next.rules == state.rules {
+                return Ok(state.ruleset_version);
+            }
+            next.ruleset_version = version;
+            self.save(&next)?;
+            *state = next;
+            version
+        };
+        if let Some(engine) = self.engine.lock().unwrap().as_ref() {
+            engine.set_rules(self.active_rules());
+        }
+        Ok(version)
+    }
+
+    fn save(&self, state: &RuleStoreState) -> Result<(), String> {
+        match &self.path {
+            Some(path) => json_file::save(path, state),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::default_rules;
+
+    fn rule(id: &str, trigger: &str) -> TextRule {
+        TextRule {
+            id: id.to_string(),
+            category: "custom".to_string(),
+            trigger: trigger.to_string(),
+            replacement: format!("<{trigger}>"),
+            locale: "*".to_string(),
+            enabled: true,
+            priority: 100,
+            updated_at: 1,
+        }
+    }
+
+    #[test]
+    fn test_upsert_bumps_version_and_reports_changes() {
+        let store = RuleStore::in_memory();
+        assert_eq!(store.version(), 0);
+
+        assert_eq!(store.upsert(vec![rule("a", "alpha")]).unwrap(), 1);
+        assert_eq!(store.upsert(vec![rule("b", "beta")]).unwrap(), 2);
+
+        let delta = store.changes_since(1);
+        assert_eq!(delta.ruleset_version, 2);
+        assert_eq!(delta.changes.len(), 1);
+        assert_eq!(delta.changes[0].id, "b");
+
+        assert_eq!(store.changes_since(0).changes.len(), 2);
+        assert!(store.changes_since(2).changes.is_empty());
+    }
+
+    #[test]
+    fn test_delete_leaves_tombstone() {
+        let store = RuleStore::in_memory();
+        store
+            .upsert(vec![rule("a", "alpha"), rule("b", "beta")])
+            .unwrap();
+        store.delete(&["a".to_string()]).unwrap();
+
+        assert_eq!(store.count_rules(), 1);
+        let delta = store.changes_since(1);
+        assert_eq!(delta.ruleset_version, 2);
+        assert_eq!(delta.changes.len(), 1);
+        assert_eq!(delta.changes[0].id, "a");
+        assert!(delta.changes[0].deleted);
+    }
+
+    #[test]
+    fn test_reupsert_revives_tombstone() {
+        let store = RuleStore::in_memory();
+        store.upsert(vec![rule("a", "alpha")]).unwrap();
+        store.delete(&["a".to_string()]).unwrap();
+        store.upsert(vec![rule("a", "alpha two")]).unwrap();
+
+        let active = store.active_rules();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].trigger, "alpha two");
+        assert!(!store.changes_since(2).changes[0].deleted);
+    }
+
+    #[test]
+    fn test_bound_engine_follows_store() {
+        let store = RuleStore::in_memory();
+        let engine = Arc::new(RuleEngine::new(Vec::new()));
+        store.bind_engine(Arc::clone(&engine));
+
+        store.seed_if_empty(default_rules(0)).unwrap();
+        assert_eq!(engine.apply("slash new"), "/new");
+
+        store.delete(&["builtin/slash-new-en".to_string()]).unwrap();
+        assert_eq!(engine.apply("slash new"), "slash new");
+    }
+
+    #[test]
+    fn test_persists_and_reloads() {
+        let path = std::env::temp_dir().join(format!("ptt-rules-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        {
+            let store = RuleStore::open(&path).unwrap();
+            store.upsert(vec![rule("a", "alpha")]).unwrap();
+            store.delete(&["a".to_string()]).unwrap();
+        }
+
+        let reopened = RuleStore::open(&path).unwrap();
+        assert_eq!(reopened.version(), 2);
+        assert_eq!(reopened.count_rules(), 0);
+        assert!(reopened.changes_since(0).changes[0].deleted);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_deleting_unknown_ids_keeps_version() {
+        let store = RuleStore::in_memory();
+        store.upsert(vec![rule("a", "alpha")]).unwrap();
+        store.delete(&["a".to_string()]).unwrap();
+
+        assert_eq!(store.delete(&["missing".to_string()]).unwrap(), 2);
+        assert_eq!(store.delete(&["a".to_string()]).unwrap(), 2);
+        assert_eq!(store.version(), 2);
+    }
+
+    #[test]
+    fn test_failed_save_changes_nothing() {
+        let path = std::env::temp_dir().join(format!("ptt-rules-fail-{}.json", std::process::id()));
+        let tmp = path.with_extension("json.tmp");
+        let _ = std::fs::remove_file(&path);
+        let store = RuleStore::open(&path).unwrap();
+        store.upsert(vec![rule("a", "alpha")]).unwrap();
+        let engine = Arc::new(RuleEngine::new(Vec::new()));
+        store.bind_engine(Arc::clone(&engine));
+
+        // A directory where the temp file goes makes the write fail
+        std::fs::create_dir_all(&tmp).unwrap();
+        assert!(store.upsert(vec![rule("b", "beta")]).is_err());
+        assert!(store.delete(&["a".to_string()]).is_err());
+
+        assert_eq!(store.version(), 1);
+        assert_eq!(store.count_rules(), 1);
+        assert_eq!(engine.apply("alpha beta"), "<alpha> beta");
+
+        std::fs::remove_dir(&tmp).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_replace_all_tombstones_missing_rules() {
+        let store = RuleStore::in_memory();
+        store
+            .upsert(vec![rule("a", "alpha"), rule("b", "beta")])
+            .unwrap();
+
+        assert_eq!(store.replace_all(vec![rule("c", "gamma")]).unwrap(), 2);
+
+        let active: Vec<String> = store.active_rules().into_iter().map(|r| r.id).collect();
+        assert_eq!(active, vec!["c"]);
+        let delta = store.changes_since(1);
+        assert_eq!(delta.changes.len(), 3);
+        assert!(delta
+            .changes
+            .iter()
+            .filter(|c| c.id != "c")
+            .all(|c| c.deleted));
+    }
+
+    #[test]
+    fn test_delta_serializes_like_android() {
+        let store = RuleStore::in_memory();
+        store.upsert(vec![rule("a", "alpha")]).unwrap();
+
+        let json = serde_json::to_value(store.changes_since(0)).unwrap();
+        assert_eq!(json["rulesetVersion"], 1);
+        assert_eq!(json["changes"][0]["updatedAt"], 1);
+        assert_eq!(json["changes"][0]["deleted"], false);
+    }
+}
@@ -0,0 +1,255 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::rule_store::{RuleStore, RuleVersionResponse};
+
+/// Requests larger than this are rejected — the sync API only takes GETs
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// A phone sends its whole request at once; slower peers are cut off
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// HTTP status plus JSON body
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn json<T: serde::Serialize>(value: &T) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self { status: 200, body },
+            Err(e) => Self::error(500, &format!("Encode response: {e}")),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// Route one request of the Android `RuleSyncApi`:
+/// `GET /v1/rules/version` and `GET /v1/rules/changes?sinceVersion=N`.
+pub fn route(method: &str, target: &str, store: &RuleStore) -> Response {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    match (method, path) {
+        ("GET", "/v1/rules/version") => Response::json(&RuleVersionResponse {
+            ruleset_version: store.version(),
+        }),
+        ("GET", "/v1/rules/changes") => {
+            match query_param(query, "sinceVersion").map(str::parse::<i64>) {
+                Some(Ok(since)) => Response::json(&store.changes_since(since)),
+                Some(Err(_)) => Response::error(400, "sinceVersion must be an integer"),
+                None => Response::error(400, "sinceVersion is required"),
+            }
+        }
+        (_, "/v1/rules/version") | (_, "/v1/rules/changes") => {
+            Response::error(405, "Method not allowed")
+        }
+        _ => Response::error(404, "Not found"),
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Read up to the end of the request head and route it; None when the peer
+/// closed without sending one.
+async fn read_request(
+    stream: &mut TcpStream,
+    store: &RuleStore,
+) -> Result<Option<Response>, String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    loop {
+        let n = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| format!("Read request: {e}"))?;
+        if n == 0 {
+            return Ok(None);
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if buf.windows(4).any(|w| w == b"\r\n\r\n") {
+            let head = String::from_utf8_lossy(&buf);
+            let mut parts = head.lines().next().unwrap_or("").split_whitespace();
+            return Ok(Some(match (parts.next(), parts.next()) {
+                (Some(method), Some(target)) => route(method, target, store),
+                _ => Response::error(400, "Malformed request line"),
+            }));
+        }
+        if buf.len() > MAX_REQUEST_BYTES {
+            return Ok(Some(Response::error(413, "Request too large")));
+        }
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    store: &RuleStore,
+    timeout: Duration,
+) -> Result<(), String> {
+    let response = match tokio::time::timeout(timeout, read_request(&mut stream, store)).await {
+        Ok(result) => match result? {
+            Some(response) => response,
+            None => return Ok(()),
+        },
+        Err(_) => Response::error(408, "Request timed out"),
+    };
+
+    let raw = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason(response.status),
+        response.body.len(),
+        response.body
+    );
+    stream
+        .write_all(raw.as_bytes())
+        .await
+        .map_err(|e| format!("Write response: {e}"))
+}
+
+/// Serve the rule sync API so paired phones can pull rule changes.
+pub async fn start_rule_sync_server(port: u16, store: Arc<RuleStore>) -> Result<(), String> {
+    let addr = format!("0.0.0.0:{}", port);
+    let listener = TcpListener::bind(&addr)
+        .await
+        .map_err(|e| format!("Failed to bind to {}: {}", addr, e))?;
+
+    log::info!("Rule sync server listening on {}", addr);
+
+    loop {
+        let (stream, _peer) = listener
+            .accept()
+            .await
+            .map_err(|e| format!("Accept failed: {}", e))?;
+
+        let store = Arc::clone(&store);
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &store, REQUEST_TIMEOUT).await {
+                log::warn!("Rule sync request failed: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rule_store::RuleDeltaResponse;
+    use crate::rules::default_rules;
+
+    fn seeded_store() -> RuleStore {
+        let store = RuleStore::in_memory();
+        store
+            .seed_if_empty(default_rules(1_700_000_000_000))
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn test_route_version() {
+        let store = seeded_store();
+        let response = route("GET", "/v1/rules/version", &store);
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, r#"{"rulesetVersion":1}"#);
+    }
+
+    #[test]
+    fn test_route_changes() {
+        let store = seeded_store();
+        store.delete(&["builtin/slash-new-ko".to_string()]).unwrap();
+
+        let response = route("GET", "/v1/rules/changes?sinceVersion=1", &store);
+        assert_eq!(response.status, 200);
+        let delta: RuleDeltaResponse = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(delta.ruleset_version, 2);
+        assert_eq!(delta.changes.len(), 1);
+        assert!(delta.changes[0].deleted);
+    }
+
+    #[test]
+    fn test_route_errors() {
+        let store = seeded_store();
+        assert_eq!(route("GET", "/v1/rules/changes", &store).status, 400);
+        assert_eq!(
+            route("GET", "/v1/rules/changes?sinceVersion=abc", &store).status,
+            400
+        );
+        assert_eq!(route("POST", "/v1/rules/version", &store).status, 405);
+        assert_eq!(route("GET", "/v1/other", &store).status, 404);
+    }
+
+    #[tokio::test]
+    async fn test_serves_over_http() {
+        let port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        tokio::spawn(start_rule_sync_server(port, Arc::new(seeded_store())));
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        stream
+            .write_all(b"GET /v1/rules/changes?sinceVersion=0 HTTP/1.1\r\nHost: localhost\r\nAccept: application/json\r\n\r\n")
+            .await
+            .unwrap();
+        let mut raw = String::new();
+        stream.read_to_string(&mut raw).await.unwrap();
+
+        assert!(raw.starts_with("HTTP/1.1 200 OK\r\n"));
+        let body = raw.split("\r\n\r\n").nth(1).unwrap();
+        let delta: RuleDeltaResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(delta.ruleset_version, 1);
+        assert_eq!(delta.changes.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stalled_request_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut client = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        // The request head never ends
+        client
+            .write_all(b"GET /v1/rules/version HTTP/1.1\r\n")
+            .await
+            .unwrap();
+
+        let store = seeded_store();
+        let served = handle_connection(stream, &store, Duration::from_millis(100));
+        tokio::time::timeout(Duration::from_secs(2), served)
+            .await
+            .expect("connection must not hang")
+            .unwrap();
+
+        let mut raw = String::new();
+        client.read_to_string(&mut raw).await.unwrap();
+        assert!(raw.starts_with("HTTP/1.1 408 Request Timeout\r\n"));
+    }
+}
@@ -0,0 +1,304 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::pipeline::{TextKind, TextStage};
+
+fn any_locale() -> String {
+    "*".to_string()
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_priority() -> i32 {
+    100
+}
+
+/// Trigger → replacement rule, field-compatible with the Android `TextRule`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextRule {
+    pub id: String,
+    pub category: String,
+    pub trigger: String,
+    pub replacement: String,
+    #[serde(default = "any_locale")]
+    pub locale: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_priority")]
+    pub priority: i32,
+    pub updated_at: i64,
+}
+
+impl TextRule {
+    /// `*` matches every locale; otherwise compared case-insensitively (`ko-KR`).
+    pub fn matches_locale(&self, locale: &str) -> bool {
+        self.locale == "*" || self.locale.eq_ignore_ascii_case(locale)
+    }
+}
+
+/// Same built-in rules the Android app seeds its store with
+pub fn default_rules(now_ms: i64) -> Vec<TextRule> {
+    vec![
+        TextRule {
+            id: "builtin/slash-new-ko".to_string(),
+            category: "slash-command".to_string(),
+            trigger: "슬래시 뉴".to_string(),
+            replacement: "/new".to_string(),
+            locale: "ko-KR".to_string(),
+            enabled: true,
+            priority: 1_000,
+            updated_at: now_ms,
+        },
+        TextRule {
+            id: "builtin/slash-new-en".to_string(),
+            category: "slash-command".to_string(),
+            trigger: "slash new".to_string(),
+            replacement: "/new".to_string(),
+            locale: "en-US".to_string(),
+            enabled: true,
+            priority: 1_000,
+            updated_at: now_ms,
+        },
+    ]
+}
+
+/// Java's `\p{Punct}` is ASCII punctuation only; keep the same boundary set.
+pub(crate) fn is_boundary(c: char) -> bool {
+    c.is_whitespace() || c.is_ascii_punctuation()
+}
+
+fn chars_eq_ignore_case(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Match whitespace-separated trigger tokens starting at `start`.
+/// Returns the end index (exclusive) of the match.
+pub(crate) fn match_tokens(chars: &[char], start: usize, tokens: &[Vec<char>]) -> Option<usize> {
+    let mut pos = start;
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 {
+            let gap_start = pos;
+            while pos < chars.len() && chars[pos].is_whitespace() {
+                pos += 1;
+            }
+            if pos == gap_start {
+                return None;
+            }
+        }
+        for &expected in token {
+            match chars.get(pos) {
+                Some(&c) if chars_eq_ignore_case(c, expected) => pos += 1,
+                _ => return None,
+            }
+        }
+    }
+    Some(pos)
+}
+
+/// Replace every whole-word occurrence of `trigger`, keeping the surrounding
+/// separators. Mirrors the Android boundary regex
+/// `(?i)(^|[\s\p{Punct}])(body)(?=$|[\s\p{Punct}])`.
+fn replace_trigger(input: &str, trigger: &str, replacement: &str) -> String {
+    let tokens: Vec<Vec<char>> = trigger
+        .split_whitespace()
+        .map(|t| t.chars().collect())
+        .collect();
+    if tokens.is_empty() {
+        return input.to_string();
+    }
+
+    let chars: Vec<char> = input.chars().collect();
+    let mut output = String::with_capacity(input.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if i == 0 || is_boundary(chars[i - 1]) {
+            if let Some(end) = match_tokens(&chars, i, &tokens) {
+                if end == chars.len() || is_boundary(chars[end]) {
+                    output.push_str(replacement);
+                    i = end;
+                    continue;
+                }
+            }
+        }
+        output.push(chars[i]);
+        i += 1;
+    }
+    output
+}
+
+/// Apply enabled rules by priority (desc), trigger length (desc), then id —
+/// the same ordering and boundary semantics as the Android `RuleEngine`.
+pub fn apply_rules(input: &str, rules: &[TextRule]) -> String {
+    if input.trim().is_empty() || rules.is_empty() {
+        return input.to_string();
+    }
+
+    let mut ordered: Vec<&TextRule> = rules.iter().filter(|r| r.enabled).collect();
+    ordered.sort_by(|a, b| {
+        b.priority
+            .cmp(&a.priority)
+            .then_with(|| b.trigger.chars().count().cmp(&a.trigger.chars().count()))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    ordered.into_iter().fold(input.to_string(), |text, rule| {
+        replace_trigger(&text, &rule.trigger, &rule.replacement)
+    })
+}
+
+/// Pipeline stage holding the active rule set
+pub struct RuleEngine {
+    rules: RwLock<Vec<TextRule>>,
+    /// When set, only rules for this locale (or `*`) apply; None applies all
+    locale: RwLock<Option<String>>,
+    apply_to_partials: AtomicBool,
+}
+
+impl RuleEngine {
+    pub fn new(rules: Vec<TextRule>) -> Self {
+        Self {
+            rules: RwLock::new(rules),
+            locale: RwLock::new(None),
+            apply_to_partials: AtomicBool::new(false),
+        }
+    }
+
+    pub fn rules(&self) -> Vec<TextRule> {
+        self.rules.read().unwrap().clone()
+    }
+
+    pub fn set_rules(&self, rules: Vec<TextRule>) {
+        *self.rules.write().unwrap() = rules;
+    }
+
+    pub fn set_locale(&self, locale: Option<String>) {
+        *self.locale.write().unwrap() = locale;
+    }
+
+    pub fn set_apply_to_partials(&self, enabled: bool) {
+        self.apply_to_partials.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let rules = self.rules.read().unwrap();
+        match self.locale.read().unwrap().as_deref() {
+            None => apply_rules(text, &rules),
+            Some(locale) => {
+                let active: Vec<TextRule> = rules
+                    .iter()
+                    .filter(|r| r.matches_locale(locale))
+                    .cloned()
+                    .collect();
+                apply_rules(text, &active)
+            }
+        }
+    }
+}
+
+impl TextStage for RuleEngine {
+    fn name(&self) -> &str {
+        "rules"
+    }
+
+    fn process(&self, text: &str, kind: TextKind) -> String {
+        if kind == TextKind::Partial && !self.apply_to_partials.load(Ordering::SeqCst) {
+            return text.to_string();
+        }
+        self.apply(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: &str, trigger: &str, replacement: &str, priority: i32) -> TextRule {
+        TextRule {
+            id: id.to_string(),
+            category: "slash-command".to_string(),
+            trigger: trigger.to_string(),
+            replacement: replacement.to_string(),
+            locale: "*".to_string(),
+            enabled: true,
+            priority,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_replaces_korean_slash_command_trigger() {
+        let rules = vec![rule("slash-new-ko", "슬래시 뉴", "/new", 1000)];
+        assert_eq!(apply_rules("슬래시 뉴 프로젝트", &rules), "/new 프로젝트");
+    }
+
+    #[test]
+    fn test_applies_higher_priority_rule_first() {
+        let rules = vec![
+            rule("generic", "slash new", "/generic", 100),
+            rule("specific", "slash new project", "/new-project", 900),
+        ];
+        assert_eq!(
+            apply_rules("slash new project now", &rules),
+            "/new-project now"
+        );
+    }
+
+    #[test]
+    fn test_does_not_replace_inside_larger_token() {
+        let rules = vec![rule("new", "new", "/new", 500)];
+        assert_eq!(apply_rules("renew this draft", &rules), "renew this draft");
+    }
+
+    #[test]
+    fn test_case_insensitive_and_flexible_whitespace() {
+        let rules = vec![rule("slash-new-en", "slash new", "/new", 1000)];
+        assert_eq!(apply_rules("Slash   NEW, please", &rules), "/new, please");
+    }
+
+    #[test]
+    fn test_keeps_punctuation_boundaries() {
+        let rules = vec![rule("new", "new", "/new", 500)];
+        assert_eq!(apply_rules("(new) new.", &rules), "(/new) /new.");
+    }
+
+    #[test]
+    fn test_disabled_rules_are_skipped() {
+        let mut disabled = rule("new", "new", "/new", 500);
+        disabled.enabled = false;
+        assert_eq!(apply_rules("new", &[disabled]), "new");
+    }
+
+    #[test]
+    fn test_android_json_is_accepted() {
+        let json = r#"{"id":"r1","category":"c","trigger":"t","replacement":"r","updatedAt":5}"#;
+        let parsed: TextRule = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.locale, "*");
+        assert!(parsed.enabled);
+        assert_eq!(parsed.priority, 100);
+        assert_eq!(parsed.updated_at, 5);
+    }
+
+    #[test]
+    fn test_engine_filters_by_locale() {
+        let engine = RuleEngine::new(default_rules(0));
+        assert_eq!(engine.apply("slash new 슬래시 뉴"), "/new /new");
+
+        engine.set_locale(Some("en-US".to_string()));
+        assert_eq!(engine.apply("slash new 슬래시 뉴"), "/new 슬래시 뉴");
+    }
+
+    #[test]
+    fn test_stage_skips_partials_unless_enabled() {
+        let engine = RuleEngine::new(default_rules(0));
+        assert_eq!(engine.process("slash new", TextKind::Partial), "slash new");
+        assert_eq!(engine.process("slash new", TextKind::Final), "/new");
+
+        engine.set_apply_to_partials(true);
+        assert_eq!(engine.process("slash new", TextKind::Partial), "/new");
+    }
+}
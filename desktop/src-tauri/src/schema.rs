@@ -0,0 +1,387 @@
+//! JSON Schema for the wire protocol, generated from the serde types in
+//! `protocol`, and what is derived from it: the TypeScript types for the
+//! frontend and field-level validation of incoming messages.
+//!
+//! The generated files are checked in; `cargo run --bin ptt-schema` rewrites
+//! them and a test fails when they no longer match `protocol.rs`.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use serde_json::Value;
+
+use crate::protocol::Message;
+
+/// Checked-in generated files, relative to `src-tauri`
+pub const SCHEMA_FILE: &str = "../../docs/protocol.schema.json";
+pub const TYPESCRIPT_FILE: &str = "../src/types/protocol.generated.ts";
+
+/// The protocol's JSON Schema (draft 2020-12)
+pub fn protocol_schema() -> &'static Value {
+    static SCHEMA: OnceLock<Value> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        serde_json::to_value(schemars::schema_for!(Message)).expect("schema serializes")
+    })
+}
+
+/// Every generated file with its expected content
+pub fn generated_files() -> Vec<(PathBuf, String)> {
+    let schema = serde_json::to_string_pretty(protocol_schema()).expect("schema serializes");
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    vec![
+        (root.join(SCHEMA_FILE), schema + "\n"),
+        (root.join(TYPESCRIPT_FILE), typescript()),
+    ]
+}
+
+// --- TypeScript ---
+
+/// TypeScript declarations for every message and payload
+pub fn typescript() -> String {
+    let schema = protocol_schema();
+    let mut out = String::from(
+        "// Generated from src-tauri/src/protocol.rs by `cargo run --bin ptt-schema`.\n\
+         // Do not edit; change the Rust types and regenerate.\n",
+    );
+
+    if let Some(defs) = schema["$defs"].as_object() {
+        for (name, def) in defs {
+            out.push('\n');
+            write_interface(&mut out, name, def);
+        }
+    }
+
+    let mut names = Vec::new();
+    for variant in schema["oneOf"].as_array().into_iter().flatten() {
+        let Some(tag) = variant["properties"]["type"]["const"].as_str() else {
+            continue;
+        };
+        let name = format!("{}Message", pascal_case(tag));
+        out.push('\n');
+        write_interface(&mut out, &name, variant);
+        names.push(name);
+    }
+
+    out.push_str("\nexport type ProtocolMessage =\n");
+    for (i, name) in names.iter().enumerate() {
+        let end = if i + 1 == names.len() { ";" } else { "" };
+        out.push_str(&format!("  | {name}{end}\n"));
+    }
+    out
+}
+
+fn write_interface(out: &mut String, name: &str, schema: &Value) {
+    write_comment(out, "", schema);
+    out.push_str(&format!("export interface {name} {{\n"));
+    let required: Vec<&str> = schema["required"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    let Some(properties) = schema["properties"].as_object() else {
+        out.push_str("}\n");
+        return;
+    };
+    // Declaration order for required fields, optional ones after
+    let mut keys: Vec<&str> = required.clone();
+    keys.extend(
+        properties
+            .keys()
+            .map(String::as_str)
+            .filter(|k| !required.contains(k)),
+    );
+    for key in keys {
+        let Some(property) = properties.get(key) else {
+            continue;
+        };
+        write_comment(out, "  ", property);
+        let optional = if required.contains(&key) { "" } else { "?" };
+        out.push_str(&format!("  {key}{optional}: {};\n", ts_type(property)));
+    }
+    out.push_str("}\n");
+}
+
+/// The Rust doc comment, one `//` line per line
+fn write_comment(out: &mut String, indent: &str, schema: &Value) {
+    if let Some(description) = schema["description"].as_str() {
+        for line in description.lines() {
+            out.push_str(&format!("{indent}// {line}\n"));
+        }
+    }
+}
+
+fn ts_type(schema: &Value) -> String {
+    if let Some(reference) = schema["$ref"].as_str() {
+        return reference
+            .rsplit('/')
+            .next()
+            .unwrap_or("unknown")
+            .to_string();
+    }
+    if let Some(constant) = schema.get("const") {
+        return constant.to_string();
+    }
+    let types: Vec<&str> = match &schema["type"] {
+        Value::String(t) => vec![t.as_str()],
+        Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => return "unknown".to_string(),
+    };
+    types
+        .iter()
+        .map(|t| match *t {
+            "string" => "string".to_string(),
+            "integer" | "number" => "number".to_string(),
+            "boolean" => "boolean".to_string(),
+            "null" => "null".to_string(),
+            "array" => format!("{}[]", ts_type(&schema["items"])),
+            _ => "Record<string, unknown>".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// "CONFIRM_HELD" → "ConfirmHeld"
+fn pascal_case(tag: &str) -> String {
+    tag.split('_')
+        .map(|word| {
+            let lower = word.to_lowercase();
+            let mut chars = lower.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+// --- Validation ---
+
+/// The first field of a message that does not match the schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    /// Dotted path such as `payload.confidence`; empty for the message itself
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Checks a decoded message against the schema, naming the offending field
+pub fn validate(value: &Value) -> Result<(), FieldError> {
+    let schema = protocol_schema();
+    check(value, schema, &schema["$defs"], "")
+}
+
+fn error(path: &str, message: String) -> FieldError {
+    FieldError {
+        path: path.to_string(),
+        message,
+    }
+}
+
+fn child(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn check(value: &Value, schema: &Value, defs: &Value, path: &str) -> Result<(), FieldError> {
+    if let Some(reference) = schema["$ref"].as_str() {
+        let name = reference.rsplit('/').next().unwrap_or_default();
+        return check(value, &defs[name], defs, path);
+    }
+    if let Some(variants) = schema["oneOf"].as_array() {
+        return check_variant(value, variants, defs, path);
+    }
+
+    if let Some(constant) = schema.get("const") {
+        if value != constant {
+            return Err(error(path, format!("expected {constant}, got {value}")));
+        }
+    }
+    let types: Vec<&str> = match &schema["type"] {
+        Value::String(t) => vec![t.as_str()],
+        Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    let actual = kind(value);
+    let matches = |t: &&str| *t == actual || (*t == "number" && actual == "integer");
+    if !types.is_empty() && !types.iter().any(matches) {
+        return Err(error(
+            path,
+            format!("expected {}, got {actual}", types.join(" or ")),
+        ));
+    }
+    if let (Some(minimum), Some(number)) = (schema["minimum"].as_f64(), value.as_f64()) {
+        if number < minimum {
+            return Err(error(path, format!("must be at least {minimum}")));
+        }
+    }
+
+    if let Value::Object(object) = value {
+        for key in schema["required"].as_array().into_iter().flatten() {
+            let key = key.as_str().unwrap_or_default();
+            if !object.contains_key(key) {
+                return Err(error(&child(path, key), "missing".to_string()));
+            }
+        }
+        if let Some(properties) = schema["properties"].as_object() {
+            for (key, property) in properties {
+                if let Some(field) = object.get(key) {
+                    check(field, property, defs, &child(path, key))?;
+                }
+            }
+        }
+    }
+    if let Value::Array(items) = value {
+        if let Some(item_schema) = schema.get("items") {
+            for (i, item) in items.iter().enumerate() {
+                check(item, item_schema, defs, &format!("{path}[{i}]"))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Picks the variant by its `type` tag so errors point into the right one
+fn check_variant(
+    value: &Value,
+    variants: &[Value],
+    defs: &Value,
+    path: &str,
+) -> Result<(), FieldError> {
+    let Value::Object(object) = value else {
+        return Err(error(path, format!("expected object, got {}", kind(value))));
+    };
+    let tag_path = child(path, "type");
+    let tag = match object.get("type") {
+        None => return Err(error(&tag_path, "missing".to_string())),
+        Some(Value::String(tag)) => tag,
+        Some(other) => {
+            return Err(error(
+                &tag_path,
+                format!("expected string, got {}", kind(other)),
+            ))
+        }
+    };
+    let variant = variants
+        .iter()
+        .find(|v| v["properties"]["type"]["const"].as_str() == Some(tag.as_str()))
+        .ok_or_else(|| error(&tag_path, format!("unknown message type \"{tag}\"")))?;
+    check(value, variant, defs, path)
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn validate_str(json: &str) -> Result<(), String> {
+        let value: Value = serde_json::from_str(json).unwrap();
+        validate(&value).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_generated_files_are_current() {
+        for (path, expected) in generated_files() {
+            let actual = std::fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("Read {}: {e}", path.display()));
+            assert!(
+                actual == expected,
+                "{} is out of date; run `cargo run --bin ptt-schema`",
+                path.display()
+            );
+        }
+    }
+
+    #[test]
+    fn test_valid_messages_pass() {
+        validate_str(r#"{"type":"HELLO","clientId":"p","payload":{"deviceModel":"Pixel","engine":"Google","capabilities":["WS"]}}"#).unwrap();
+        validate_str(r#"{"type":"FINAL","clientId":"p","timestamp":5,"payload":{"sessionId":"s","text":"hi","confidence":1}}"#).unwrap();
+        validate_str(
+            r#"{"type":"ACK","clientId":"p","payload":{"ackType":"FINAL","status":null}}"#,
+        )
+        .unwrap();
+        validate_str(r#"{"type":"HEARTBEAT","clientId":"p"}"#).unwrap();
+    }
+
+    #[test]
+    fn test_reports_wrong_field_type() {
+        assert_eq!(
+            validate_str(
+                r#"{"type":"FINAL","clientId":"p","timestamp":5,"payload":{"sessionId":"s","text":"hi","confidence":"high"}}"#
+            ),
+            Err("payload.confidence: expected number, got string".to_string())
+        );
+        assert_eq!(
+            validate_str(
+                r#"{"type":"PARTIAL","clientId":"p","timestamp":-1,"payload":{"sessionId":"s","seq":1,"text":"","confidence":0.5}}"#
+            ),
+            Err("timestamp: must be at least 0".to_string())
+        );
+        assert_eq!(
+            validate_str(
+                r#"{"type":"HELLO","clientId":"p","payload":{"deviceModel":"Pixel","engine":"Google","capabilities":["WS",3]}}"#
+            ),
+            Err("payload.capabilities[1]: expected string, got integer".to_string())
+        );
+    }
+
+    #[test]
+    fn test_reports_missing_field() {
+        assert_eq!(
+            validate_str(r#"{"type":"PTT_START","clientId":"p","payload":{}}"#),
+            Err("payload.sessionId: missing".to_string())
+        );
+        assert_eq!(
+            validate_str(r#"{"clientId":"p"}"#),
+            Err("type: missing".to_string())
+        );
+    }
+
+    #[test]
+    fn test_reports_unknown_type_and_non_object() {
+        assert_eq!(
+            validate_str(r#"{"type":"GOODBYE","clientId":"p"}"#),
+            Err("type: unknown message type \"GOODBYE\"".to_string())
+        );
+        assert_eq!(
+            validate(&json!([1, 2])).unwrap_err().to_string(),
+            "expected object, got array"
+        );
+    }
+
+    #[test]
+    fn test_typescript_covers_every_message() {
+        let ts = typescript();
+        assert!(ts.contains("export interface ConfirmHeldMessage {\n  type: \"CONFIRM_HELD\";"));
+        assert!(ts.contains("  heldId?: number | null;\n"));
+        assert!(ts.contains("  | AckMessage;\n"));
+    }
+}
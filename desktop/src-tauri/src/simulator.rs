@@ -0,0 +1,473 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::stream::SplitSink;
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::protocol::{
+    self, AckPayload, FinalPayload, HelloPayload, Message, PartialPayload, PttStartPayload,
+};
+
+/// One line of a simulator script
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptStep {
+    /// Dictate this text: PTT_START, growing PARTIALs, FINAL
+    Say(String),
+    /// `@pause MS`
+    Pause(Duration),
+    /// `@disconnect`: drop the connection and reconnect
+    Disconnect,
+    /// `@undo`
+    Undo,
+}
+
+/// Parse a script: one utterance per line, `#` comments, and `@pause MS`,
+/// `@disconnect` and `@undo` directives.
+pub fn parse_script(source: &str) -> Result<Vec<ScriptStep>, String> {
+    let mut steps = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some(directive) = line.strip_prefix('@') else {
+            steps.push(ScriptStep::Say(line.to_string()));
+            continue;
+        };
+        let mut parts = directive.split_whitespace();
+        let step = match (parts.next(), parts.next()) {
+            (Some("pause"), Some(ms)) => ms
+                .parse()
+                .map(|ms| ScriptStep::Pause(Duration::from_millis(ms)))
+                .map_err(|e| format!("line {}: invalid pause {ms}: {e}", index + 1))?,
+            (Some("disconnect"), None) => ScriptStep::Disconnect,
+            (Some("undo"), None) => ScriptStep::Undo,
+            _ => return Err(format!("line {}: unknown directive {line}", index + 1)),
+        };
+        steps.push(step);
+    }
+    Ok(steps)
+}
+
+/// How the simulated phone behaves
+#[derive(Debug, Clone)]
+pub struct SimOptions {
+    pub client_id: String,
+    pub device_model: String,
+    pub engine: String,
+    /// Must not be zero
+    pub heartbeat: Duration,
+    /// Gap between PARTIALs (and before the FINAL)
+    pub partial_interval: Duration,
+    /// FINAL confidence; PARTIALs ramp up towards it
+    pub confidence: f64,
+    /// Probability that two neighbouring PARTIALs swap places
+    pub reorder: f64,
+    /// Probability that a PARTIAL is sent twice
+    pub duplicate: f64,
+    /// Probability that the connection drops mid-utterance
+    pub disconnect: f64,
+    pub seed: u64,
+}
+
+impl Default for SimOptions {
+    fn default() -> Self {
+        Self {
+            client_id: "sim-01".to_string(),
+            device_model: "ptt-sim".to_string(),
+            engine: "Simulated".to_string(),
+            heartbeat: Duration::from_secs(5),
+            partial_interval: Duration::from_millis(150),
+            confidence: 0.9,
+            reorder: 0.0,
+            duplicate: 0.0,
+            disconnect: 0.0,
+            seed: 1,
+        }
+    }
+}
+
+/// Small deterministic PRNG (xorshift64*) so fault runs can be repeated by seed
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    /// Uniform in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn chance(&mut self, probability: f64) -> bool {
+        probability > 0.0 && self.next_f64() < probability
+    }
+}
+
+/// Messages for one utterance, faults applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct Utterance {
+    pub messages: Vec<Message>,
+    /// The connection drops after `messages`; no FINAL was sent
+    pub interrupted: bool,
+}
+
+fn timestamp() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
+
+/// Build PTT_START, one PARTIAL per word prefix and the FINAL. Timestamps
+/// are filled in when the messages are sent.
+pub fn utterance(options: &SimOptions, session_id: &str, text: &str, rng: &mut Rng) -> Utterance {
+    let client_id = options.client_id.clone();
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut partials: Vec<Message> = (1..words.len())
+        .map(|count| {
+            let progress = count as f64 / words.len() as f64;
+            let confidence = (options.confidence * (0.5 + 0.5 * progress) * 100.0).round() / 100.0;
+            Message::Partial {
+                client_id: client_id.clone(),
+                timestamp: 0,
+                payload: PartialPayload {
+                    session_id: session_id.to_string(),
+                    seq: count as u64,
+                    text: words[..count].join(" "),
+                    confidence,
+                },
+            }
+        })
+        .collect();
+
+    for index in 1..partials.len() {
+        if rng.chance(options.reorder) {
+            partials.swap(index - 1, index);
+        }
+    }
+    let mut messages = vec![Message::PttStart {
+        client_id: client_id.clone(),
+        payload: PttStartPayload {
+            session_id: session_id.to_string(),
+        },
+    }];
+    for partial in partials {
+        if rng.chance(options.duplicate) {
+            messages.push(partial.clone());
+        }
+        messages.push(partial);
+    }
+
+    if rng.chance(options.disconnect) {
+        // Keep PTT_START and a random share of the PARTIALs
+        let keep = 1 + (rng.next_f64() * (messages.len() - 1) as f64) as usize;
+        messages.truncate(keep);
+        return Utterance {
+            messages,
+            interrupted: true,
+        };
+    }
+    messages.push(Message::Final {
+        client_id,
+        timestamp: 0,
+        payload: FinalPayload {
+            session_id: session_id.to_string(),
+            text: words.join(" "),
+            confidence: options.confidence,
+        },
+    });
+    Utterance {
+        messages,
+        interrupted: false,
+    }
+}
+
+/// A FINAL as it left the simulator
+#[derive(Debug, Clone)]
+pub struct SentFinal {
+    pub text: String,
+    pub at: Instant,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimReport {
+    pub partials: usize,
+    pub finals: Vec<SentFinal>,
+    pub interrupted: usize,
+    pub reconnects: usize,
+    /// ACKs received, in order
+    pub acks: Vec<AckPayload>,
+}
+
+type WsSink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, tungstenite::Message>;
+
+/// One live connection: the write half plus a task collecting ACKs
+struct Connection {
+    sink: WsSink,
+    reader: tokio::task::JoinHandle<()>,
+    last_heartbeat: Instant,
+}
+
+impl Connection {
+    async fn open(
+        url: &str,
+        options: &SimOptions,
+        acks: Arc<Mutex<Vec<AckPayload>>>,
+    ) -> Result<Self, String> {
+        let (ws, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .map_err(|e| format!("Connect {url}: {e}"))?;
+        let (sink, mut stream) = ws.split();
+        let reader = tokio::spawn(async move {
+            while let Some(Ok(frame)) = stream.next().await {
+                let tungstenite::Message::Text(json) = frame else {
+                    continue;
+                };
+                if let Ok(Message::Ack { payload, .. }) = protocol::parse_message(&json) {
+                    acks.lock().unwrap().push(payload);
+                }
+            }
+        });
+        let mut connection = Self {
+            sink,
+            reader,
+            last_heartbeat: Instant::now(),
+        };
+        connection
+            .send(Message::Hello {
+                client_id: options.client_id.clone(),
+                payload: HelloPayload {
+                    device_model: options.device_model.clone(),
+                    engine: options.engine.clone(),
+                    capabilities: vec!["WS".to_string()],
+                },
+            })
+            .await?;
+        Ok(connection)
+    }
+
+    async fn send(&mut self, mut message: Message) -> Result<(), String> {
+        if let Message::Partial { timestamp, .. } | Message::Final { timestamp, .. } = &mut message
+        {
+            *timestamp = self::timestamp();
+        }
+        let json = protocol::serialize_message(&message).map_err(|e| e.to_string())?;
+        self.sink
+            .send(tungstenite::Message::Text(json))
+            .await
+            .map_err(|e| format!("Send: {e}"))
+    }
+
+    /// Wait `duration`, sending HEARTBEATs when they fall due.
+    async fn pace(&mut self, duration: Duration, options: &SimOptions) -> Result<(), String> {
+        let until = Instant::now() + duration;
+        loop {
+            let next_heartbeat = self.last_heartbeat + options.heartbeat;
+            if next_heartbeat >= until {
+                tokio::time::sleep(until.saturating_duration_since(Instant::now())).await;
+                return Ok(());
+            }
+            tokio::time::sleep(next_heartbeat.saturating_duration_since(Instant::now())).await;
+            self.send(Message::Heartbeat {
+                client_id: options.client_id.clone(),
+            })
+            .await?;
+            self.last_heartbeat = Instant::now();
+        }
+    }
+
+    async fn close(mut self) {
+        let _ = self.sink.close().await;
+        // Let the reader drain ACKs already on the wire
+        let _ = tokio::time::timeout(Duration::from_millis(500), &mut self.reader).await;
+        self.reader.abort();
+    }
+}
+
+/// Play `script` against the server at `url` as one phone.
+pub async fn run(
+    url: &str,
+    script: &[ScriptStep],
+    options: &SimOptions,
+) -> Result<SimReport, String> {
+    if options.heartbeat.is_zero() {
+        return Err("Heartbeat interval must not be zero".to_string());
+    }
+    let acks = Arc::new(Mutex::new(Vec::new()));
+    let mut rng = Rng::new(options.seed);
+    let mut report = SimReport::default();
+    let mut connection = Connection::open(url, options, Arc::clone(&acks)).await?;
+    let mut sessions = 0;
+
+    for step in script {
+        match step {
+            ScriptStep::Say(text) => {
+                sessions += 1;
+                let session_id = format!("{}-s{sessions}", options.client_id);
+                let planned = utterance(options, &session_id, text, &mut rng);
+                for message in planned.messages {
+                    match &message {
+                        Message::Partial { .. } => report.partials += 1,
+                        Message::Final { payload, .. } => report.finals.push(SentFinal {
+                            text: payload.text.clone(),
+                            at: Instant::now(),
+                        }),
+                        _ => {}
+                    }
+                    let is_start = matches!(message, Message::PttStart { .. });
+                    connection.send(message).await?;
+                    if !is_start {
+                        connection.pace(options.partial_interval, options).await?;
+                    }
+                }
+                if planned.interrupted {
+                    report.interrupted += 1;
+                    connection.close().await;
+                    connection = Connection::open(url, options, Arc::clone(&acks)).await?;
+                    report.reconnects += 1;
+                }
+            }
+            ScriptStep::Pause(duration) => connection.pace(*duration, options).await?,
+            ScriptStep::Disconnect => {
+                connection.close().await;
+                connection = Connection::open(url, options, Arc::clone(&acks)).await?;
+                report.reconnects += 1;
+            }
+            ScriptStep::Undo => {
+                connection
+                    .send(Message::Undo {
+                        client_id: options.client_id.clone(),
+                    })
+                    .await?
+            }
+        }
+    }
+    connection.close().await;
+    report.acks = std::mem::take(&mut *acks.lock().unwrap());
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client_registry::ClientRegistry;
+    use crate::injection::testing::MockInjector;
+    use crate::ws_server::{self, LogEmitter, ServerContext};
+
+    fn partial_texts(utterance: &Utterance) -> Vec<String> {
+        utterance
+            .messages
+            .iter()
+            .filter_map(|m| match m {
+                Message::Partial { payload, .. } => Some(payload.text.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_script() {
+        let script = "# standup\nhello world\n\n@pause 250\n@disconnect\n@undo\n";
+        assert_eq!(
+            parse_script(script).unwrap(),
+            vec![
+                ScriptStep::Say("hello world".to_string()),
+                ScriptStep::Pause(Duration::from_millis(250)),
+                ScriptStep::Disconnect,
+                ScriptStep::Undo,
+            ]
+        );
+        assert!(parse_script("@pause soon").is_err());
+        assert!(parse_script("@explode").is_err());
+    }
+
+    #[test]
+    fn test_partials_grow_into_final() {
+        let planned = utterance(
+            &SimOptions::default(),
+            "s-1",
+            "one two three",
+            &mut Rng::new(1),
+        );
+        assert!(!planned.interrupted);
+        assert!(matches!(planned.messages[0], Message::PttStart { .. }));
+        assert_eq!(partial_texts(&planned), vec!["one", "one two"]);
+        let Some(Message::Final { payload, .. }) = planned.messages.last() else {
+            panic!("no FINAL");
+        };
+        assert_eq!(payload.text, "one two three");
+    }
+
+    #[test]
+    fn test_faults() {
+        let options = SimOptions {
+            reorder: 1.0,
+            duplicate: 1.0,
+            ..SimOptions::default()
+        };
+        let planned = utterance(&options, "s-1", "a b c d", &mut Rng::new(7));
+        // Every neighbour swap moves the first PARTIAL to the end
+        assert_eq!(
+            partial_texts(&planned),
+            vec!["a b", "a b", "a b c", "a b c", "a", "a"]
+        );
+
+        let options = SimOptions {
+            disconnect: 1.0,
+            ..SimOptions::default()
+        };
+        let planned = utterance(&options, "s-1", "a b c d", &mut Rng::new(7));
+        assert!(planned.interrupted);
+        assert!(!planned
+            .messages
+            .iter()
+            .any(|m| matches!(m, Message::Final { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_run_against_server() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let (injector, injected) = MockInjector::new();
+        let registry = Arc::new(tokio::sync::Mutex::new(ClientRegistry::new(30)));
+        let ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(LogEmitter));
+        tokio::spawn(ws_server::serve(port, Arc::new(ctx)));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let options = SimOptions {
+            partial_interval: Duration::from_millis(5),
+            heartbeat: Duration::from_millis(20),
+            ..SimOptions::default()
+        };
+        let script = parse_script("first note\n@disconnect\nsecond note here").unwrap();
+        let report = run(&format!("ws://127.0.0.1:{port}"), &script, &options)
+            .await
+            .unwrap();
+
+        assert_eq!(report.partials, 3);
+        assert_eq!(report.finals.len(), 2);
+        assert_eq!(report.reconnects, 1);
+        let final_acks = report.acks.iter().filter(|a| a.ack_type == "FINAL").count();
+        assert_eq!(final_acks, 2);
+        assert_eq!(injected.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_zero_heartbeat_is_rejected() {
+        let options = SimOptions {
+            heartbeat: Duration::ZERO,
+            ..SimOptions::default()
+        };
+        // Fails before connecting, so the URL is never dialled
+        let result = run("ws://127.0.0.1:1", &[], &options).await;
+        assert_eq!(result.unwrap_err(), "Heartbeat interval must not be zero");
+    }
+}
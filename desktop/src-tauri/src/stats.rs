@@ -0,0 +1,608 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::json_file;
+
+/// Daily buckets older than this are dropped when saving
+const MAX_DAYS: usize = 400;
+/// How often `flush_periodically` writes changed statistics to disk
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+/// Confidence histogram resolution: one bin per 0.01
+const CONFIDENCE_BINS: usize = 101;
+/// Upper edges (ms) of the PTT-to-FINAL latency histogram; the last bin is open-ended
+const LATENCY_EDGES_MS: [u64; 15] = [
+    100, 200, 300, 500, 750, 1_000, 1_500, 2_000, 3_000, 5_000, 7_500, 10_000, 15_000, 30_000,
+    60_000,
+];
+
+/// One FINAL as seen by `handle_message`
+#[derive(Debug, Clone, Copy)]
+pub struct FinalSample<'a> {
+    pub client_id: &'a str,
+    pub device_model: &'a str,
+    pub engine: &'a str,
+    pub text: &'a str,
+    pub confidence: f64,
+    pub injection_failed: bool,
+    pub at: Instant,
+}
+
+/// Counters for one client/engine on one day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DailyBucket {
+    /// Local date, YYYY-MM-DD
+    day: String,
+    client_id: String,
+    device_model: String,
+    engine: String,
+    /// Sessions that ended in a FINAL (files before `abandoned` called it `sessions`)
+    #[serde(alias = "sessions")]
+    finals: u64,
+    /// Sessions that ended without a FINAL
+    #[serde(default)]
+    abandoned: u64,
+    words: u64,
+    partials: u64,
+    injection_failures: u64,
+    confidence_sum: f64,
+    confidence_histogram: Vec<u64>,
+    latency_count: u64,
+    latency_sum_ms: u64,
+    latency_histogram: Vec<u64>,
+}
+
+impl DailyBucket {
+    fn new(day: &str, client_id: &str, device_model: &str, engine: &str) -> Self {
+        Self {
+            day: day.to_string(),
+            client_id: client_id.to_string(),
+            device_model: device_model.to_string(),
+            engine: engine.to_string(),
+            finals: 0,
+            abandoned: 0,
+            words: 0,
+            partials: 0,
+            injection_failures: 0,
+            confidence_sum: 0.0,
+            confidence_histogram: vec![0; CONFIDENCE_BINS],
+            latency_count: 0,
+            latency_sum_ms: 0,
+            latency_histogram: vec![0; LATENCY_EDGES_MS.len() + 1],
+        }
+    }
+
+    fn merge(&mut self, other: &DailyBucket) {
+        self.finals += other.finals;
+        self.abandoned += other.abandoned;
+        self.words += other.words;
+        self.partials += other.partials;
+        self.injection_failures += other.injection_failures;
+        self.confidence_sum += other.confidence_sum;
+        self.latency_count += other.latency_count;
+        self.latency_sum_ms += other.latency_sum_ms;
+        for (a, b) in self
+            .confidence_histogram
+            .iter_mut()
+            .zip(&other.confidence_histogram)
+        {
+            *a += b;
+        }
+        for (a, b) in self
+            .latency_histogram
+            .iter_mut()
+            .zip(&other.latency_histogram)
+        {
+            *a += b;
+        }
+    }
+}
+
+/// Smallest bin index holding the `p`-th percentile of `histogram`
+fn percentile_bin(histogram: &[u64], p: f64) -> Option<usize> {
+    let total: u64 = histogram.iter().sum();
+    if total == 0 {
+        return None;
+    }
+    let rank = ((total as f64) * p).ceil().max(1.0) as u64;
+    let mut seen = 0;
+    histogram.iter().position(|&count| {
+        seen += count;
+        seen >= rank
+    })
+}
+
+fn latency_bin(latency_ms: u64) -> usize {
+    LATENCY_EDGES_MS
+        .iter()
+        .position(|&edge| latency_ms <= edge)
+        .unwrap_or(LATENCY_EDGES_MS.len())
+}
+
+/// Latency percentile as the upper edge of its bin (the last bin reports its lower edge)
+fn latency_percentile(histogram: &[u64], p: f64) -> Option<u64> {
+    percentile_bin(histogram, p).map(|bin| {
+        LATENCY_EDGES_MS
+            .get(bin)
+            .copied()
+            .unwrap_or(LATENCY_EDGES_MS[LATENCY_EDGES_MS.len() - 1])
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupBy {
+    Client,
+    Engine,
+}
+
+/// Report request; day bounds are inclusive YYYY-MM-DD strings
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsQuery {
+    pub group_by: GroupBy,
+    #[serde(default)]
+    pub from_day: Option<String>,
+    #[serde(default)]
+    pub to_day: Option<String>,
+    /// One row per day and group (for charts) instead of totals per group
+    #[serde(default)]
+    pub per_day: bool,
+}
+
+/// Aggregated numbers for one group (and day, with `per_day`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsRow {
+    pub day: Option<String>,
+    /// Client id or engine name, depending on `GroupBy`
+    pub key: String,
+    /// Device models seen for the group
+    pub device_models: Vec<String>,
+    /// Every PTT_START, with or without a FINAL
+    pub sessions: u64,
+    /// Sessions the client dropped or restarted before sending a FINAL
+    pub abandoned: u64,
+    pub words: u64,
+    pub partials: u64,
+    pub injection_failures: u64,
+    pub avg_confidence: Option<f64>,
+    pub p50_confidence: Option<f64>,
+    pub p90_confidence: Option<f64>,
+    pub avg_latency_ms: Option<u64>,
+    pub p50_latency_ms: Option<u64>,
+    pub p90_latency_ms: Option<u64>,
+}
+
+impl StatsRow {
+    fn from_bucket(
+        day: Option<String>,
+        key: String,
+        devices: Vec<String>,
+        b: &DailyBucket,
+    ) -> Self {
+        let confidence = |p| percentile_bin(&b.confidence_histogram, p).map(|i| i as f64 / 100.0);
+        Self {
+            day,
+            key,
+            device_models: devices,
+            sessions: b.finals + b.abandoned,
+            abandoned: b.abandoned,
+            words: b.words,
+            partials: b.partials,
+            injection_failures: b.injection_failures,
+            avg_confidence: (b.finals > 0).then(|| b.confidence_sum / b.finals as f64),
+            p50_confidence: confidence(0.5),
+            p90_confidence: confidence(0.9),
+            avg_latency_ms: (b.latency_count > 0).then(|| b.latency_sum_ms / b.latency_count),
+            p50_latency_ms: latency_percentile(&b.latency_histogram, 0.5),
+            p90_latency_ms: latency_percentile(&b.latency_histogram, 0.9),
+        }
+    }
+}
+
+/// Session in progress: PTT_START time and PARTIALs so far
+struct OpenSession {
+    device_model: String,
+    engine: String,
+    started: Instant,
+    partials: u64,
+}
+
+#[derive(Default)]
+struct StatsState {
+    open: HashMap<String, OpenSession>,
+    buckets: Vec<DailyBucket>,
+    /// Changed since the last flush
+    dirty: bool,
+}
+
+impl StatsState {
+    fn bucket(
+        &mut self,
+        day: &str,
+        client_id: &str,
+        device_model: &str,
+        engine: &str,
+    ) -> &mut DailyBucket {
+        self.dirty = true;
+        let index = match self
+            .buckets
+            .iter()
+            .position(|b| b.day == day && b.client_id == client_id && b.engine == engine)
+        {
+            Some(index) => index,
+            None => {
+                self.buckets
+                    .push(DailyBucket::new(day, client_id, device_model, engine));
+                self.buckets.len() - 1
+            }
+        };
+        &mut self.buckets[index]
+    }
+
+    fn abandon(&mut self, client_id: &str, day: &str) {
+        if let Some(session) = self.open.remove(client_id) {
+            let bucket = self.bucket(day, client_id, &session.device_model, &session.engine);
+            bucket.abandoned += 1;
+            bucket.partials += session.partials;
+        }
+    }
+}
+
+/// Usage statistics per client and engine, aggregated into daily buckets
+/// persisted as JSON.
+///
+/// Recording only touches memory; `flush` (run every `FLUSH_INTERVAL` by
+/// `flush_periodically` and once on exit) writes the file.
+pub struct Statistics {
+    path: Option<PathBuf>,
+    state: Mutex<StatsState>,
+    /// Keeps two flushes from writing the file at the same time
+    writing: Mutex<()>,
+}
+
+impl Statistics {
+    /// Load statistics from `path`, starting empty if the file doesn't exist yet.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let buckets: Vec<DailyBucket> = json_file::load(&path)?;
+        Ok(Self {
+            path: Some(path),
+            state: Mutex::new(StatsState {
+                open: HashMap::new(),
+                buckets,
+                dirty: false,
+            }),
+            writing: Mutex::new(()),
+        })
+    }
+
+    /// Statistics kept only for the lifetime of the process
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            state: Mutex::new(StatsState::default()),
+            writing: Mutex::new(()),
+        }
+    }
+
+    /// PTT_START: start timing the client's session. A session the client
+    /// left open counts as abandoned on `day`.
+    pub fn session_started(
+        &self,
+        client_id: &str,
+        device_model: &str,
+        engine: &str,
+        at: Instant,
+        day: &str,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        state.abandon(client_id, day);
+        state.open.insert(
+            client_id.to_string(),
+            OpenSession {
+                device_model: device_model.to_string(),
+                engine: engine.to_string(),
+                started: at,
+                partials: 0,
+            },
+        );
+    }
+
+    pub fn partial(&self, client_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(session) = state.open.get_mut(client_id) {
+            session.partials += 1;
+        }
+    }
+
+    /// Count a FINAL into the bucket for `day`. Latency is only known when
+    /// the PTT_START was seen.
+    pub fn record_final(&self, sample: FinalSample, day: &str) {
+        let mut state = self.state.lock().unwrap();
+        let session = state.open.remove(sample.client_id);
+        let bucket = state.bucket(day, sample.client_id, sample.device_model, sample.engine);
+        let confidence = sample.confidence.clamp(0.0, 1.0);
+        bucket.finals += 1;
+        bucket.words += sample.text.split_whitespace().count() as u64;
+        bucket.confidence_sum += confidence;
+        bucket.confidence_histogram[(confidence * 100.0).round() as usize] += 1;
+        if sample.injection_failed {
+            bucket.injection_failures += 1;
+        }
+        if let Some(session) = session {
+            let latency_ms = sample.at.duration_since(session.started).as_millis() as u64;
+            bucket.partials += session.partials;
+            bucket.latency_count += 1;
+            bucket.latency_sum_ms += latency_ms;
+            bucket.latency_histogram[latency_bin(latency_ms)] += 1;
+        }
+    }
+
+    /// Client disconnected: its open session won't produce a FINAL and
+    /// counts as abandoned on `day`.
+    pub fn abandon(&self, client_id: &str, day: &str) {
+        self.state.lock().unwrap().abandon(client_id, day);
+    }
+
+    pub fn report(&self, query: &StatsQuery) -> Vec<StatsRow> {
+        let state = self.state.lock().unwrap();
+        let in_range = |day: &str| {
+            query.from_day.as_deref().is_none_or(|from| day >= from)
+                && query.to_day.as_deref().is_none_or(|to| day <= to)
+        };
+
+        let mut groups: Vec<(Option<String>, String, Vec<String>, DailyBucket)> = Vec::new();
+        for bucket in state.buckets.iter().filter(|b| in_range(&b.day)) {
+            let key = match query.group_by {
+                GroupBy::Client => &bucket.client_id,
+                GroupBy::Engine => &bucket.engine,
+            };
+            let day = query.per_day.then(|| bucket.day.clone());
+            match groups.iter_mut().find(|g| g.0 == day && &g.1 == key) {
+                Some((_, _, devices, total)) => {
+                    total.merge(bucket);
+                    if !devices.contains(&bucket.device_model) {
+                        devices.push(bucket.device_model.clone());
+                    }
+                }
+                None => groups.push((
+                    day,
+                    key.clone(),
+                    vec![bucket.device_model.clone()],
+                    bucket.clone(),
+                )),
+            }
+        }
+        groups.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+        groups
+            .into_iter()
+            .map(|(day, key, devices, total)| StatsRow::from_bucket(day, key, devices, &total))
+            .collect()
+    }
+
+    /// Write the buckets to disk if anything changed since the last flush.
+    /// Blocks on file I/O, so async code runs it via `spawn_blocking`.
+    pub fn flush(&self) -> Result<(), String> {
+        let _writing = self.writing.lock().unwrap();
+        let json = {
+            let mut state = self.state.lock().unwrap();
+            if !state.dirty {
+                return Ok(());
+            }
+            let mut days: Vec<&str> = state.buckets.iter().map(|b| b.day.as_str()).collect();
+            days.sort_unstable();
+            days.dedup();
+            if days.len() > MAX_DAYS {
+                let oldest_kept = days[days.len() - MAX_DAYS].to_string();
+                state.buckets.retain(|b| b.day >= oldest_kept);
+            }
+            state.dirty = false;
+            serde_json::to_string(&state.buckets).map_err(|e| format!("Encode stats: {e}"))?
+        };
+        self.save(&json).inspect_err(|_| {
+            // Try again on the next flush
+            self.state.lock().unwrap().dirty = true;
+        })
+    }
+
+    /// Flush every `every` until the runtime shuts down.
+    pub async fn flush_periodically(self: Arc<Self>, every: Duration) {
+        let mut ticks = tokio::time::interval(every);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticks.tick().await;
+            let stats = Arc::clone(&self);
+            match tokio::task::spawn_blocking(move || stats.flush()).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => log::warn!("Statistics: {}", e),
+                Err(e) => log::warn!("Statistics flush task: {}", e),
+            }
+        }
+    }
+
+    fn save(&self, json: &str) -> Result<(), String> {
+        match &self.path {
+            Some(path) => json_file::write(path, json),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn dictate(
+        stats: &Statistics,
+        client_id: &str,
+        engine: &str,
+        confidence: f64,
+        latency_ms: u64,
+        day: &str,
+    ) {
+        let start = Instant::now();
+        stats.session_started(client_id, "Pixel 8", engine, start, day);
+        stats.partial(client_id);
+        stats.partial(client_id);
+        let sample = FinalSample {
+            client_id,
+            device_model: "Pixel 8",
+            engine,
+            text: "three words here",
+            confidence,
+            injection_failed: confidence < 0.5,
+            at: start + Duration::from_millis(latency_ms),
+        };
+        stats.record_final(sample, day);
+    }
+
+    fn query(group_by: GroupBy) -> StatsQuery {
+        StatsQuery {
+            group_by,
+            from_day: None,
+            to_day: None,
+            per_day: false,
+        }
+    }
+
+    #[test]
+    fn test_totals_per_engine() {
+        let stats = Statistics::in_memory();
+        dictate(&stats, "phone-01", "Google", 0.9, 800, "2026-10-01");
+        dictate(&stats, "phone-02", "Google", 0.7, 1200, "2026-10-01");
+        dictate(&stats, "phone-01", "Whisper", 0.4, 2500, "2026-10-02");
+
+        let rows = stats.report(&query(GroupBy::Engine));
+
+        assert_eq!(rows.len(), 2);
+        let google = &rows[0];
+        assert_eq!(google.key, "Google");
+        assert_eq!(google.sessions, 2);
+        assert_eq!(google.words, 6);
+        assert_eq!(google.partials, 4);
+        assert_eq!(google.injection_failures, 0);
+        assert!((google.avg_confidence.unwrap() - 0.8).abs() < 1e-9);
+        assert_eq!(google.p50_confidence, Some(0.7));
+        assert_eq!(google.p90_confidence, Some(0.9));
+        assert_eq!(google.avg_latency_ms, Some(1000));
+        assert_eq!(google.p90_latency_ms, Some(1500));
+        assert_eq!(rows[1].injection_failures, 1);
+    }
+
+    #[test]
+    fn test_per_day_rows_and_range() {
+        let stats = Statistics::in_memory();
+        dictate(&stats, "phone-01", "Google", 0.9, 800, "2026-10-01");
+        dictate(&stats, "phone-01", "Google", 0.9, 800, "2026-10-02");
+        dictate(&stats, "phone-01", "Google", 0.9, 800, "2026-10-03");
+
+        let rows = stats.report(&StatsQuery {
+            from_day: Some("2026-10-02".to_string()),
+            per_day: true,
+            ..query(GroupBy::Client)
+        });
+
+        let days: Vec<_> = rows.iter().map(|r| r.day.clone().unwrap()).collect();
+        assert_eq!(days, vec!["2026-10-02", "2026-10-03"]);
+        assert!(rows.iter().all(|r| r.key == "phone-01" && r.sessions == 1));
+    }
+
+    #[test]
+    fn test_final_without_ptt_start_has_no_latency() {
+        let stats = Statistics::in_memory();
+        let sample = FinalSample {
+            client_id: "phone-01",
+            device_model: "Pixel 8",
+            engine: "Google",
+            text: "hi",
+            confidence: 0.8,
+            injection_failed: false,
+            at: Instant::now(),
+        };
+        stats.record_final(sample, "2026-10-01");
+
+        let row = &stats.report(&query(GroupBy::Client))[0];
+        assert_eq!(row.sessions, 1);
+        assert_eq!(row.avg_latency_ms, None);
+        assert_eq!(row.p50_latency_ms, None);
+    }
+
+    #[test]
+    fn test_stats_survive_reopen() {
+        let path = std::env::temp_dir().join(format!("ptt-stats-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let stats = Statistics::open(&path).unwrap();
+        dictate(&stats, "phone-01", "Google", 0.9, 800, "2026-10-01");
+        // Nothing is written until the flush
+        assert!(!path.exists());
+        stats.flush().unwrap();
+        drop(stats);
+
+        let reopened = Statistics::open(&path).unwrap();
+        assert_eq!(reopened.report(&query(GroupBy::Engine))[0].sessions, 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_files_from_before_abandoned_sessions_still_load() {
+        let path = std::env::temp_dir().join(format!("ptt-stats-old-{}.json", std::process::id()));
+        let mut bucket = serde_json::to_value(DailyBucket::new(
+            "2026-10-01",
+            "phone-01",
+            "Pixel 8",
+            "Google",
+        ))
+        .unwrap();
+        let map = bucket.as_object_mut().unwrap();
+        map.remove("finals");
+        map.remove("abandoned");
+        map.insert("sessions".to_string(), 3.into());
+        std::fs::write(&path, serde_json::json!([bucket]).to_string()).unwrap();
+
+        let stats = Statistics::open(&path).unwrap();
+        let row = &stats.report(&query(GroupBy::Client))[0];
+        assert_eq!(row.sessions, 3);
+        assert_eq!(row.abandoned, 0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_abandoned_sessions_are_counted() {
+        let stats = Statistics::in_memory();
+        let start = Instant::now();
+        stats.session_started("phone-01", "Pixel 8", "Google", start, "2026-10-01");
+        stats.partial("phone-01");
+        // Started over before a FINAL
+        stats.session_started("phone-01", "Pixel 8", "Google", start, "2026-10-01");
+        dictate(&stats, "phone-02", "Google", 0.8, 500, "2026-10-01");
+        stats.session_started("phone-02", "Pixel 7", "Google", start, "2026-10-01");
+        stats.abandon("phone-02", "2026-10-01");
+        // Nothing open any more
+        stats.abandon("phone-02", "2026-10-01");
+
+        let rows = stats.report(&query(GroupBy::Engine));
+        // phone-01's second session is still open
+        assert_eq!(rows[0].sessions, 3);
+        assert_eq!(rows[0].abandoned, 2);
+        assert_eq!(rows[0].partials, 3);
+        // Confidence only averages the FINALs
+        assert!((rows[0].avg_confidence.unwrap() - 0.8).abs() < 1e-9);
+    }
+}
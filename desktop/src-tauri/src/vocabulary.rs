@@ -0,0 +1,470 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::json_file;
+use crate::pipeline::{TextKind, TextStage};
+use crate::rules::is_boundary;
+
+/// Terms shorter than this (letters and digits only) are corrected through aliases only
+const MIN_FUZZY_LEN: usize = 4;
+/// Latin terms whose sound key has fewer consonants are corrected through aliases only
+const MIN_KEY_LEN: usize = 4;
+/// Longest window, in words, compared against a term
+const MAX_WINDOW_WORDS: usize = 4;
+
+/// Known term plus misrecognitions the user taught
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VocabularyEntry {
+    /// Spelling to inject ("Kubernetes", "Jiwoo Park")
+    pub term: String,
+    /// Exact misrecognitions corrected to `term` regardless of distance
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// Import/export file format
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VocabularyFile {
+    entries: Vec<VocabularyEntry>,
+}
+
+/// Lowercased letters and digits; spacing and punctuation don't count
+fn normalized(text: &str) -> Vec<char> {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Edits allowed for a term of `len` characters
+fn max_edits(len: usize) -> usize {
+    (len / 4).min(3)
+}
+
+/// Coarse English sound key: similar-sounding consonants share a code and
+/// vowels are dropped after the first letter. None for non-Latin text.
+fn phonetic_key(word: &[char]) -> Option<String> {
+    if word.is_empty() || !word.iter().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    let mut key = String::new();
+    let mut last = None;
+    for (i, &c) in word.iter().enumerate() {
+        let code = match c {
+            'a' | 'e' | 'i' | 'o' | 'u' | 'y' if i == 0 => Some('a'),
+            'a' | 'e' | 'i' | 'o' | 'u' | 'y' | 'h' | 'w' => None,
+            'c' | 'k' | 'q' | 'g' => Some('k'),
+            'x' => Some('k'),
+            's' | 'z' => Some('s'),
+            'f' | 'v' => Some('f'),
+            'd' | 't' => Some('t'),
+            'b' | 'p' => Some('p'),
+            'm' | 'n' => Some('n'),
+            other => Some(other),
+        };
+        // Vowels still separate repeated consonants ("kaka" → "kk")
+        if code != last {
+            key.extend(code);
+        }
+        last = code;
+    }
+    Some(key)
+}
+
+/// Byte range of one word in the text
+#[derive(Debug, Clone, Copy)]
+struct Word {
+    start: usize,
+    end: usize,
+}
+
+fn words(text: &str) -> Vec<Word> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (is_boundary(c), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                words.push(Word { start: s, end: i });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push(Word {
+            start: s,
+            end: text.len(),
+        });
+    }
+    words
+}
+
+struct Candidate<'a> {
+    term: &'a str,
+    words: usize,
+    score: usize,
+}
+
+/// How closely `heard` matches the entry; lower is better, None is no match.
+fn score(heard: &[char], entry: &VocabularyEntry) -> Option<usize> {
+    if entry.aliases.iter().any(|a| normalized(a) == heard) {
+        return Some(0);
+    }
+    let term = normalized(&entry.term);
+    if term.len() < MIN_FUZZY_LEN || heard.len().abs_diff(term.len()) > term.len() / 2 {
+        return None;
+    }
+    let distance = levenshtein(heard, &term);
+    if heard.first() != term.first() || distance > max_edits(term.len()) {
+        return None;
+    }
+    // A few edits turn many English words into each other ("black", "stack"
+    // -> "Slack"), so Latin terms must sound alike as well
+    match phonetic_key(&term) {
+        Some(key) => {
+            (key.len() >= MIN_KEY_LEN && phonetic_key(heard) == Some(key)).then_some(distance)
+        }
+        None => Some(distance),
+    }
+}
+
+/// Personal vocabulary persisted as JSON. Corrects FINALs toward known terms
+/// by learned aliases, or by a close spelling that also sounds alike.
+pub struct Vocabulary {
+    path: Option<PathBuf>,
+    enabled: AtomicBool,
+    entries: RwLock<Vec<VocabularyEntry>>,
+}
+
+impl Vocabulary {
+    /// Load the vocabulary from `path`, starting empty if the file doesn't exist yet.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let file: VocabularyFile = json_file::load(&path)?;
+        Ok(Self {
+            path: Some(path),
+            enabled: AtomicBool::new(true),
+            entries: RwLock::new(file.entries),
+        })
+    }
+
+    /// Empty vocabulary without a backing file
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            enabled: AtomicBool::new(true),
+            entries: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn entries(&self) -> Vec<VocabularyEntry> {
+        self.entries.read().unwrap().clone()
+    }
+
+    pub fn set_entries(&self, entries: Vec<VocabularyEntry>) -> Result<(), String> {
+        if entries.iter().any(|e| normalized(&e.term).is_empty()) {
+            return Err("Vocabulary terms must contain letters or digits".to_string());
+        }
+        let mut current = self.entries.write().unwrap();
+        self.save(&entries)?;
+        *current = entries;
+        Ok(())
+    }
+
+    /// Teach that `heard` should become `term`, adding the term if it is new.
+    pub fn learn(&self, heard: &str, term: &str) -> Result<(), String> {
+        let heard = heard.trim();
+        if normalized(heard).is_empty() || normalized(term).is_empty() {
+            return Err("Both the heard text and the term are required".to_string());
+        }
+        let mut entries = self.entries.write().unwrap();
+        let mut next = entries.clone();
+        let index = match next.iter().position(|e| e.term == term) {
+            Some(index) => index,
+            None => {
+                next.push(VocabularyEntry {
+                    term: term.to_string(),
+                    aliases: Vec::new(),
+                });
+                next.len() - 1
+            }
+        };
+        let aliases = &mut next[index].aliases;
+        if !aliases.iter().any(|a| normalized(a) == normalized(heard)) {
+            aliases.push(heard.to_string());
+        }
+        self.save(&next)?;
+        *entries = next;
+        Ok(())
+    }
+
+    pub fn export_json(&self) -> Result<String, String> {
+        let file = VocabularyFile {
+            entries: self.entries(),
+        };
+        serde_json::to_string_pretty(&file).map_err(|e| format!("Encode vocabulary: {e}"))
+    }
+
+    /// Import an exported vocabulary. With `replace` the current entries are
+    /// dropped; otherwise terms are merged and their aliases combined.
+    /// Returns the number of entries read.
+    pub fn import_json(&self, json: &str, replace: bool) -> Result<usize, String> {
+        let file: VocabularyFile =
+            serde_json::from_str(json).map_err(|e| format!("Invalid vocabulary: {e}"))?;
+        let count = file.entries.len();
+        let mut merged = if replace { Vec::new() } else { self.entries() };
+        for entry in file.entries {
+            match merged.iter_mut().find(|e| e.term == entry.term) {
+                Some(existing) => {
+                    for alias in entry.aliases {
+                        if !existing.aliases.contains(&alias) {
+                            existing.aliases.push(alias);
+                        }
+                    }
+                }
+                None => merged.push(entry),
+            }
+        }
+        self.set_entries(merged)?;
+        Ok(count)
+    }
+
+    /// Replace misrecognized words with the closest known term. At each
+    /// word the best-scoring window wins; ties go to the longer window.
+    pub fn correct(&self, text: &str) -> String {
+        let entries = self.entries.read().unwrap();
+        if entries.is_empty() {
+            return text.to_string();
+        }
+        let words = words(text);
+        let mut output = String::with_capacity(text.len());
+        let mut cursor = 0;
+        let mut i = 0;
+        while i < words.len() {
+            let mut best: Option<Candidate> = None;
+            for n in 1..=MAX_WINDOW_WORDS.min(words.len() - i) {
+                let window = &words[i..i + n];
+                // Only words separated by plain whitespace form a phrase
+                let joined = window
+                    .windows(2)
+                    .all(|w| text[w[0].end..w[1].start].chars().all(char::is_whitespace));
+                if !joined {
+                    break;
+                }
+                let heard = normalized(&text[window[0].start..window[n - 1].end]);
+                for entry in entries.iter() {
+                    let Some(score) = score(&heard, entry) else {
+                        continue;
+                    };
+                    if best.as_ref().is_none_or(|b| score <= b.score) {
+                        best = Some(Candidate {
+                            term: &entry.term,
+                            words: n,
+                            score,
+                        });
+                    }
+                }
+            }
+            match best {
+                Some(candidate) => {
+                    output.push_str(&text[cursor..words[i].start]);
+                    output.push_str(candidate.term);
+                    cursor = words[i + candidate.words - 1].end;
+                    i += candidate.words;
+                }
+                None => i += 1,
+            }
+        }
+        output.push_str(&text[cursor..]);
+        output
+    }
+
+    fn save(&self, entries: &[VocabularyEntry]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = VocabularyFile {
+            entries: entries.to_vec(),
+        };
+        json_file::save(path, &file)
+    }
+}
+
+impl Default for Vocabulary {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+impl TextStage for Vocabulary {
+    fn name(&self) -> &str {
+        "vocabulary"
+    }
+
+    /// Only FINALs are corrected; PARTIALs change too often to be worth it.
+    fn process(&self, text: &str, kind: TextKind) -> String {
+        if kind == TextKind::Partial || !self.is_enabled() {
+            return text.to_string();
+        }
+        self.correct(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vocabulary(terms: &[&str]) -> Vocabulary {
+        let vocabulary = Vocabulary::in_memory();
+        let entries = terms
+            .iter()
+            .map(|t| VocabularyEntry {
+                term: t.to_string(),
+                aliases: Vec::new(),
+            })
+            .collect();
+        vocabulary.set_entries(entries).unwrap();
+        vocabulary
+    }
+
+    #[test]
+    fn test_edit_distance_corrects_names() {
+        let vocabulary = vocabulary(&["John Smith", "Tauri"]);
+        assert_eq!(
+            vocabulary.correct("ask Jon Smyth about it."),
+            "ask John Smith about it."
+        );
+    }
+
+    #[test]
+    fn test_sound_alike_spans_several_words() {
+        let vocabulary = vocabulary(&["Kubernetes"]);
+        assert_eq!(
+            vocabulary.correct("deploy it to kuber nettes today"),
+            "deploy it to Kubernetes today"
+        );
+    }
+
+    #[test]
+    fn test_unrelated_words_are_kept() {
+        let vocabulary = vocabulary(&["Tauri", "Anna", "Kubernetes"]);
+        let text = "the story is any good, and the cat sat";
+        assert_eq!(vocabulary.correct(text), text);
+    }
+
+    #[test]
+    fn test_common_words_are_not_pulled_to_short_terms() {
+        let vocabulary = vocabulary(&["Python", "Slack", "Postgres", "Jira", "Jenkins"]);
+        let text = "press the button at the bottom, he was beaten black and blue";
+        assert_eq!(vocabulary.correct(text), text);
+        let text = "push it on the stack, the posters are in the hire pile";
+        assert_eq!(vocabulary.correct(text), text);
+        // Spelled close but sounding different
+        assert_eq!(vocabulary.correct("the jerkins"), "the jerkins");
+    }
+
+    #[test]
+    fn test_close_spelling_must_start_alike() {
+        let vocabulary = vocabulary(&["Grafana"]);
+        assert_eq!(vocabulary.correct("open krafana"), "open krafana");
+        assert_eq!(vocabulary.correct("open grafanna"), "open Grafana");
+    }
+
+    #[test]
+    fn test_learned_alias_applies_exactly() {
+        let vocabulary = vocabulary(&[]);
+        vocabulary.learn("cube control", "kubectl").unwrap();
+        assert_eq!(
+            vocabulary.correct("run Cube Control get pods"),
+            "run kubectl get pods"
+        );
+        assert!(vocabulary.learn(" ", "kubectl").is_err());
+    }
+
+    #[test]
+    fn test_korean_terms_use_edit_distance() {
+        let vocabulary = vocabulary(&["김지우 팀장님"]);
+        assert_eq!(
+            vocabulary.correct("김지오 팀장님 확인 부탁드립니다"),
+            "김지우 팀장님 확인 부탁드립니다"
+        );
+    }
+
+    #[test]
+    fn test_export_import_roundtrip_and_merge() {
+        let source = vocabulary(&["Tauri"]);
+        source.learn("tory app", "Tauri").unwrap();
+        let json = source.export_json().unwrap();
+
+        let target = vocabulary(&["Kubernetes"]);
+        assert_eq!(target.import_json(&json, false).unwrap(), 1);
+        let terms: Vec<String> = target.entries().into_iter().map(|e| e.term).collect();
+        assert_eq!(terms, vec!["Kubernetes", "Tauri"]);
+        assert_eq!(target.entries()[1].aliases, vec!["tory app"]);
+
+        target.import_json(&json, true).unwrap();
+        assert_eq!(target.entries(), source.entries());
+        assert!(target.import_json("not json", false).is_err());
+    }
+
+    #[test]
+    fn test_failed_save_changes_nothing() {
+        let path = std::env::temp_dir().join(format!("ptt-vocab-fail-{}.json", std::process::id()));
+        let tmp = path.with_extension("json.tmp");
+        let _ = std::fs::remove_file(&path);
+        let vocabulary = Vocabulary::open(&path).unwrap();
+        vocabulary.learn("cube control", "kubectl").unwrap();
+        let before = vocabulary.entries();
+
+        // A directory where the temp file goes makes the write fail
+        std::fs::create_dir_all(&tmp).unwrap();
+        assert!(vocabulary.learn("tory", "Tauri").is_err());
+        assert!(vocabulary.set_entries(Vec::new()).is_err());
+
+        assert_eq!(vocabulary.entries(), before);
+        assert_eq!(vocabulary.correct("tory"), "tory");
+
+        std::fs::remove_dir(&tmp).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_stage_skips_partials_and_disabled() {
+        let vocabulary = vocabulary(&["John Smith"]);
+        assert_eq!(
+            vocabulary.process("Jon Smyth", TextKind::Partial),
+            "Jon Smyth"
+        );
+        vocabulary.set_enabled(false);
+        assert_eq!(
+            vocabulary.process("Jon Smyth", TextKind::Final),
+            "Jon Smyth"
+        );
+    }
+}
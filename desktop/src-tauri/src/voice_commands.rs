@@ -0,0 +1,464 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::injection::undo::UndoStack;
+use crate::injection::{KeyAction, TextInjector};
+use crate::rules::{is_boundary, match_tokens};
+
+/// Editing command that can be spoken inside a FINAL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VoiceCommand {
+    NewLine,
+    NewParagraph,
+    Tab,
+    PressEnter,
+    SelectAll,
+    /// Erase the text inserted by the previous dictation (undo)
+    DeleteLastDictation,
+}
+
+impl VoiceCommand {
+    /// Commands whose effect can't be taken back by erasing a few characters
+    fn is_destructive(self) -> bool {
+        matches!(
+            self,
+            VoiceCommand::PressEnter | VoiceCommand::SelectAll | VoiceCommand::DeleteLastDictation
+        )
+    }
+
+    fn key_action(self) -> Option<KeyAction> {
+        match self {
+            VoiceCommand::NewLine => Some(KeyAction::NewLine),
+            VoiceCommand::NewParagraph => Some(KeyAction::NewParagraph),
+            VoiceCommand::Tab => Some(KeyAction::Tab),
+            VoiceCommand::PressEnter => Some(KeyAction::Enter),
+            VoiceCommand::SelectAll => Some(KeyAction::SelectAll),
+            VoiceCommand::DeleteLastDictation => None,
+        }
+    }
+}
+
+/// Spoken phrase bound to a command. `locale` is informational (for the
+/// settings UI); every phrase is recognized regardless of the phone's locale.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandPhrase {
+    pub phrase: String,
+    pub locale: String,
+    pub command: VoiceCommand,
+}
+
+fn phrase(phrase: &str, locale: &str, command: VoiceCommand) -> CommandPhrase {
+    CommandPhrase {
+        phrase: phrase.to_string(),
+        locale: locale.to_string(),
+        command,
+    }
+}
+
+/// Built-in English and Korean phrases
+pub fn default_phrases() -> Vec<CommandPhrase> {
+    use VoiceCommand::*;
+    vec![
+        phrase("new line", "en-US", NewLine),
+        phrase("newline", "en-US", NewLine),
+        phrase("new paragraph", "en-US", NewParagraph),
+        phrase("tab key", "en-US", Tab),
+        phrase("press tab", "en-US", Tab),
+        phrase("press enter", "en-US", PressEnter),
+        phrase("select all", "en-US", SelectAll),
+        phrase("delete that", "en-US", DeleteLastDictation),
+        phrase("scratch that", "en-US", DeleteLastDictation),
+        phrase("undo that", "en-US", DeleteLastDictation),
+        phrase("undo last dictation", "en-US", DeleteLastDictation),
+        phrase("줄 바꿈", "ko-KR", NewLine),
+        phrase("줄바꿈", "ko-KR", NewLine),
+        phrase("새 문단", "ko-KR", NewParagraph),
+        phrase("새 단락", "ko-KR", NewParagraph),
+        phrase("탭 키", "ko-KR", Tab),
+        phrase("엔터 키", "ko-KR", PressEnter),
+        phrase("전체 선택", "ko-KR", SelectAll),
+        phrase("방금 거 지워", "ko-KR", DeleteLastDictation),
+        phrase("방금 말한 거 지워", "ko-KR", DeleteLastDictation),
+        phrase("실행 취소", "ko-KR", DeleteLastDictation),
+    ]
+}
+
+/// Piece of an interpreted FINAL
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Text(String),
+    Command(VoiceCommand),
+}
+
+/// Sentence punctuation the recognizer tends to attach to a spoken command
+fn is_command_trailer(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '.' | ',' | '!' | '?')
+}
+
+/// Split `text` into literal text and commands. Phrases match whole words,
+/// case-insensitively, longest phrase first. Destructive commands (Enter,
+/// select all, delete that) only count at the very start or end of the
+/// FINAL, so "select all the rows in the sheet" is typed as text.
+pub fn parse(text: &str, phrases: &[CommandPhrase]) -> Vec<Segment> {
+    let mut ordered: Vec<(Vec<Vec<char>>, VoiceCommand)> = phrases
+        .iter()
+        .map(|p| {
+            let tokens = p.phrase.split_whitespace().map(|t| t.chars().collect());
+            (tokens.collect::<Vec<Vec<char>>>(), p.command)
+        })
+        .filter(|(tokens, _)| !tokens.is_empty())
+        .collect();
+    ordered
+        .sort_by_key(|(tokens, _)| std::cmp::Reverse(tokens.iter().map(Vec::len).sum::<usize>()));
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut segments = Vec::new();
+    let mut pending = String::new();
+    let mut i = 0;
+    'scan: while i < chars.len() {
+        if i == 0 || is_boundary(chars[i - 1]) {
+            for (tokens, command) in &ordered {
+                let Some(end) = match_tokens(&chars, i, tokens) else {
+                    continue;
+                };
+                if end < chars.len() && !is_boundary(chars[end]) {
+                    continue;
+                }
+                if command.is_destructive() {
+                    let at_start = pending.trim().is_empty()
+                        && !segments.iter().any(|s| matches!(s, Segment::Text(_)));
+                    let at_end = chars[end..].iter().all(|c| is_command_trailer(*c));
+                    if !at_start && !at_end {
+                        continue;
+                    }
+                }
+                let before = pending.trim_end();
+                if !before.is_empty() {
+                    segments.push(Segment::Text(before.to_string()));
+                }
+                pending.clear();
+                segments.push(Segment::Command(*command));
+                i = end;
+                while i < chars.len() && is_command_trailer(chars[i]) {
+                    i += 1;
+                }
+                continue 'scan;
+            }
+        }
+        pending.push(chars[i]);
+        i += 1;
+    }
+    if !pending.is_empty() {
+        segments.push(Segment::Text(pending));
+    }
+    segments
+}
+
+/// The text a FINAL leaves behind once its commands ran, for sinks that
+/// can't press keys: line breaks and tabs become characters, "delete that"
+/// drops what was dictated before it in the same FINAL.
+pub fn dictated_text(segments: &[Segment]) -> String {
+    let mut text = String::new();
+    let mut erasable_from = 0;
+    for segment in segments {
+        match segment {
+            Segment::Text(t) => {
+                if !text.is_empty() && !text.ends_with(char::is_whitespace) {
+                    text.push(' ');
+                }
+                text.push_str(t);
+            }
+            Segment::Command(VoiceCommand::NewLine) => text.push('\n'),
+            Segment::Command(VoiceCommand::NewParagraph) => text.push_str("\n\n"),
+            Segment::Command(VoiceCommand::Tab) => text.push('\t'),
+            Segment::Command(VoiceCommand::PressEnter) => {
+                text.push('\n');
+                erasable_from = text.len();
+            }
+            Segment::Command(VoiceCommand::SelectAll) => {}
+            Segment::Command(VoiceCommand::DeleteLastDictation) => text.truncate(erasable_from),
+        }
+    }
+    text
+}
+
+/// Interprets spoken commands in FINALs. Off until enabled in the settings,
+/// since a misheard phrase presses real keys.
+pub struct VoiceCommands {
+    enabled: AtomicBool,
+    phrases: RwLock<Vec<CommandPhrase>>,
+}
+
+impl VoiceCommands {
+    pub fn new(phrases: Vec<CommandPhrase>) -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            phrases: RwLock::new(phrases),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn phrases(&self) -> Vec<CommandPhrase> {
+        self.phrases.read().unwrap().clone()
+    }
+
+    pub fn set_phrases(&self, phrases: Vec<CommandPhrase>) {
+        *self.phrases.write().unwrap() = phrases;
+    }
+
+    /// Segments of `text` when it contains at least one command; None means
+    /// the FINAL is plain dictation.
+    pub fn interpret(&self, text: &str) -> Option<Vec<Segment>> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let segments = parse(text, &self.phrases.read().unwrap());
+        segments
+            .iter()
+            .any(|s| matches!(s, Segment::Command(_)))
+            .then_some(segments)
+    }
+
+    /// Inject text segments and run commands in order. "Delete that" erases
+    /// text dictated earlier in the same FINAL, or else undoes the previous
+    /// FINAL. Whatever remains inserted is recorded on `undo`.
+    pub fn execute(
+        &self,
+        client_id: &str,
+        segments: &[Segment],
+        injector: &dyn TextInjector,
+        undo: &UndoStack,
+    ) -> Result<(), String> {
+        let mut inserted = 0;
+        for segment in segments {
+            match segment {
+                Segment::Text(text) => {
+                    injector.inject(text)?;
+                    inserted += text.chars().count();
+                }
+                Segment::Command(command) => match command.key_action() {
+                    Some(action) => {
+                        injector.press(action)?;
+                        // Enter may have submitted the text; nothing before it is erasable
+                        inserted = match action.inserted_chars() {
+                            Some(n) => inserted + n,
+                            None => 0,
+                        };
+                    }
+                    None => {
+                        if inserted > 0 {
+                            injector.erase(std::mem::take(&mut inserted))?;
+                        } else if undo.last(Some(client_id)).is_some() {
+                            undo.undo(Some(client_id), injector)?;
+                        }
+                    }
+                },
+            }
+        }
+        undo.record(client_id, inserted, injector);
+        Ok(())
+    }
+}
+
+impl Default for VoiceCommands {
+    fn default() -> Self {
+        Self::new(default_phrases())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::injection::testing::MockInjector;
+
+    fn parsed(text: &str) -> Vec<Segment> {
+        parse(text, &default_phrases())
+    }
+
+    fn enabled_commands() -> VoiceCommands {
+        let commands = VoiceCommands::default();
+        commands.set_enabled(true);
+        commands
+    }
+
+    #[test]
+    fn test_plain_text_is_single_segment() {
+        assert_eq!(
+            parsed("renew the headline"),
+            vec![Segment::Text("renew the headline".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_inline_command_splits_text() {
+        assert_eq!(
+            parsed("Dear team, new paragraph. Thanks"),
+            vec![
+                Segment::Text("Dear team,".to_string()),
+                Segment::Command(VoiceCommand::NewParagraph),
+                Segment::Text("Thanks".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_korean_commands() {
+        assert_eq!(
+            parsed("안녕하세요 줄 바꿈 반갑습니다"),
+            vec![
+                Segment::Text("안녕하세요".to_string()),
+                Segment::Command(VoiceCommand::NewLine),
+                Segment::Text("반갑습니다".to_string()),
+            ]
+        );
+        assert_eq!(
+            parsed("방금 말한 거 지워"),
+            vec![Segment::Command(VoiceCommand::DeleteLastDictation)]
+        );
+    }
+
+    #[test]
+    fn test_interpret_ignores_plain_dictation_and_disabled() {
+        let commands = VoiceCommands::default();
+        assert!(commands.interpret("Press Enter").is_none());
+
+        commands.set_enabled(true);
+        assert!(commands.interpret("just words").is_none());
+        assert!(commands.interpret("Press Enter").is_some());
+
+        commands.set_enabled(false);
+        assert!(commands.interpret("Press Enter").is_none());
+    }
+
+    #[test]
+    fn test_execute_sends_keys_and_text() {
+        let commands = enabled_commands();
+        let undo = UndoStack::new();
+        let (mock, log) = MockInjector::new();
+        let segments = commands.interpret("hello new line world tab key").unwrap();
+
+        commands
+            .execute("phone-01", &segments, &mock, &undo)
+            .unwrap();
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["hello", "<press NewLine>", "world", "<press Tab>"]
+        );
+    }
+
+    #[test]
+    fn test_delete_that_erases_previous_dictation() {
+        let commands = enabled_commands();
+        let undo = UndoStack::new();
+        let (mock, log) = MockInjector::new();
+        undo.record("phone-01", 11, &mock);
+
+        let segments = commands.interpret("scratch that").unwrap();
+        commands
+            .execute("phone-01", &segments, &mock, &undo)
+            .unwrap();
+        // Nothing left to delete the second time
+        commands
+            .execute("phone-01", &segments, &mock, &undo)
+            .unwrap();
+
+        assert_eq!(*log.lock().unwrap(), vec!["<erase 11>"]);
+    }
+
+    #[test]
+    fn test_delete_that_within_same_final() {
+        let commands = enabled_commands();
+        let undo = UndoStack::new();
+        let (mock, log) = MockInjector::new();
+        let segments = commands.interpret("oops, delete that").unwrap();
+
+        commands
+            .execute("phone-01", &segments, &mock, &undo)
+            .unwrap();
+
+        assert_eq!(*log.lock().unwrap(), vec!["oops,", "<erase 5>"]);
+        assert!(undo.last(Some("phone-01")).is_none());
+    }
+
+    #[test]
+    fn test_destructive_commands_only_at_the_edges() {
+        assert_eq!(
+            parsed("please select all the rows"),
+            vec![Segment::Text("please select all the rows".to_string())]
+        );
+        assert_eq!(
+            parsed("then press enter twice and delete that file"),
+            vec![Segment::Text(
+                "then press enter twice and delete that file".to_string()
+            )]
+        );
+        assert_eq!(
+            parsed("Select all. New line"),
+            vec![
+                Segment::Command(VoiceCommand::SelectAll),
+                Segment::Command(VoiceCommand::NewLine),
+            ]
+        );
+        assert_eq!(
+            parsed("Looks good, press enter."),
+            vec![
+                Segment::Text("Looks good,".to_string()),
+                Segment::Command(VoiceCommand::PressEnter),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mid_sentence_select_all_is_typed() {
+        let commands = enabled_commands();
+        let undo = UndoStack::new();
+        let (mock, log) = MockInjector::new();
+        assert!(commands
+            .interpret("we should select all candidates")
+            .is_none());
+
+        let segments = commands
+            .interpret("we should select all candidates new line")
+            .unwrap();
+        commands
+            .execute("phone-01", &segments, &mock, &undo)
+            .unwrap();
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["we should select all candidates", "<press NewLine>"]
+        );
+    }
+
+    #[test]
+    fn test_dictated_text_resolves_commands() {
+        assert_eq!(
+            dictated_text(&parsed("Dear team, new paragraph. Thanks tab key ok")),
+            "Dear team,\n\nThanks\tok"
+        );
+        assert_eq!(dictated_text(&parsed("new line oops delete that")), "");
+        assert_eq!(dictated_text(&parsed("hi press enter")), "hi\n");
+    }
+
+    #[test]
+    fn test_phrases_roundtrip_json() {
+        let json =
+            serde_json::to_string(&phrase("new line", "en-US", VoiceCommand::NewLine)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"phrase":"new line","locale":"en-US","command":"new-line"}"#
+        );
+    }
+}
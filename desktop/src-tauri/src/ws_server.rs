@@ -4,9 +4,22 @@ use futures_util::{SinkExt, StreamExt};
 use tokio::net::TcpListener;
 use tokio::sync::Mutex;
 
+use crate::capture::{CaptureRecorder, Direction};
 use crate::client_registry::ClientRegistry;
+use crate::codec::{Codec, Implied};
+use crate::confidence::{ConfidenceGate, Gate, HeldText, HoldReason};
+use crate::formatting::JoinFormatter;
+use crate::history::{History, Outcome, SessionRef};
+use crate::injection::fanout::BackgroundFanOut;
+use crate::injection::undo::UndoStack;
 use crate::injection::TextInjector;
+use crate::live_typing::{self, LiveTyping};
+use crate::pipeline::{TextKind, TextPipeline};
 use crate::protocol;
+use crate::redaction::Redactor;
+use crate::review::{PendingEntry, ReviewQueue};
+use crate::stats::{FinalSample, Statistics};
+use crate::voice_commands::{self, VoiceCommands};
 
 /// Event emitted to the frontend
 #[derive(Debug, Clone, serde::Serialize)]
@@ -36,6 +49,35 @@ pub enum ServerEvent {
         client_id: String,
         session_id: String,
     },
+    ClipboardCopied {
+        text: String,
+    },
+    DictationUndone {
+        client_id: String,
+        chars: usize,
+    },
+    /// FINAL between the confidence thresholds, waiting for confirmation
+    TextHeld {
+        client_id: String,
+        held: HeldText,
+    },
+    /// FINAL below the lowest confidence threshold, not injected
+    TextRejected {
+        client_id: String,
+        session_id: String,
+        text: String,
+        confidence: f64,
+    },
+    HeldTextResolved {
+        client_id: String,
+        id: u64,
+        accepted: bool,
+    },
+    /// FINAL queued for review instead of being injected
+    PendingQueued {
+        client_id: String,
+        entry: PendingEntry,
+    },
 }
 
 /// Callback trait for server events (enables testing without Tauri)
@@ -43,14 +85,301 @@ pub trait EventEmitter: Send + Sync {
     fn emit(&self, event: ServerEvent);
 }
 
+/// Emitter for running without a frontend — events only go to the log
+pub struct LogEmitter;
+
+impl EventEmitter for LogEmitter {
+    fn emit(&self, event: ServerEvent) {
+        log::debug!("{:?}", event);
+    }
+}
+
+/// Shared state handed to every connection task
+pub struct ServerContext {
+    pub registry: Arc<Mutex<ClientRegistry>>,
+    pub injector: Arc<dyn TextInjector>,
+    /// Log files, pipes and webhooks that receive each FINAL besides the
+    /// focused app, delivered on their own thread
+    pub extra_sinks: Option<BackgroundFanOut>,
+    pub emitter: Arc<dyn EventEmitter>,
+    pub live_typing: Arc<LiveTyping>,
+    pub pipeline: Arc<TextPipeline>,
+    pub voice_commands: Arc<VoiceCommands>,
+    pub undo: Arc<UndoStack>,
+    pub formatter: Arc<JoinFormatter>,
+    pub confidence: Arc<ConfidenceGate>,
+    pub review: Arc<ReviewQueue>,
+    pub redaction: Arc<Redactor>,
+    pub history: Arc<History>,
+    pub stats: Arc<Statistics>,
+    pub capture: Arc<CaptureRecorder>,
+}
+
+impl ServerContext {
+    pub fn new(
+        registry: Arc<Mutex<ClientRegistry>>,
+        injector: Arc<dyn TextInjector>,
+        emitter: Arc<dyn EventEmitter>,
+    ) -> Self {
+        Self {
+            registry,
+            injector,
+            extra_sinks: None,
+            emitter,
+            live_typing: Arc::new(LiveTyping::new(false)),
+            pipeline: Arc::new(TextPipeline::new()),
+            voice_commands: Arc::new(VoiceCommands::default()),
+            undo: Arc::new(UndoStack::new()),
+            formatter: Arc::new(JoinFormatter::default()),
+            confidence: Arc::new(ConfidenceGate::default()),
+            review: Arc::new(ReviewQueue::default()),
+            redaction: Arc::new(Redactor::default()),
+            history: Arc::new(History::in_memory()),
+            stats: Arc::new(Statistics::in_memory()),
+            capture: Arc::new(CaptureRecorder::new()),
+        }
+    }
+
+    /// Inject a FINAL: voice commands, join formatting, undo bookkeeping and,
+    /// when `live` is set, reconciling what live typing already typed.
+    fn deliver_final(&self, client_id: &str, text: &str, live: bool) -> Result<(), String> {
+        let (result, delivered) = self.inject_final(client_id, text, live);
+        // Extra sinks never decide whether the FINAL was delivered, nor
+        // hold up the ACK
+        if let Some(sinks) = &self.extra_sinks {
+            sinks.send(&delivered);
+        }
+        result
+    }
+
+    /// Type a FINAL into the focused app; also returns the text as dictated
+    /// (formatted, commands resolved) for the extra sinks.
+    fn inject_final(
+        &self,
+        client_id: &str,
+        text: &str,
+        live: bool,
+    ) -> (Result<(), String>, String) {
+        let injector = self.injector.as_ref();
+        if let Some(segments) = self.voice_commands.interpret(text) {
+            // Live-typed command words must not stay in the document
+            if live {
+                self.discard_live_typing(client_id);
+            }
+            let result = self
+                .voice_commands
+                .execute(client_id, &segments, injector, &self.undo);
+            // Line breaks and erasing leave the cursor in a new context
+            self.formatter.reset();
+            let result = result.map_err(|e| format!("Voice command failed: {e}"));
+            return (result, voice_commands::dictated_text(&segments));
+        }
+
+        let target = injector.target().unwrap_or_default();
+        let now = std::time::Instant::now();
+        let formatted = self.formatter.format(&target, text, now);
+        let typed = if live {
+            self.live_typing.finish(client_id, &formatted)
+        } else {
+            None
+        };
+        let result = match typed {
+            Some(edit) => live_typing::apply_edit(injector, &edit)
+                .map_err(|e| format!("Live typing reconcile failed: {e}")),
+            None => injector.inject(&formatted),
+        };
+        if result.is_ok() {
+            self.undo
+                .record(client_id, formatted.chars().count(), injector);
+            // The backend that took it, which may be a fallback
+            let target = injector.target().unwrap_or_default();
+            self.formatter.record(&target, &formatted, now);
+        }
+        (result, formatted)
+    }
+
+    /// Erase whatever live typing typed for the client's current session.
+    fn discard_live_typing(&self, client_id: &str) {
+        if let Some(edit) = self.live_typing.finish(client_id, "") {
+            if let Err(e) = live_typing::apply_edit(self.injector.as_ref(), &edit) {
+                log::warn!("Live typing reconcile failed for {}: {}", client_id, e);
+            }
+        }
+    }
+
+    /// Inject (`accept`) or drop a held FINAL and tell the frontend. A FINAL
+    /// that fails to inject stays held so it can be confirmed again.
+    pub fn resolve_held(&self, id: u64, accept: bool) -> Result<HeldText, String> {
+        // Taken before injecting, so a second confirm can't type it too
+        let held = self.confidence.take(id)?;
+        if accept {
+            if let Err(e) = self.deliver_final(&held.client_id, &held.text, false) {
+                self.confidence.restore(held);
+                return Err(e);
+            }
+            self.emitter.emit(ServerEvent::FinalText {
+                client_id: held.client_id.clone(),
+                session_id: held.session_id.clone(),
+                text: held.text.clone(),
+                confidence: held.confidence,
+            });
+        }
+        let outcome = if accept {
+            Outcome::Injected
+        } else {
+            Outcome::Rejected
+        };
+        self.record_outcome(&held.client_id, &held.session_id, outcome);
+        self.emitter.emit(ServerEvent::HeldTextResolved {
+            client_id: held.client_id.clone(),
+            id,
+            accepted: accept,
+        });
+        Ok(held)
+    }
+
+    /// Hold a FINAL for confirmation and tell the frontend; a full held
+    /// queue rejects it instead.
+    fn hold_final(
+        &self,
+        client_id: &str,
+        session_id: &str,
+        text: &str,
+        confidence: f64,
+        reason: HoldReason,
+    ) -> Result<HeldText, String> {
+        match self
+            .confidence
+            .hold(client_id, session_id, text, confidence, reason)
+        {
+            Ok(held) => {
+                self.emitter.emit(ServerEvent::TextHeld {
+                    client_id: client_id.to_string(),
+                    held: held.clone(),
+                });
+                Ok(held)
+            }
+            Err(e) => {
+                log::warn!("Rejected FINAL from {}: {}", client_id, e);
+                self.emitter.emit(ServerEvent::TextRejected {
+                    client_id: client_id.to_string(),
+                    session_id: session_id.to_string(),
+                    text: text.to_string(),
+                    confidence,
+                });
+                Err(e)
+            }
+        }
+    }
+
+    /// Inject a reviewed entry (with any edits) and drop it from the queue.
+    /// An entry that fails to inject stays queued.
+    pub fn approve_pending(&self, id: u64) -> Result<PendingEntry, String> {
+        let claimed = self.review.claim(id)?;
+        let entry = claimed.entry.clone();
+        if let Err(e) = self.deliver_final(&entry.client_id, &entry.text, false) {
+            if let Err(e) = self.review.release(claimed) {
+                log::error!("Review queue: {}", e);
+            }
+            return Err(e);
+        }
+        // Already typed and out of the queue; only the file may lag behind
+        if let Err(e) = self.review.complete(claimed) {
+            log::error!("Review queue: {}", e);
+        }
+        self.emitter.emit(ServerEvent::FinalText {
+            client_id: entry.client_id.clone(),
+            session_id: entry.session_id.clone(),
+            text: entry.text.clone(),
+            confidence: entry.confidence,
+        });
+        self.record_outcome(&entry.client_id, &entry.session_id, Outcome::Injected);
+        Ok(entry)
+    }
+
+    /// Approve every queued entry in order, carrying on past failures;
+    /// returns how many were injected.
+    pub fn approve_all_pending(&self) -> Result<usize, String> {
+        let mut approved = 0;
+        let mut failures = Vec::new();
+        for entry in self.review.entries() {
+            match self.approve_pending(entry.id) {
+                Ok(_) => approved += 1,
+                Err(e) => failures.push(format!("#{}: {}", entry.id, e)),
+            }
+        }
+        if failures.is_empty() {
+            Ok(approved)
+        } else {
+            Err(format!(
+                "Approved {}, failed {}: {}",
+                approved,
+                failures.len(),
+                failures.join("; ")
+            ))
+        }
+    }
+
+    /// Drop a reviewed entry without injecting it.
+    pub fn discard_pending(&self, id: u64) -> Result<PendingEntry, String> {
+        let entry = self.review.remove(id)?;
+        self.record_outcome(&entry.client_id, &entry.session_id, Outcome::Rejected);
+        Ok(entry)
+    }
+
+    fn record_outcome(&self, client_id: &str, session_id: &str, outcome: Outcome) {
+        if let Err(e) = self.history.set_outcome(client_id, session_id, outcome) {
+            log::warn!("History: {}", e);
+        }
+    }
+
+    /// Erase the last FINAL of `client_id` (or of any client) and tell the frontend.
+    pub fn undo_last_dictation(&self, client_id: Option<&str>) -> Result<usize, String> {
+        let undone = self.undo.undo(client_id, self.injector.as_ref())?;
+        self.formatter.reset();
+        self.emitter.emit(ServerEvent::DictationUndone {
+            client_id: undone.client_id,
+            chars: undone.chars,
+        });
+        Ok(undone.chars)
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Local date statistics are bucketed by
+fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// Message timestamp as the history stores it; a value past `i64::MAX` is
+/// no real Unix-ms time, so the message is dropped like any invalid one.
+fn history_timestamp(timestamp: u64) -> Option<i64> {
+    let converted = i64::try_from(timestamp).ok();
+    if converted.is_none() {
+        log::warn!("Invalid message: timestamp {} out of range", timestamp);
+    }
+    converted
+}
+
+/// Device model and engine the client announced in HELLO
+fn client_details(registry: &ClientRegistry, client_id: &str) -> (String, String) {
+    registry
+        .get(client_id)
+        .map(|c| (c.device_model.clone(), c.engine.clone()))
+        .unwrap_or_default()
+}
+
 /// Handle a single parsed protocol message. Returns an optional response to send back.
-async fn handle_message(
+pub(crate) async fn handle_message(
     message: protocol::Message,
-    registry: &Arc<Mutex<ClientRegistry>>,
-    injector: &Arc<dyn TextInjector>,
-    emitter: &Arc<dyn EventEmitter>,
+    ctx: &ServerContext,
     client_id_slot: &mut Option<String>,
-) -> Option<tokio_tungstenite::tungstenite::Message> {
+) -> Option<protocol::Message> {
+    let registry = &ctx.registry;
+    let emitter = &ctx.emitter;
     match message {
         protocol::Message::Hello { client_id, payload } => {
             {
@@ -62,21 +391,41 @@ async fn handle_message(
                 client_id: client_id.clone(),
                 device_model: payload.device_model,
             });
+            let mut ack_payload = protocol::AckPayload::new("HELLO");
+            let codec = Codec::negotiate(&payload.capabilities);
+            if codec.is_binary() {
+                ack_payload.encoding = Some(codec.name().to_string());
+            }
             let ack = protocol::Message::Ack {
                 client_id,
-                payload: protocol::AckPayload {
-                    ack_type: "HELLO".to_string(),
-                },
+                payload: ack_payload,
             };
-            let json = protocol::serialize_message(&ack).ok()?;
-            Some(tokio_tungstenite::tungstenite::Message::Text(json))
+            Some(ack)
         }
         protocol::Message::PttStart { client_id, payload } => {
-            {
+            let (device_model, engine) = {
                 let mut reg = registry.lock().await;
                 reg.set_session(&client_id, Some(payload.session_id.clone()));
                 reg.set_partial_text(&client_id, None);
+                client_details(&reg, &client_id)
+            };
+            let session = SessionRef {
+                client_id: &client_id,
+                device_model: &device_model,
+                engine: &engine,
+                session_id: &payload.session_id,
+            };
+            if let Err(e) = ctx.history.start(session, now_ms()) {
+                log::warn!("History: {}", e);
             }
+            ctx.stats.session_started(
+                &client_id,
+                &device_model,
+                &engine,
+                std::time::Instant::now(),
+                &today(),
+            );
+            ctx.live_typing.begin(&client_id);
             emitter.emit(ServerEvent::PttStarted {
                 client_id,
                 session_id: payload.session_id,
@@ -84,50 +433,237 @@ async fn handle_message(
             None
         }
         protocol::Message::Partial {
-            client_id, payload, ..
+            client_id,
+            timestamp,
+            payload,
         } => {
+            let timestamp = history_timestamp(timestamp)?;
+            let text = ctx.pipeline.process(&payload.text, TextKind::Partial);
+            let text = ctx.redaction.redact(&text, TextKind::Partial).text;
             {
                 let mut reg = registry.lock().await;
-                reg.set_partial_text(&client_id, Some(payload.text.clone()));
+                reg.set_partial_text(&client_id, Some(text.clone()));
+            }
+            let recorded = ctx
+                .history
+                .partial(&client_id, &payload.session_id, timestamp);
+            if let Err(e) = recorded {
+                log::warn!("History: {}", e);
+            }
+            ctx.stats.partial(&client_id);
+            // In review mode nothing reaches the target app before approval
+            if !ctx.review.is_enabled() {
+                let typed =
+                    ctx.live_typing
+                        .update(&client_id, payload.seq, &text, ctx.injector.as_ref());
+                if let Err(e) = typed {
+                    log::warn!("Live typing failed for {}: {}", client_id, e);
+                }
             }
             emitter.emit(ServerEvent::PartialText {
                 client_id,
                 session_id: payload.session_id,
-                text: payload.text,
+                text,
                 seq: payload.seq,
                 confidence: payload.confidence,
             });
             None
         }
         protocol::Message::Final {
-            client_id, payload, ..
+            client_id,
+            timestamp,
+            payload,
         } => {
-            {
+            let timestamp = history_timestamp(timestamp)?;
+            let text = ctx.pipeline.process(&payload.text, TextKind::Final);
+            let redacted = ctx.redaction.redact(&text, TextKind::Final);
+            let text = redacted.text;
+            let (device_model, engine) = {
                 let mut reg = registry.lock().await;
                 reg.set_session(&client_id, None);
                 reg.set_partial_text(&client_id, None);
-            }
-            let _ = injector.inject(&payload.text);
-            emitter.emit(ServerEvent::FinalText {
-                client_id: client_id.clone(),
-                session_id: payload.session_id,
-                text: payload.text,
+                client_details(&reg, &client_id)
+            };
+            let session_id = payload.session_id.clone();
+            let mut ack_payload = protocol::AckPayload::new("FINAL");
+            let sensitive = redacted.needs_confirmation;
+            let gate = match ctx.confidence.evaluate(payload.confidence) {
+                Gate::Inject if sensitive => Gate::Hold,
+                gate => gate,
+            };
+            let reason = if sensitive {
+                HoldReason::SensitiveData
+            } else {
+                HoldReason::LowConfidence
+            };
+            let outcome = match gate {
+                Gate::Inject if ctx.review.is_enabled() => {
+                    ctx.discard_live_typing(&client_id);
+                    match ctx.review.push(
+                        &client_id,
+                        &payload.session_id,
+                        &text,
+                        payload.confidence,
+                    ) {
+                        Ok(entry) => {
+                            emitter.emit(ServerEvent::PendingQueued {
+                                client_id: client_id.clone(),
+                                entry,
+                            });
+                            Outcome::Pending
+                        }
+                        Err(e) => {
+                            // Keep the text in memory rather than losing it
+                            log::error!("Review queue failed for {}: {}", client_id, e);
+                            ack_payload.error = Some(format!("Review queue: {e}"));
+                            let held = ctx.hold_final(
+                                &client_id,
+                                &payload.session_id,
+                                &text,
+                                payload.confidence,
+                                HoldReason::ReviewUnavailable,
+                            );
+                            match held {
+                                Ok(held) => {
+                                    ack_payload.held_id = Some(held.id);
+                                    Outcome::Held
+                                }
+                                Err(_) => Outcome::Rejected,
+                            }
+                        }
+                    }
+                }
+                Gate::Inject => match ctx.deliver_final(&client_id, &text, true) {
+                    Ok(()) => {
+                        emitter.emit(ServerEvent::FinalText {
+                            client_id: client_id.clone(),
+                            session_id: payload.session_id,
+                            text: text.clone(),
+                            confidence: payload.confidence,
+                        });
+                        Outcome::Injected
+                    }
+                    Err(e) => {
+                        log::warn!("Injection failed for {}: {}", client_id, e);
+                        ack_payload.error = Some(e);
+                        Outcome::Failed
+                    }
+                },
+                Gate::Hold => {
+                    ctx.discard_live_typing(&client_id);
+                    let held = ctx.hold_final(
+                        &client_id,
+                        &payload.session_id,
+                        &text,
+                        payload.confidence,
+                        reason,
+                    );
+                    match held {
+                        Ok(held) => {
+                            ack_payload.held_id = Some(held.id);
+                            Outcome::Held
+                        }
+                        Err(e) => {
+                            ack_payload.error = Some(e);
+                            Outcome::Rejected
+                        }
+                    }
+                }
+                Gate::Reject => {
+                    ctx.discard_live_typing(&client_id);
+                    log::info!(
+                        "Rejected FINAL from {} (confidence {:.2})",
+                        client_id,
+                        payload.confidence
+                    );
+                    emitter.emit(ServerEvent::TextRejected {
+                        client_id: client_id.clone(),
+                        session_id: payload.session_id,
+                        text: text.clone(),
+                        confidence: payload.confidence,
+                    });
+                    Outcome::Rejected
+                }
+            };
+            ack_payload.status = Some(outcome.as_str().to_string());
+            let sample = FinalSample {
+                client_id: &client_id,
+                device_model: &device_model,
+                engine: &engine,
+                text: &text,
                 confidence: payload.confidence,
-            });
+                injection_failed: outcome == Outcome::Failed,
+                at: std::time::Instant::now(),
+            };
+            ctx.stats.record_final(sample, &today());
+            let session = SessionRef {
+                client_id: &client_id,
+                device_model: &device_model,
+                engine: &engine,
+                session_id: &session_id,
+            };
+            // Sensitive text that wasn't confirmed stays out of the history
+            let stored_text = if sensitive && outcome != Outcome::Injected {
+                ctx.redaction.mask_all(&text)
+            } else {
+                text.clone()
+            };
+            let recorded = ctx.history.finish(
+                session,
+                &stored_text,
+                payload.confidence,
+                timestamp,
+                outcome,
+                now_ms(),
+            );
+            if let Err(e) = recorded {
+                log::warn!("History: {}", e);
+            }
             let ack = protocol::Message::Ack {
                 client_id,
-                payload: protocol::AckPayload {
-                    ack_type: "FINAL".to_string(),
-                },
+                payload: ack_payload,
             };
-            let json = protocol::serialize_message(&ack).ok()?;
-            Some(tokio_tungstenite::tungstenite::Message::Text(json))
+            Some(ack)
         }
         protocol::Message::Heartbeat { client_id } => {
             let mut reg = registry.lock().await;
             reg.heartbeat(&client_id);
             None
         }
+        protocol::Message::Undo { client_id } => {
+            if let Err(e) = ctx.undo_last_dictation(Some(&client_id)) {
+                log::warn!("Undo failed for {}: {}", client_id, e);
+            }
+            let ack = protocol::Message::Ack {
+                client_id,
+                payload: protocol::AckPayload::new("UNDO"),
+            };
+            Some(ack)
+        }
+        protocol::Message::ConfirmHeld { client_id, payload } => {
+            // A phone may only decide on its own held texts
+            let owned = ctx
+                .confidence
+                .held()
+                .iter()
+                .any(|h| h.id == payload.held_id && h.client_id == client_id);
+            let result = if owned {
+                ctx.resolve_held(payload.held_id, payload.accept)
+                    .map(|_| ())
+            } else {
+                Err(format!("No held text with id {}", payload.held_id))
+            };
+            let mut ack_payload = protocol::AckPayload::new("CONFIRM_HELD");
+            if let Err(e) = result {
+                log::warn!("Confirm failed for {}: {}", client_id, e);
+                ack_payload.status = Some("failed".to_string());
+            }
+            let ack = protocol::Message::Ack {
+                client_id,
+                payload: ack_payload,
+            };
+            Some(ack)
+        }
         protocol::Message::Ack { .. } => None,
     }
 }
@@ -138,6 +674,36 @@ pub async fn start_server(
     injector: Arc<dyn TextInjector>,
     emitter: Arc<dyn EventEmitter>,
 ) -> Result<(), String> {
+    serve(
+        port,
+        Arc::new(ServerContext::new(registry, injector, emitter)),
+    )
+    .await
+}
+
+/// A response in the connection's encoding: JSON as text, others as binary
+fn encode_frame(
+    codec: Codec,
+    implied: &Implied,
+    message: &protocol::Message,
+) -> Option<tokio_tungstenite::tungstenite::Message> {
+    let bytes = match codec.encode_with(message, implied) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log::error!("Failed to encode response: {}", e);
+            return None;
+        }
+    };
+    if codec.is_binary() {
+        return Some(tokio_tungstenite::tungstenite::Message::Binary(bytes));
+    }
+    String::from_utf8(bytes)
+        .ok()
+        .map(tokio_tungstenite::tungstenite::Message::Text)
+}
+
+/// Run the server with a fully configured context
+pub async fn serve(port: u16, ctx: Arc<ServerContext>) -> Result<(), String> {
     let addr = format!("0.0.0.0:{}", port);
     let listener = TcpListener::bind(&addr)
         .await
@@ -151,9 +717,7 @@ pub async fn start_server(
             .await
             .map_err(|e| format!("Accept failed: {}", e))?;
 
-        let registry = Arc::clone(&registry);
-        let injector = Arc::clone(&injector);
-        let emitter = Arc::clone(&emitter);
+        let ctx = Arc::clone(&ctx);
 
         tokio::spawn(async move {
             let ws_stream = match tokio_tungstenite::accept_async(stream).await {
@@ -166,6 +730,9 @@ pub async fn start_server(
 
             let (mut sink, mut stream) = ws_stream.split();
             let mut client_id_slot: Option<String> = None;
+            let connection = ctx.capture.next_connection();
+            let mut codec = Codec::Json;
+            let mut implied = Implied::default();
 
             while let Some(msg_result) = stream.next().await {
                 let msg = match msg_result {
@@ -176,13 +743,18 @@ pub async fn start_server(
                     }
                 };
 
-                let text = match &msg {
-                    tokio_tungstenite::tungstenite::Message::Text(t) => t.to_string(),
+                let decoded = match msg {
+                    tokio_tungstenite::tungstenite::Message::Text(t) => {
+                        protocol::parse_message_validated(&t)
+                    }
+                    tokio_tungstenite::tungstenite::Message::Binary(b) => {
+                        codec.decode_with(&b, &implied)
+                    }
                     tokio_tungstenite::tungstenite::Message::Close(_) => break,
                     _ => continue,
                 };
 
-                let parsed = match protocol::parse_message(&text) {
+                let parsed = match decoded {
                     Ok(p) => p,
                     Err(e) => {
                         log::warn!("Invalid message: {}", e);
@@ -190,32 +762,60 @@ pub async fn start_server(
                     }
                 };
 
-                if let Some(response) =
-                    handle_message(parsed, &registry, &injector, &emitter, &mut client_id_slot)
-                        .await
-                {
-                    if let Err(e) = sink.send(response).await {
-                        log::error!("Failed to send response: {}", e);
-                        break;
+                implied.observe(&parsed);
+                ctx.capture.record(connection, Direction::In, &parsed);
+                let Some(response) = handle_message(parsed, &ctx, &mut client_id_slot).await else {
+                    continue;
+                };
+                ctx.capture.record(connection, Direction::Out, &response);
+                let Some(frame) = encode_frame(codec, &implied, &response) else {
+                    continue;
+                };
+                if let Err(e) = sink.send(frame).await {
+                    log::error!("Failed to send response: {}", e);
+                    break;
+                }
+                // The HELLO ACK itself still goes out in the old encoding
+                if let protocol::Message::Ack { payload, .. } = &response {
+                    if payload.ack_type == "HELLO" {
+                        codec = payload
+                            .encoding
+                            .as_deref()
+                            .and_then(Codec::from_name)
+                            .unwrap_or(Codec::Json);
                     }
                 }
             }
 
-            // Client disconnected — clean up
-            if let Some(cid) = &client_id_slot {
-                let mut reg = registry.lock().await;
-                reg.unregister(cid);
-                emitter.emit(ServerEvent::ClientDisconnected {
-                    client_id: cid.clone(),
-                });
-            }
+            disconnect(&ctx, client_id_slot.as_deref()).await;
         });
     }
 }
 
+/// Client disconnected — clean up
+pub(crate) async fn disconnect(ctx: &ServerContext, client_id: Option<&str>) {
+    let Some(cid) = client_id else {
+        return;
+    };
+    let mut reg = ctx.registry.lock().await;
+    reg.unregister(cid);
+    ctx.live_typing.clear(cid);
+    if let Err(e) = ctx.history.abandon_open(cid, now_ms()) {
+        log::warn!("History: {}", e);
+    }
+    ctx.stats.abandon(cid, &today());
+    ctx.emitter.emit(ServerEvent::ClientDisconnected {
+        client_id: cid.to_string(),
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::confidence::ConfidencePolicy;
+    use crate::history::HistoryQuery;
+    use crate::redaction::{RedactAction, RedactionConfig};
+    use crate::stats::{GroupBy, StatsQuery};
 
     struct TestEmitter {
         events: Arc<std::sync::Mutex<Vec<ServerEvent>>>,
@@ -260,6 +860,22 @@ mod tests {
             self.injected.lock().unwrap().push(text.to_string());
             Ok(())
         }
+
+        fn erase(&self, count: usize) -> Result<(), String> {
+            self.injected
+                .lock()
+                .unwrap()
+                .push(format!("<erase {count}>"));
+            Ok(())
+        }
+
+        fn press(&self, action: crate::injection::KeyAction) -> Result<(), String> {
+            self.injected
+                .lock()
+                .unwrap()
+                .push(format!("<press {action:?}>"));
+            Ok(())
+        }
     }
 
     /// Helper: find a free port by binding to :0, recording the port, then dropping.
@@ -268,6 +884,39 @@ mod tests {
         listener.local_addr().unwrap().port()
     }
 
+    type TestSocket = tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >;
+
+    /// Helper: start `serve` with the given context and connect as phone-01 (HELLO ACK consumed).
+    async fn serve_and_connect(ctx: ServerContext) -> TestSocket {
+        let port = free_port();
+        tokio::spawn(serve(port, Arc::new(ctx)));
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let url = format!("ws://127.0.0.1:{}", port);
+        let (mut ws, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        let hello_json = r#"{"type":"HELLO","clientId":"phone-01","payload":{"deviceModel":"Galaxy S23","engine":"Google","capabilities":["WS"]}}"#;
+        ws.send(tokio_tungstenite::tungstenite::Message::Text(
+            hello_json.into(),
+        ))
+        .await
+        .unwrap();
+        let _ = ws.next().await.unwrap().unwrap(); // consume HELLO ACK
+        ws
+    }
+
+    /// Helper: send raw JSON messages in order.
+    async fn send_all(ws: &mut TestSocket, messages: &[&str]) {
+        for json in messages {
+            ws.send(tokio_tungstenite::tungstenite::Message::Text(
+                json.to_string(),
+            ))
+            .await
+            .unwrap();
+        }
+    }
+
     #[tokio::test]
     async fn test_hello_ack_roundtrip() {
         let port = free_port();
@@ -397,4 +1046,813 @@ mod tests {
 
         ws.close(None).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_binary_frames_use_negotiated_codec() {
+        let port = free_port();
+        let (injector, injected) = MockInjector::new();
+        let (emitter, _events) = TestEmitter::new();
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        tokio::spawn(start_server(
+            port,
+            registry,
+            Arc::new(injector),
+            Arc::new(emitter),
+        ));
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let url = format!("ws://127.0.0.1:{}", port);
+        let (mut ws, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        let hello_json = r#"{"type":"HELLO","clientId":"phone-01","payload":{"deviceModel":"Pixel 8","engine":"Google","capabilities":["WS","MSGPACK","CBOR"]}}"#;
+        send_all(&mut ws, &[hello_json]).await;
+
+        // The HELLO ACK is still JSON and names the codec
+        let ack_text = ws.next().await.unwrap().unwrap().into_text().unwrap();
+        match protocol::parse_message(&ack_text).unwrap() {
+            protocol::Message::Ack { payload, .. } => {
+                assert_eq!(payload.encoding.as_deref(), Some("CBOR"));
+            }
+            other => panic!("expected ACK, got {:?}", other),
+        }
+
+        let final_msg = protocol::Message::Final {
+            client_id: "phone-01".to_string(),
+            timestamp: 1670000000000,
+            payload: protocol::FinalPayload {
+                session_id: "s-bin".to_string(),
+                text: "Sent as CBOR".to_string(),
+                confidence: 0.95,
+            },
+        };
+        // The connection already knows the client id from HELLO
+        let implied = Implied {
+            client_id: Some("phone-01".to_string()),
+            session_id: None,
+        };
+        ws.send(tokio_tungstenite::tungstenite::Message::Binary(
+            Codec::Cbor.encode_with(&final_msg, &implied).unwrap(),
+        ))
+        .await
+        .unwrap();
+
+        // Answered in CBOR too, without the client id
+        let reply = ws.next().await.unwrap().unwrap();
+        let tokio_tungstenite::tungstenite::Message::Binary(bytes) = reply else {
+            panic!("expected a binary frame, got {:?}", reply);
+        };
+        assert!(Codec::Cbor.decode(&bytes).is_err());
+        match Codec::Cbor.decode_with(&bytes, &implied).unwrap() {
+            protocol::Message::Ack { payload, .. } => assert_eq!(payload.ack_type, "FINAL"),
+            other => panic!("expected ACK, got {:?}", other),
+        }
+        assert_eq!(*injected.lock().unwrap(), vec!["Sent as CBOR".to_string()]);
+
+        ws.close(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_live_typing_types_partials_and_reconciles_final() {
+        let port = free_port();
+
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, _events) = TestEmitter::new();
+        let (injector, injected) = MockInjector::new();
+
+        let ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        ctx.live_typing.set_enabled(true);
+        tokio::spawn(serve(port, Arc::new(ctx)));
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let url = format!("ws://127.0.0.1:{}", port);
+        let (mut ws, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+
+        let hello_json = r#"{"type":"HELLO","clientId":"phone-01","payload":{"deviceModel":"Galaxy S23","engine":"Google","capabilities":["WS"]}}"#;
+        ws.send(tokio_tungstenite::tungstenite::Message::Text(
+            hello_json.into(),
+        ))
+        .await
+        .unwrap();
+        let _ = ws.next().await.unwrap().unwrap(); // consume HELLO ACK
+
+        let messages = [
+            r#"{"type":"PTT_START","clientId":"phone-01","payload":{"sessionId":"s-abc123"}}"#,
+            r#"{"type":"PARTIAL","clientId":"phone-01","timestamp":1670000000000,"payload":{"sessionId":"s-abc123","seq":1,"text":"hel","confidence":0.5}}"#,
+            r#"{"type":"PARTIAL","clientId":"phone-01","timestamp":1670000000100,"payload":{"sessionId":"s-abc123","seq":2,"text":"help","confidence":0.6}}"#,
+            r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000200,"payload":{"sessionId":"s-abc123","text":"Help","confidence":0.9}}"#,
+        ];
+        for json in messages {
+            ws.send(tokio_tungstenite::tungstenite::Message::Text(json.into()))
+                .await
+                .unwrap();
+        }
+        let _ = ws.next().await.unwrap().unwrap(); // consume FINAL ACK
+
+        {
+            let texts = injected.lock().unwrap();
+            assert_eq!(*texts, vec!["hel", "p", "<erase 4>", "Help"]);
+        }
+
+        ws.close(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_extra_sinks_get_only_the_final() {
+        use crate::injection::fanout::FanOutInjector;
+
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, _events) = TestEmitter::new();
+        let (injector, injected) = MockInjector::new();
+        let (sink, sink_log) = MockInjector::new();
+
+        let mut ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        ctx.live_typing.set_enabled(true);
+        ctx.voice_commands.set_enabled(true);
+        ctx.extra_sinks = Some(BackgroundFanOut::spawn(FanOutInjector::new(vec![
+            ("file".to_string(), Arc::new(sink)),
+            (
+                "webhook".to_string(),
+                Arc::new(crate::injection::testing::MockInjector::failing()),
+            ),
+        ])));
+        let mut ws = serve_and_connect(ctx).await;
+
+        send_all(
+            &mut ws,
+            &[
+                r#"{"type":"PTT_START","clientId":"phone-01","payload":{"sessionId":"s-1"}}"#,
+                r#"{"type":"PARTIAL","clientId":"phone-01","timestamp":1670000000000,"payload":{"sessionId":"s-1","seq":1,"text":"hel","confidence":0.5}}"#,
+                r#"{"type":"PARTIAL","clientId":"phone-01","timestamp":1670000000100,"payload":{"sessionId":"s-1","seq":2,"text":"help","confidence":0.6}}"#,
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000200,"payload":{"sessionId":"s-1","text":"Help new line","confidence":0.9}}"#,
+            ],
+        )
+        .await;
+        let ack_text = ws.next().await.unwrap().unwrap().into_text().unwrap();
+
+        // A failing extra sink doesn't change the focused app's outcome
+        match protocol::parse_message(&ack_text).unwrap() {
+            protocol::Message::Ack { payload, .. } => {
+                assert_eq!(payload.status.as_deref(), Some("injected"))
+            }
+            other => panic!("expected ACK, got {:?}", other),
+        }
+        assert_eq!(
+            *injected.lock().unwrap(),
+            vec!["hel", "p", "<erase 4>", "Help", "<press NewLine>"]
+        );
+        // Sinks are delivered to in the background
+        for _ in 0..50 {
+            if !sink_log.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(*sink_log.lock().unwrap(), vec!["Help\n"]);
+
+        ws.close(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_final_is_rewritten_by_pipeline() {
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, events) = TestEmitter::new();
+        let (injector, injected) = MockInjector::new();
+
+        let ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        ctx.pipeline.push(Arc::new(crate::rules::RuleEngine::new(
+            crate::rules::default_rules(0),
+        )));
+        let mut ws = serve_and_connect(ctx).await;
+
+        send_all(
+            &mut ws,
+            &[
+                r#"{"type":"PARTIAL","clientId":"phone-01","timestamp":1670000000000,"payload":{"sessionId":"s-1","seq":1,"text":"slash new","confidence":0.5}}"#,
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000100,"payload":{"sessionId":"s-1","text":"slash new project","confidence":0.9}}"#,
+            ],
+        )
+        .await;
+        let _ = ws.next().await.unwrap().unwrap(); // consume FINAL ACK
+
+        assert_eq!(*injected.lock().unwrap(), vec!["/new project"]);
+        {
+            let evts = events.lock().unwrap();
+            // PARTIALs are left alone unless the rule stage opts in
+            assert!(evts.iter().any(
+                |e| matches!(e, ServerEvent::PartialText { text, .. } if text == "slash new")
+            ));
+            assert!(evts.iter().any(
+                |e| matches!(e, ServerEvent::FinalText { text, .. } if text == "/new project")
+            ));
+        }
+
+        ws.close(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_voice_commands_replace_live_typed_words() {
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, _events) = TestEmitter::new();
+        let (injector, injected) = MockInjector::new();
+
+        let ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        ctx.live_typing.set_enabled(true);
+        ctx.voice_commands.set_enabled(true);
+        let mut ws = serve_and_connect(ctx).await;
+
+        send_all(
+            &mut ws,
+            &[
+                r#"{"type":"PTT_START","clientId":"phone-01","payload":{"sessionId":"s-1"}}"#,
+                r#"{"type":"PARTIAL","clientId":"phone-01","timestamp":1670000000000,"payload":{"sessionId":"s-1","seq":1,"text":"hi new","confidence":0.5}}"#,
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000100,"payload":{"sessionId":"s-1","text":"hi new line","confidence":0.9}}"#,
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000200,"payload":{"sessionId":"s-2","text":"scratch that","confidence":0.9}}"#,
+            ],
+        )
+        .await;
+        let _ = ws.next().await.unwrap().unwrap(); // consume FINAL ACK
+        let _ = ws.next().await.unwrap().unwrap(); // consume FINAL ACK
+
+        assert_eq!(
+            *injected.lock().unwrap(),
+            vec!["hi new", "<erase 6>", "hi", "<press NewLine>", "<erase 3>"]
+        );
+
+        ws.close(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_undo_message_erases_last_final() {
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, events) = TestEmitter::new();
+        let (injector, injected) = MockInjector::new();
+
+        let ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        let mut ws = serve_and_connect(ctx).await;
+
+        send_all(
+            &mut ws,
+            &[
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000000,"payload":{"sessionId":"s-1","text":"wrong words","confidence":0.4}}"#,
+                r#"{"type":"UNDO","clientId":"phone-01"}"#,
+            ],
+        )
+        .await;
+        let _ = ws.next().await.unwrap().unwrap(); // consume FINAL ACK
+        let ack = ws.next().await.unwrap().unwrap();
+        assert!(ack.to_text().unwrap().contains(r#""ackType":"UNDO""#));
+
+        assert_eq!(*injected.lock().unwrap(), vec!["wrong words", "<erase 11>"]);
+        assert!(events
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|e| matches!(e, ServerEvent::DictationUndone { chars: 11, .. })));
+
+        ws.close(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_consecutive_finals_are_joined() {
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, _events) = TestEmitter::new();
+        let (injector, injected) = MockInjector::new();
+
+        let ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        ctx.formatter.set_enabled(true);
+        let mut ws = serve_and_connect(ctx).await;
+
+        send_all(
+            &mut ws,
+            &[
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000000,"payload":{"sessionId":"s-1","text":"Hello there.","confidence":0.9}}"#,
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000001000,"payload":{"sessionId":"s-2","text":"how are you","confidence":0.9}}"#,
+            ],
+        )
+        .await;
+        let _ = ws.next().await.unwrap().unwrap(); // consume FINAL ACK
+        let _ = ws.next().await.unwrap().unwrap(); // consume FINAL ACK
+
+        assert_eq!(
+            *injected.lock().unwrap(),
+            vec!["Hello there.", " How are you"]
+        );
+
+        ws.close(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_low_confidence_finals_are_held_or_rejected() {
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, events) = TestEmitter::new();
+        let (injector, injected) = MockInjector::new();
+
+        let mut ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        let gate = Arc::new(ConfidenceGate::new(ConfidencePolicy {
+            auto_inject_at: 0.8,
+            reject_below: 0.3,
+        }));
+        ctx.confidence = Arc::clone(&gate);
+        let mut ws = serve_and_connect(ctx).await;
+
+        send_all(
+            &mut ws,
+            &[
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000000,"payload":{"sessionId":"s-1","text":"maybe this","confidence":0.5}}"#,
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000001000,"payload":{"sessionId":"s-2","text":"noise","confidence":0.1}}"#,
+            ],
+        )
+        .await;
+        let held_ack = ws.next().await.unwrap().unwrap().into_text().unwrap();
+        let rejected_ack = ws.next().await.unwrap().unwrap().into_text().unwrap();
+
+        assert!(held_ack.contains(r#""status":"held""#));
+        assert!(held_ack.contains(r#""heldId":1"#));
+        assert!(rejected_ack.contains(r#""status":"rejected""#));
+        assert!(injected.lock().unwrap().is_empty());
+        assert_eq!(gate.held().len(), 1);
+        {
+            let evts = events.lock().unwrap();
+            assert!(evts.iter().any(
+                |e| matches!(e, ServerEvent::TextHeld { held, .. } if held.text == "maybe this")
+            ));
+            assert!(evts
+                .iter()
+                .any(|e| matches!(e, ServerEvent::TextRejected { text, .. } if text == "noise")));
+        }
+
+        // Confirming from the phone injects the held text
+        send_all(
+            &mut ws,
+            &[r#"{"type":"CONFIRM_HELD","clientId":"phone-01","timestamp":1670000002000,"payload":{"heldId":1,"accept":true}}"#],
+        )
+        .await;
+        let _ = ws.next().await.unwrap().unwrap(); // consume CONFIRM_HELD ACK
+
+        assert_eq!(*injected.lock().unwrap(), vec!["maybe this"]);
+        assert!(gate.held().is_empty());
+
+        ws.close(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_out_of_range_timestamp_is_dropped() {
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, _events) = TestEmitter::new();
+        let (injector, injected) = MockInjector::new();
+        let mut ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        let history = Arc::new(History::in_memory());
+        ctx.history = Arc::clone(&history);
+        let mut ws = serve_and_connect(ctx).await;
+
+        send_all(
+            &mut ws,
+            &[
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":18446744073709551615,"payload":{"sessionId":"s-1","text":"dropped","confidence":0.9}}"#,
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000000,"payload":{"sessionId":"s-2","text":"kept","confidence":0.9}}"#,
+            ],
+        )
+        .await;
+        let ack = ws.next().await.unwrap().unwrap().into_text().unwrap();
+
+        assert!(ack.contains(r#""status":"injected""#));
+        assert_eq!(*injected.lock().unwrap(), vec!["kept"]);
+        let page = history.query(&HistoryQuery::default()).unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.entries[0].speech_ended_at, Some(1670000000000));
+
+        ws.close(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_injection_is_reported() {
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, events) = TestEmitter::new();
+        let injector = Arc::new(crate::injection::testing::MockInjector::failing());
+        let mut ctx = ServerContext::new(registry, injector, Arc::new(emitter));
+        let history = Arc::new(History::in_memory());
+        ctx.history = Arc::clone(&history);
+        ctx.confidence
+            .set_policy(crate::confidence::ConfidencePolicy {
+                auto_inject_at: 0.8,
+                reject_below: 0.3,
+            })
+            .unwrap();
+        let gate = Arc::clone(&ctx.confidence);
+        let mut ws = serve_and_connect(ctx).await;
+
+        send_all(
+            &mut ws,
+            &[
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000000,"payload":{"sessionId":"s-1","text":"lost","confidence":0.9}}"#,
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000001000,"payload":{"sessionId":"s-2","text":"maybe","confidence":0.5}}"#,
+                r#"{"type":"CONFIRM_HELD","clientId":"phone-01","timestamp":1670000002000,"payload":{"heldId":1,"accept":true}}"#,
+            ],
+        )
+        .await;
+        let final_ack = ws.next().await.unwrap().unwrap().into_text().unwrap();
+        let _ = ws.next().await.unwrap().unwrap(); // consume held FINAL ACK
+        let confirm_ack = ws.next().await.unwrap().unwrap().into_text().unwrap();
+
+        assert!(final_ack.contains(r#""status":"failed""#));
+        assert!(confirm_ack.contains(r#""status":"failed""#));
+        // Still held, so it can be confirmed once injection works again
+        assert_eq!(gate.held().len(), 1);
+        let page = history.query(&HistoryQuery::default()).unwrap();
+        let failed = page.entries.iter().find(|e| e.session_id == "s-1").unwrap();
+        assert_eq!(failed.outcome, Some(Outcome::Failed));
+        assert!(!events.lock().unwrap().iter().any(|e| matches!(
+            e,
+            ServerEvent::FinalText { .. } | ServerEvent::HeldTextResolved { .. }
+        )));
+
+        ws.close(None).await.unwrap();
+    }
+
+    #[test]
+    fn test_rejecting_held_text_injects_nothing() {
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, events) = TestEmitter::new();
+        let (injector, injected) = MockInjector::new();
+        let ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        let held = ctx
+            .confidence
+            .hold("phone-01", "s-1", "maybe", 0.5, HoldReason::LowConfidence)
+            .unwrap();
+
+        ctx.resolve_held(held.id, false).unwrap();
+
+        assert!(injected.lock().unwrap().is_empty());
+        assert!(ctx.resolve_held(held.id, true).is_err());
+        assert!(matches!(
+            events.lock().unwrap().last(),
+            Some(ServerEvent::HeldTextResolved {
+                accepted: false,
+                ..
+            })
+        ));
+    }
+
+    /// Injector that takes a while, so concurrent confirms overlap
+    struct SlowInjector(Arc<std::sync::Mutex<Vec<String>>>);
+
+    impl TextInjector for SlowInjector {
+        fn inject(&self, text: &str) -> Result<(), String> {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            self.0.lock().unwrap().push(text.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_concurrent_confirms_inject_once() {
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, _events) = TestEmitter::new();
+        let injected = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let injector = SlowInjector(Arc::clone(&injected));
+        let ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        let held = ctx
+            .confidence
+            .hold("phone-01", "s-1", "maybe", 0.5, HoldReason::LowConfidence)
+            .unwrap();
+
+        // The phone's CONFIRM_HELD and the desktop's confirm race
+        let barrier = std::sync::Barrier::new(2);
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let confirms: Vec<_> = (0..2)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        ctx.resolve_held(held.id, true)
+                    })
+                })
+                .collect();
+            confirms.into_iter().map(|c| c.join().unwrap()).collect()
+        });
+
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+        assert_eq!(*injected.lock().unwrap(), vec!["maybe"]);
+        assert!(ctx.confidence.held().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_review_mode_queues_finals_until_approved() {
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, _events) = TestEmitter::new();
+        let (injector, injected) = MockInjector::new();
+
+        let mut ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        let review = Arc::new(ReviewQueue::in_memory());
+        review.set_enabled(true).unwrap();
+        ctx.review = Arc::clone(&review);
+        let ctx = Arc::new(ctx);
+        let port = free_port();
+        tokio::spawn(serve(port, Arc::clone(&ctx)));
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let url = format!("ws://127.0.0.1:{}", port);
+        let (mut ws, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+
+        send_all(
+            &mut ws,
+            &[
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000000,"payload":{"sessionId":"s-1","text":"first draft","confidence":0.9}}"#,
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000001000,"payload":{"sessionId":"s-2","text":"second","confidence":0.9}}"#,
+            ],
+        )
+        .await;
+        let ack = ws.next().await.unwrap().unwrap().into_text().unwrap();
+        let _ = ws.next().await.unwrap().unwrap(); // consume FINAL ACK
+
+        assert!(ack.contains(r#""status":"pending""#));
+        assert!(injected.lock().unwrap().is_empty());
+
+        let entries = review.entries();
+        review.edit(entries[0].id, "final draft.").unwrap();
+        review.move_to(entries[1].id, 0).unwrap();
+        for entry in review.entries() {
+            ctx.approve_pending(entry.id).unwrap();
+        }
+
+        assert_eq!(*injected.lock().unwrap(), vec!["second", "final draft."]);
+        assert!(review.entries().is_empty());
+
+        ws.close(None).await.unwrap();
+    }
+
+    #[test]
+    fn test_failed_approval_keeps_the_entry_queued() {
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, events) = TestEmitter::new();
+        let (injector, injected) = crate::injection::testing::MockInjector::new();
+        let injector = Arc::new(injector);
+        let mut ctx = ServerContext::new(registry, Arc::clone(&injector) as _, Arc::new(emitter));
+        let review = Arc::new(ReviewQueue::in_memory());
+        ctx.review = Arc::clone(&review);
+        let first = review.push("phone-01", "s-1", "first", 0.9).unwrap();
+        review.push("phone-01", "s-2", "second", 0.9).unwrap();
+
+        injector.fail_next(1);
+        let err = ctx.approve_all_pending().unwrap_err();
+
+        assert!(err.contains("Approved 1, failed 1"));
+        assert_eq!(*injected.lock().unwrap(), vec!["second"]);
+        assert_eq!(review.entries(), vec![first.clone()]);
+        let finals: Vec<String> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|e| match e {
+                ServerEvent::FinalText { text, .. } => Some(text.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(finals, vec!["second"]);
+
+        assert_eq!(ctx.approve_all_pending().unwrap(), 1);
+        assert!(review.entries().is_empty());
+    }
+
+    #[test]
+    fn test_unsaved_approval_is_not_injected_twice() {
+        let path =
+            std::env::temp_dir().join(format!("ptt-review-approve-{}.json", std::process::id()));
+        let tmp = path.with_extension("json.tmp");
+        let _ = std::fs::remove_file(&path);
+        let review = Arc::new(ReviewQueue::open(&path).unwrap());
+        let entry = review.push("phone-01", "s-1", "typed once", 0.9).unwrap();
+
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, _events) = TestEmitter::new();
+        let (injector, injected) = MockInjector::new();
+        let mut ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        ctx.review = Arc::clone(&review);
+
+        // A directory where the temp file goes makes the save fail
+        std::fs::create_dir_all(&tmp).unwrap();
+        ctx.approve_pending(entry.id).unwrap();
+        assert!(ctx.approve_pending(entry.id).is_err());
+        assert_eq!(ctx.approve_all_pending().unwrap(), 0);
+
+        assert_eq!(*injected.lock().unwrap(), vec!["typed once"]);
+        assert!(review.entries().is_empty());
+
+        std::fs::remove_dir(&tmp).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unsaved_review_entry_is_held_instead() {
+        let path = std::env::temp_dir().join(format!("ptt-review-ws-{}.json", std::process::id()));
+        let tmp = path.with_extension("json.tmp");
+        let _ = std::fs::remove_file(&path);
+        let review = Arc::new(ReviewQueue::open(&path).unwrap());
+        review.set_enabled(true).unwrap();
+        // A directory where the temp file goes makes the write fail
+        std::fs::create_dir_all(&tmp).unwrap();
+
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, _events) = TestEmitter::new();
+        let (injector, injected) = MockInjector::new();
+        let mut ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        ctx.review = Arc::clone(&review);
+        let gate = Arc::clone(&ctx.confidence);
+        let mut ws = serve_and_connect(ctx).await;
+
+        send_all(
+            &mut ws,
+            &[
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000000,"payload":{"sessionId":"s-1","text":"keep me","confidence":0.9}}"#,
+            ],
+        )
+        .await;
+        let ack = ws.next().await.unwrap().unwrap().into_text().unwrap();
+
+        assert!(ack.contains(r#""status":"held""#));
+        assert!(ack.contains(r#""heldId":1"#));
+        assert!(ack.contains(r#""error":"Review queue: "#));
+        let held = gate.held();
+        assert_eq!(held.len(), 1);
+        assert_eq!(held[0].text, "keep me");
+        assert_eq!(held[0].reason, HoldReason::ReviewUnavailable);
+        assert!(injected.lock().unwrap().is_empty());
+
+        ws.close(None).await.unwrap();
+        std::fs::remove_dir(&tmp).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sensitive_finals_are_redacted_or_held() {
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, events) = TestEmitter::new();
+        let (injector, injected) = MockInjector::new();
+
+        let mut ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        ctx.redaction = Arc::new(
+            Redactor::new(RedactionConfig {
+                card_numbers: Some(RedactAction::Mask),
+                emails: Some(RedactAction::Confirm),
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+        let history = Arc::new(History::in_memory());
+        ctx.history = Arc::clone(&history);
+        let mut ws = serve_and_connect(ctx).await;
+
+        send_all(
+            &mut ws,
+            &[
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000000,"payload":{"sessionId":"s-1","text":"card 4111 1111 1111 1111","confidence":0.9}}"#,
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000001000,"payload":{"sessionId":"s-2","text":"mail ops@example.com","confidence":0.9}}"#,
+            ],
+        )
+        .await;
+        let _ = ws.next().await.unwrap().unwrap(); // consume FINAL ACK
+        let held_ack = ws.next().await.unwrap().unwrap().into_text().unwrap();
+
+        assert_eq!(*injected.lock().unwrap(), vec!["card **** **** **** 1111"]);
+        assert!(held_ack.contains(r#""status":"held""#));
+        assert!(events.lock().unwrap().iter().any(|e| matches!(
+            e,
+            ServerEvent::TextHeld { held, .. } if held.reason == HoldReason::SensitiveData
+        )));
+        let page = history.query(&HistoryQuery::default()).unwrap();
+        let held = page.entries.iter().find(|e| e.session_id == "s-2").unwrap();
+        assert_eq!(held.final_text.as_deref(), Some("mail ***@*******.***"));
+
+        ws.close(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sensitive_low_confidence_finals_stay_masked() {
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, events) = TestEmitter::new();
+        let (injector, injected) = MockInjector::new();
+
+        let mut ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        ctx.redaction = Arc::new(
+            Redactor::new(RedactionConfig {
+                emails: Some(RedactAction::Confirm),
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+        ctx.confidence
+            .set_policy(ConfidencePolicy {
+                auto_inject_at: 0.8,
+                reject_below: 0.3,
+            })
+            .unwrap();
+        let history = Arc::new(History::in_memory());
+        ctx.history = Arc::clone(&history);
+        let mut ws = serve_and_connect(ctx).await;
+
+        send_all(
+            &mut ws,
+            &[
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000000,"payload":{"sessionId":"s-1","text":"mail ops@example.com","confidence":0.5}}"#,
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000001000,"payload":{"sessionId":"s-2","text":"mail ops@example.com","confidence":0.1}}"#,
+            ],
+        )
+        .await;
+        let held_ack = ws.next().await.unwrap().unwrap().into_text().unwrap();
+        let rejected_ack = ws.next().await.unwrap().unwrap().into_text().unwrap();
+
+        assert!(injected.lock().unwrap().is_empty());
+        assert!(held_ack.contains(r#""status":"held""#));
+        assert!(rejected_ack.contains(r#""status":"rejected""#));
+        assert!(events.lock().unwrap().iter().any(|e| matches!(
+            e,
+            ServerEvent::TextHeld { held, .. } if held.reason == HoldReason::SensitiveData
+        )));
+        let page = history.query(&HistoryQuery::default()).unwrap();
+        for session_id in ["s-1", "s-2"] {
+            let entry = page
+                .entries
+                .iter()
+                .find(|e| e.session_id == session_id)
+                .unwrap();
+            assert_eq!(entry.final_text.as_deref(), Some("mail ***@*******.***"));
+        }
+
+        ws.close(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sessions_are_recorded_in_history() {
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, _events) = TestEmitter::new();
+        let (injector, _injected) = MockInjector::new();
+
+        let mut ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        let history = Arc::new(History::in_memory());
+        ctx.history = Arc::clone(&history);
+        let mut ws = serve_and_connect(ctx).await;
+
+        send_all(
+            &mut ws,
+            &[
+                r#"{"type":"PTT_START","clientId":"phone-01","payload":{"sessionId":"s-1"}}"#,
+                r#"{"type":"PARTIAL","clientId":"phone-01","timestamp":1670000000000,"payload":{"sessionId":"s-1","seq":1,"text":"hel","confidence":0.5}}"#,
+                r#"{"type":"PARTIAL","clientId":"phone-01","timestamp":1670000000100,"payload":{"sessionId":"s-1","seq":2,"text":"hello","confidence":0.6}}"#,
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000200,"payload":{"sessionId":"s-1","text":"hello","confidence":0.9}}"#,
+                r#"{"type":"PTT_START","clientId":"phone-01","payload":{"sessionId":"s-2"}}"#,
+            ],
+        )
+        .await;
+        let _ = ws.next().await.unwrap().unwrap(); // consume FINAL ACK
+        ws.close(None).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let page = history.query(&HistoryQuery::default()).unwrap();
+        assert_eq!(page.total, 2);
+        let finished = page.entries.iter().find(|e| e.session_id == "s-1").unwrap();
+        assert_eq!(finished.device_model, "Galaxy S23");
+        assert_eq!(finished.engine, "Google");
+        assert_eq!(finished.partial_count, 2);
+        assert_eq!(finished.final_text.as_deref(), Some("hello"));
+        assert_eq!(finished.outcome, Some(Outcome::Injected));
+        // Disconnected before its FINAL
+        let open = page.entries.iter().find(|e| e.session_id == "s-2").unwrap();
+        assert_eq!(open.outcome, Some(Outcome::Abandoned));
+    }
+
+    #[tokio::test]
+    async fn test_finals_feed_usage_statistics() {
+        let registry = Arc::new(Mutex::new(ClientRegistry::new(30)));
+        let (emitter, _events) = TestEmitter::new();
+        let (injector, _injected) = MockInjector::new();
+
+        let mut ctx = ServerContext::new(registry, Arc::new(injector), Arc::new(emitter));
+        let stats = Arc::new(Statistics::in_memory());
+        ctx.stats = Arc::clone(&stats);
+        let mut ws = serve_and_connect(ctx).await;
+
+        send_all(
+            &mut ws,
+            &[
+                r#"{"type":"PTT_START","clientId":"phone-01","payload":{"sessionId":"s-1"}}"#,
+                r#"{"type":"PARTIAL","clientId":"phone-01","timestamp":1670000000000,"payload":{"sessionId":"s-1","seq":1,"text":"hello","confidence":0.5}}"#,
+                r#"{"type":"FINAL","clientId":"phone-01","timestamp":1670000000200,"payload":{"sessionId":"s-1","text":"hello there world","confidence":0.8}}"#,
+            ],
+        )
+        .await;
+        let _ = ws.next().await.unwrap().unwrap(); // consume FINAL ACK
+
+        let rows = stats.report(&StatsQuery {
+            group_by: GroupBy::Engine,
+            from_day: None,
+            to_day: None,
+            per_day: false,
+        });
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].key, "Google");
+        assert_eq!(rows[0].device_models, vec!["Galaxy S23"]);
+        assert_eq!(rows[0].sessions, 1);
+        assert_eq!(rows[0].words, 3);
+        assert_eq!(rows[0].partials, 1);
+        assert_eq!(rows[0].injection_failures, 0);
+        assert!(rows[0].avg_latency_ms.is_some());
+    }
 }
@@ -0,0 +1,217 @@
+//! Load and soak tests: many simulated phones against `start_server`, with a
+//! counting injector and emitter in place of the OS and the frontend.
+//!
+//! The regular run is small enough for CI. The soak test is ignored by default:
+//!
+//!     PTT_LOAD_CLIENTS=200 PTT_LOAD_UTTERANCES=50 \
+//!         cargo test --test load -- --ignored --nocapture
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use desktop_lib::client_registry::ClientRegistry;
+use desktop_lib::injection::TextInjector;
+use desktop_lib::simulator::{self, ScriptStep, SimOptions};
+use desktop_lib::ws_server::{self, EventEmitter, ServerEvent};
+
+/// Remembers when each text was injected
+#[derive(Default)]
+struct CountingInjector {
+    injected: Mutex<HashMap<String, Instant>>,
+}
+
+impl TextInjector for CountingInjector {
+    fn inject(&self, text: &str) -> Result<(), String> {
+        self.injected
+            .lock()
+            .unwrap()
+            .insert(normalize(text), Instant::now());
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct CountingEmitter {
+    partials: AtomicUsize,
+    finals: AtomicUsize,
+    other: AtomicUsize,
+}
+
+impl EventEmitter for CountingEmitter {
+    fn emit(&self, event: ServerEvent) {
+        let counter = match event {
+            ServerEvent::PartialText { .. } => &self.partials,
+            ServerEvent::FinalText { .. } => &self.finals,
+            _ => &self.other,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Undo the join formatter's spacing and capitalization
+fn normalize(text: &str) -> String {
+    text.trim().to_lowercase()
+}
+
+struct LoadConfig {
+    clients: usize,
+    utterances: usize,
+    partial_interval: Duration,
+}
+
+struct LoadReport {
+    finals_sent: usize,
+    finals_injected: usize,
+    partials_sent: usize,
+    partial_events: usize,
+    elapsed: Duration,
+    /// FINAL send → injection, sorted
+    latencies: Vec<Duration>,
+}
+
+impl LoadReport {
+    fn percentile(&self, p: f64) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = ((self.latencies.len() as f64 * p).ceil() as usize).max(1);
+        self.latencies[rank.min(self.latencies.len()) - 1]
+    }
+
+    fn print(&self, config: &LoadConfig) {
+        println!(
+            "{} clients × {} utterances in {:.1?}: {} FINALs ({} injected), {} PARTIALs, {} PARTIAL events",
+            config.clients,
+            config.utterances,
+            self.elapsed,
+            self.finals_sent,
+            self.finals_injected,
+            self.partials_sent,
+            self.partial_events
+        );
+        println!(
+            "FINAL → injection: p50 {:.1?}  p90 {:.1?}  p99 {:.1?}  max {:.1?}",
+            self.percentile(0.5),
+            self.percentile(0.9),
+            self.percentile(0.99),
+            self.latencies.last().copied().unwrap_or_default()
+        );
+    }
+}
+
+fn env_or(name: &str, default: usize) -> usize {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// Every utterance is unique so injections can be matched to FINALs
+fn script(client: usize, utterances: usize) -> Vec<ScriptStep> {
+    (0..utterances)
+        .map(|n| {
+            ScriptStep::Say(format!(
+                "client {client} note {n} the quarterly numbers look fine to me"
+            ))
+        })
+        .collect()
+}
+
+async fn run_load(config: &LoadConfig) -> LoadReport {
+    let port = free_port();
+    let injector = Arc::new(CountingInjector::default());
+    let emitter = Arc::new(CountingEmitter::default());
+    let registry = Arc::new(tokio::sync::Mutex::new(ClientRegistry::new(30)));
+    tokio::spawn(ws_server::start_server(
+        port,
+        registry,
+        injector.clone(),
+        emitter.clone(),
+    ));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let url = format!("ws://127.0.0.1:{port}");
+    let started = Instant::now();
+    let phones: Vec<_> = (0..config.clients)
+        .map(|client| {
+            let url = url.clone();
+            let script = script(client, config.utterances);
+            let options = SimOptions {
+                client_id: format!("load-{client:04}"),
+                partial_interval: config.partial_interval,
+                heartbeat: Duration::from_secs(1),
+                seed: client as u64 + 1,
+                ..SimOptions::default()
+            };
+            tokio::spawn(async move { simulator::run(&url, &script, &options).await })
+        })
+        .collect();
+
+    let mut finals = Vec::new();
+    let mut partials_sent = 0;
+    for phone in phones {
+        let report = phone.await.unwrap().unwrap();
+        partials_sent += report.partials;
+        finals.extend(report.finals);
+    }
+    let elapsed = started.elapsed();
+
+    let injected = injector.injected.lock().unwrap();
+    let mut latencies: Vec<Duration> = finals
+        .iter()
+        .filter_map(|sent| {
+            let at = injected.get(&normalize(&sent.text))?;
+            Some(at.saturating_duration_since(sent.at))
+        })
+        .collect();
+    latencies.sort();
+    LoadReport {
+        finals_sent: finals.len(),
+        finals_injected: latencies.len(),
+        partials_sent,
+        partial_events: emitter.partials.load(Ordering::Relaxed),
+        elapsed,
+        latencies,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_many_clients_all_finals_injected() {
+    let config = LoadConfig {
+        clients: env_or("PTT_LOAD_CLIENTS", 20),
+        utterances: env_or("PTT_LOAD_UTTERANCES", 5),
+        partial_interval: Duration::from_millis(10),
+    };
+    let report = run_load(&config).await;
+    report.print(&config);
+
+    assert_eq!(report.finals_sent, config.clients * config.utterances);
+    assert_eq!(report.finals_injected, report.finals_sent);
+    assert_eq!(report.partial_events, report.partials_sent);
+    // Generous bound for shared CI machines
+    assert!(report.percentile(0.99) < Duration::from_secs(2));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+#[ignore = "soak test; run with --ignored"]
+async fn soak_many_clients() {
+    let config = LoadConfig {
+        clients: env_or("PTT_LOAD_CLIENTS", 100),
+        utterances: env_or("PTT_LOAD_UTTERANCES", 30),
+        partial_interval: Duration::from_millis(env_or("PTT_LOAD_INTERVAL_MS", 100) as u64),
+    };
+    let report = run_load(&config).await;
+    report.print(&config);
+
+    assert_eq!(report.finals_injected, report.finals_sent);
+}
@@ -0,0 +1,178 @@
+//! Property tests for the wire protocol: every `Message` variant survives
+//! serialize → parse unchanged, and `parse_message` never panics.
+
+use desktop_lib::codec::{Codec, Implied};
+use desktop_lib::protocol::{
+    parse_message, serialize_message, AckPayload, ConfirmHeldPayload, FinalPayload, HelloPayload,
+    Message, PartialPayload, PttStartPayload,
+};
+use proptest::prelude::*;
+
+/// Mostly short Unicode, sometimes tens of kilobytes
+fn text() -> impl Strategy<Value = String> {
+    prop_oneof![
+        9 => any::<String>(),
+        1 => prop::collection::vec(any::<char>(), 10_000..70_000)
+            .prop_map(|chars| chars.into_iter().collect()),
+    ]
+}
+
+/// Short decimals, which JSON represents exactly
+fn confidence() -> impl Strategy<Value = f64> {
+    (0u32..=10_000).prop_map(|n| f64::from(n) / 10_000.0)
+}
+
+fn message() -> impl Strategy<Value = Message> {
+    prop_oneof![
+        (text(), text(), text(), prop::collection::vec(text(), 0..4)).prop_map(
+            |(client_id, device_model, engine, capabilities)| Message::Hello {
+                client_id,
+                payload: HelloPayload {
+                    device_model,
+                    engine,
+                    capabilities,
+                },
+            }
+        ),
+        (text(), text()).prop_map(|(client_id, session_id)| Message::PttStart {
+            client_id,
+            payload: PttStartPayload { session_id },
+        }),
+        (
+            text(),
+            any::<u64>(),
+            text(),
+            any::<u64>(),
+            text(),
+            confidence()
+        )
+            .prop_map(
+                |(client_id, timestamp, session_id, seq, text, confidence)| Message::Partial {
+                    client_id,
+                    timestamp,
+                    payload: PartialPayload {
+                        session_id,
+                        seq,
+                        text,
+                        confidence,
+                    },
+                }
+            ),
+        (text(), any::<u64>(), text(), text(), confidence()).prop_map(
+            |(client_id, timestamp, session_id, text, confidence)| Message::Final {
+                client_id,
+                timestamp,
+                payload: FinalPayload {
+                    session_id,
+                    text,
+                    confidence,
+                },
+            }
+        ),
+        text().prop_map(|client_id| Message::Heartbeat { client_id }),
+        text().prop_map(|client_id| Message::Undo { client_id }),
+        (text(), any::<u64>(), any::<bool>()).prop_map(|(client_id, held_id, accept)| {
+            Message::ConfirmHeld {
+                client_id,
+                payload: ConfirmHeldPayload { held_id, accept },
+            }
+        }),
+        (
+            text(),
+            text(),
+            prop::option::of(text()),
+            prop::option::of(any::<u64>()),
+            prop::option::of(text()),
+            prop::option::of(text())
+        )
+            .prop_map(|(client_id, ack_type, status, held_id, error, encoding)| {
+                Message::Ack {
+                    client_id,
+                    payload: AckPayload {
+                        ack_type,
+                        status,
+                        held_id,
+                        error,
+                        encoding,
+                    },
+                }
+            }),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn roundtrip_every_variant(message in message()) {
+        let json = serialize_message(&message).unwrap();
+        prop_assert_eq!(parse_message(&json).unwrap(), message);
+    }
+
+    /// Binary frames round-trip with and without the ids a connection implies
+    #[test]
+    fn binary_roundtrip_every_variant(message in message(), session_id in text()) {
+        let client_id = match &message {
+            Message::Hello { client_id, .. }
+            | Message::PttStart { client_id, .. }
+            | Message::Partial { client_id, .. }
+            | Message::Final { client_id, .. }
+            | Message::Heartbeat { client_id }
+            | Message::Undo { client_id }
+            | Message::ConfirmHeld { client_id, .. }
+            | Message::Ack { client_id, .. } => client_id.clone(),
+        };
+        let implied = Implied { client_id: Some(client_id), session_id: Some(session_id) };
+        for codec in [Codec::Cbor, Codec::MessagePack] {
+            let bytes = codec.encode(&message).unwrap();
+            prop_assert_eq!(&codec.decode(&bytes).unwrap(), &message);
+            let bytes = codec.encode_with(&message, &implied).unwrap();
+            prop_assert_eq!(&codec.decode_with(&bytes, &implied).unwrap(), &message);
+        }
+    }
+
+    #[test]
+    fn parse_never_panics(input in any::<String>()) {
+        let _ = parse_message(&input);
+    }
+
+    /// Valid messages with one byte changed: errors are fine, panics are not
+    #[test]
+    fn parse_survives_corrupted_messages(
+        message in message(),
+        position in any::<prop::sample::Index>(),
+        byte in any::<u8>(),
+    ) {
+        let mut bytes = serialize_message(&message).unwrap().into_bytes();
+        let index = position.index(bytes.len());
+        bytes[index] = byte;
+        let _ = parse_message(&String::from_utf8_lossy(&bytes));
+    }
+
+    /// Any JSON object with a known type tag either parses or is rejected
+    #[test]
+    fn parse_survives_wrong_field_types(
+        kind in prop::sample::select(vec![
+            "HELLO", "PTT_START", "PARTIAL", "FINAL", "HEARTBEAT", "UNDO", "CONFIRM_HELD", "ACK",
+        ]),
+        fields in prop::collection::vec(
+            (
+                prop::sample::select(vec![
+                    "clientId", "timestamp", "payload", "sessionId", "seq", "text", "confidence",
+                ]),
+                prop_oneof![
+                    Just(serde_json::Value::Null),
+                    any::<bool>().prop_map(serde_json::Value::from),
+                    any::<i64>().prop_map(serde_json::Value::from),
+                    any::<String>().prop_map(serde_json::Value::from),
+                ],
+            ),
+            0..6,
+        ),
+    ) {
+        let mut object = serde_json::Map::new();
+        object.insert("type".to_string(), kind.into());
+        for (key, value) in fields {
+            object.insert(key.to_string(), value);
+        }
+        let _ = parse_message(&serde_json::Value::Object(object).to_string());
+    }
+}
@@ -129,4 +129,56 @@ describe("applyEvent", () => {
       expect(next.size).toBe(0);
     }
   });
+
+  it("leaves client state untouched on ClipboardCopied", () => {
+    const client = createClientState("c1", "Pixel 8");
+    const state = stateWith(client);
+    const event: ServerEvent = { kind: "ClipboardCopied", text: "copied" };
+
+    const next = applyEvent(state, event);
+
+    expect(next).toBe(state);
+  });
+
+  it("drops the last final text on DictationUndone", () => {
+    const client = {
+      ...createClientState("c1", "Pixel 8"),
+      finalTexts: ["kept", "wrong words"],
+    };
+    const event: ServerEvent = {
+      kind: "DictationUndone",
+      client_id: "c1",
+      chars: 11,
+    };
+
+    const next = applyEvent(stateWith(client), event);
+
+    expect(next.get("c1")!.finalTexts).toEqual(["kept"]);
+  });
+
+  it("ends the session without a final text on TextHeld", () => {
+    const client = {
+      ...createClientState("c1", "Pixel 8"),
+      currentSession: "s-1",
+      partialText: "maybe",
+    };
+    const event: ServerEvent = {
+      kind: "TextHeld",
+      client_id: "c1",
+      held: {
+        id: 1,
+        clientId: "c1",
+        sessionId: "s-1",
+        text: "maybe this",
+        confidence: 0.5,
+        reason: "low-confidence",
+      },
+    };
+
+    const next = applyEvent(stateWith(client), event);
+
+    expect(next.get("c1")!.currentSession).toBeNull();
+    expect(next.get("c1")!.partialText).toBe("");
+    expect(next.get("c1")!.finalTexts).toEqual([]);
+  });
 });
@@ -1,3 +1,6 @@
+// Wire protocol types, generated from the Rust definitions
+export * from "./protocol.generated";
+
 export interface ClientConnectedEvent {
   kind: "ClientConnected";
   client_id: string;
@@ -32,12 +35,201 @@ export interface PttStartedEvent {
   session_id: string;
 }
 
+export interface ClipboardCopiedEvent {
+  kind: "ClipboardCopied";
+  text: string;
+}
+
+export interface DictationUndoneEvent {
+  kind: "DictationUndone";
+  client_id: string;
+  chars: number;
+}
+
+export type HoldReason =
+  | "low-confidence"
+  | "sensitive-data"
+  | "review-unavailable";
+
+export interface HeldText {
+  id: number;
+  clientId: string;
+  sessionId: string;
+  text: string;
+  confidence: number;
+  reason: HoldReason;
+}
+
+export interface TextHeldEvent {
+  kind: "TextHeld";
+  client_id: string;
+  held: HeldText;
+}
+
+export interface TextRejectedEvent {
+  kind: "TextRejected";
+  client_id: string;
+  session_id: string;
+  text: string;
+  confidence: number;
+}
+
+export interface HeldTextResolvedEvent {
+  kind: "HeldTextResolved";
+  client_id: string;
+  id: number;
+  accepted: boolean;
+}
+
+export interface PendingEntry {
+  id: number;
+  clientId: string;
+  sessionId: string;
+  text: string;
+  confidence: number;
+  queuedAt: number;
+}
+
+export interface PendingQueuedEvent {
+  kind: "PendingQueued";
+  client_id: string;
+  entry: PendingEntry;
+}
+
 export type ServerEvent =
   | ClientConnectedEvent
   | ClientDisconnectedEvent
   | PartialTextEvent
   | FinalTextEvent
-  | PttStartedEvent;
+  | PttStartedEvent
+  | ClipboardCopiedEvent
+  | DictationUndoneEvent
+  | TextHeldEvent
+  | TextRejectedEvent
+  | HeldTextResolvedEvent
+  | PendingQueuedEvent;
+
+export type InjectionMode = "auto" | "clipboard-only";
+
+export type VoiceCommand =
+  | "new-line"
+  | "new-paragraph"
+  | "tab"
+  | "press-enter"
+  | "select-all"
+  | "delete-last-dictation";
+
+export interface ConfidencePolicy {
+  autoInjectAt: number;
+  rejectBelow: number;
+}
+
+export type RedactAction = "mask" | "drop" | "confirm";
+
+// `null` turns a detector off
+export interface RedactionConfig {
+  cardNumbers: RedactAction | null;
+  emails: RedactAction | null;
+  phoneNumbers: RedactAction | null;
+  words: RedactAction | null;
+  wordList: string[];
+}
+
+export interface VocabularyEntry {
+  term: string;
+  aliases: string[];
+}
+
+export type Outcome =
+  | "injected"
+  | "pending"
+  | "held"
+  | "rejected"
+  | "failed"
+  | "abandoned";
+
+export interface HistoryEntry {
+  id: number;
+  clientId: string;
+  deviceModel: string;
+  engine: string;
+  sessionId: string;
+  startedAt: number;
+  endedAt: number | null;
+  // Protocol timestamps (phone clock) of the first PARTIAL and the FINAL
+  speechStartedAt: number | null;
+  speechEndedAt: number | null;
+  partialCount: number;
+  finalText: string | null;
+  confidence: number | null;
+  outcome: Outcome | null;
+}
+
+export interface HistoryQuery {
+  text?: string;
+  clientId?: string;
+  offset?: number;
+  limit?: number;
+}
+
+export interface HistoryPage {
+  entries: HistoryEntry[];
+  total: number;
+}
+
+// Sessions to export; every filter is optional
+export interface HistorySelection {
+  ids?: number[] | null;
+  // Unix ms bounds on startedAt, inclusive
+  from?: number | null;
+  to?: number | null;
+  clientId?: string | null;
+  text?: string | null;
+}
+
+export type ExportFormat = "markdown" | "jsonl" | "csv" | "srt" | "vtt";
+
+export interface Retention {
+  maxEntries: number | null;
+  maxAgeDays: number | null;
+}
+
+export type StatsGroupBy = "client" | "engine";
+
+// Day bounds are inclusive YYYY-MM-DD strings
+export interface StatsQuery {
+  groupBy: StatsGroupBy;
+  fromDay?: string | null;
+  toDay?: string | null;
+  perDay?: boolean;
+}
+
+export interface StatsRow {
+  // Set only for perDay queries
+  day: string | null;
+  // Client id or engine name, depending on groupBy
+  key: string;
+  deviceModels: string[];
+  // Every PTT_START, including abandoned sessions
+  sessions: number;
+  // Sessions that ended without a FINAL
+  abandoned: number;
+  words: number;
+  partials: number;
+  injectionFailures: number;
+  avgConfidence: number | null;
+  p50Confidence: number | null;
+  p90Confidence: number | null;
+  avgLatencyMs: number | null;
+  p50LatencyMs: number | null;
+  p90LatencyMs: number | null;
+}
+
+export interface CommandPhrase {
+  phrase: string;
+  locale: string;
+  command: VoiceCommand;
+}
 
 export interface ClientState {
   clientId: string;
@@ -75,6 +267,9 @@ export function applyEvent(
     return next;
   }
 
+  // Not tied to a client: clipboard-only notifications carry no client state
+  if (event.kind === "ClipboardCopied") return state;
+
   const existing = state.get(event.client_id);
   if (!existing) return state;
 
@@ -101,6 +296,24 @@ export function applyEvent(
         currentSession: event.session_id,
       });
       break;
+    case "DictationUndone":
+      next.set(event.client_id, {
+        ...existing,
+        finalTexts: existing.finalTexts.slice(0, -1),
+      });
+      break;
+    case "TextHeld":
+    case "TextRejected":
+    case "PendingQueued":
+      // The session ended without injecting; accepted text arrives as FinalText
+      next.set(event.client_id, {
+        ...existing,
+        partialText: "",
+        currentSession: null,
+      });
+      break;
+    case "HeldTextResolved":
+      return state;
   }
 
   return next;
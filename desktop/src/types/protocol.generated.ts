@@ -0,0 +1,106 @@
+// Generated from src-tauri/src/protocol.rs by `cargo run --bin ptt-schema`.
+// Do not edit; change the Rust types and regenerate.
+
+export interface AckPayload {
+  ackType: string;
+  // HELLO only: binary encoding ("CBOR" or "MSGPACK") the desktop accepts
+  // and answers in from now on; absent means JSON only
+  encoding?: string | null;
+  // FINAL only: why the FINAL failed or fell back from the review queue
+  // to being held
+  error?: string | null;
+  // FINAL only: queue id when the text is held for confirmation
+  heldId?: number | null;
+  // FINAL: "injected", "pending" (review queue), "held", "rejected" or
+  // "failed" (injection error); CONFIRM_HELD: "failed" when nothing happened
+  status?: string | null;
+}
+
+export interface ConfirmHeldPayload {
+  heldId: number;
+  // true injects the held text, false drops it
+  accept: boolean;
+}
+
+export interface FinalPayload {
+  sessionId: string;
+  text: string;
+  confidence: number;
+}
+
+export interface HelloPayload {
+  deviceModel: string;
+  engine: string;
+  capabilities: string[];
+}
+
+export interface PartialPayload {
+  sessionId: string;
+  seq: number;
+  text: string;
+  confidence: number;
+}
+
+export interface PttStartPayload {
+  sessionId: string;
+}
+
+export interface HelloMessage {
+  type: "HELLO";
+  clientId: string;
+  payload: HelloPayload;
+}
+
+export interface PttStartMessage {
+  type: "PTT_START";
+  clientId: string;
+  payload: PttStartPayload;
+}
+
+export interface PartialMessage {
+  type: "PARTIAL";
+  clientId: string;
+  timestamp: number;
+  payload: PartialPayload;
+}
+
+export interface FinalMessage {
+  type: "FINAL";
+  clientId: string;
+  timestamp: number;
+  payload: FinalPayload;
+}
+
+export interface HeartbeatMessage {
+  type: "HEARTBEAT";
+  clientId: string;
+}
+
+// Erase what this client's last FINAL inserted
+export interface UndoMessage {
+  type: "UNDO";
+  clientId: string;
+}
+
+// Accept or drop a FINAL the desktop held for confirmation
+export interface ConfirmHeldMessage {
+  type: "CONFIRM_HELD";
+  clientId: string;
+  payload: ConfirmHeldPayload;
+}
+
+export interface AckMessage {
+  type: "ACK";
+  clientId: string;
+  payload: AckPayload;
+}
+
+export type ProtocolMessage =
+  | HelloMessage
+  | PttStartMessage
+  | PartialMessage
+  | FinalMessage
+  | HeartbeatMessage
+  | UndoMessage
+  | ConfirmHeldMessage
+  | AckMessage;